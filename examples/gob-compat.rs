@@ -0,0 +1,50 @@
+//! Compares the type definitions in two gob streams, e.g. reference
+//! outputs of the old and new version of a program, and reports
+//! whether they can interoperate.
+//!
+//! Exits with status 0 if the definitions are compatible in both
+//! directions, 1 if they are not, and 2 on errors.
+//!
+//! ```text
+//! cargo run --example gob-compat -- OLD.gob NEW.gob
+//! ```
+
+extern crate gob;
+
+use std::env;
+use std::fs::File;
+use std::io::BufReader;
+use std::process;
+
+use gob::de::TypeDefinitions;
+use gob::{compat, Error, StreamDeserializer};
+
+fn read_type_definitions(path: &str) -> Result<TypeDefinitions, Error> {
+    let file = File::open(path)?;
+    let mut stream = StreamDeserializer::new(BufReader::new(file));
+    while stream.skip()? {}
+    Ok(stream.type_definitions().clone())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} OLD.gob NEW.gob", args[0]);
+        process::exit(2);
+    }
+
+    let load = |path: &str| {
+        read_type_definitions(path).unwrap_or_else(|err| {
+            eprintln!("{}: {}", path, err);
+            process::exit(2);
+        })
+    };
+    let old = load(&args[1]);
+    let new = load(&args[2]);
+
+    let report = compat::check(&old, &new);
+    print!("{}", report);
+    if report.compatibility() != compat::Compatibility::Both {
+        process::exit(1);
+    }
+}
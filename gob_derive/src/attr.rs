@@ -0,0 +1,249 @@
+//! Parsing of the `#[gob(...)]` attributes, and of the `#[serde(...)]`
+//! attributes which affect the serialized form of a type.
+
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Expr, LitStr, Token};
+
+use case::RenameRule;
+
+#[derive(Default)]
+pub struct Container {
+    /// `#[gob(name = "...")]`
+    pub gob_name: Option<String>,
+    /// `#[serde(rename = "...")]`
+    pub serde_name: Option<String>,
+    /// `#[serde(rename_all = "...")]`
+    pub rename_all: Option<RenameRule>,
+    /// `#[gob(repr = "int")]`
+    pub repr_int: bool,
+    /// `#[serde(tag = "...")]`
+    pub tag: Option<String>,
+    /// `#[serde(content = "...")]`
+    pub content: Option<String>,
+    /// `#[serde(into = "...")]`
+    pub into: Option<String>,
+    /// `#[serde(try_from = "...")]`
+    pub try_from: Option<String>,
+}
+
+impl Container {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Container> {
+        let mut container = Container::default();
+        for attr in attrs {
+            if attr.path().is_ident("gob") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        container.gob_name = Some(parse_lit_str(&meta)?);
+                        Ok(())
+                    } else if meta.path.is_ident("repr") {
+                        if parse_lit_str(&meta)? != "int" {
+                            return Err(meta.error("the only supported representation is \"int\""));
+                        }
+                        container.repr_int = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("unknown gob container attribute"))
+                    }
+                })?;
+            } else if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        container.serde_name = parse_serialize_name(&meta)?;
+                    } else if meta.path.is_ident("rename_all") {
+                        if let Some(rule) = parse_serialize_name(&meta)? {
+                            container.rename_all = Some(
+                                RenameRule::from_str(&rule)
+                                    .ok_or_else(|| meta.error("unknown rename rule"))?,
+                            );
+                        }
+                    } else if meta.path.is_ident("tag") {
+                        container.tag = Some(parse_lit_str(&meta)?);
+                    } else if meta.path.is_ident("content") {
+                        container.content = Some(parse_lit_str(&meta)?);
+                    } else if meta.path.is_ident("into") {
+                        container.into = Some(parse_lit_str(&meta)?);
+                    } else if meta.path.is_ident("try_from") {
+                        container.try_from = Some(parse_lit_str(&meta)?);
+                    } else if meta.path.is_ident("untagged") || meta.path.is_ident("transparent") {
+                        return Err(meta.error("this serde representation is not supported by gob"));
+                    } else {
+                        skip_meta(&meta)?;
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(container)
+    }
+}
+
+/// The Go interface an opaque field's type marshals itself through.
+#[derive(Clone, Copy)]
+pub enum Opaque {
+    /// `encoding.BinaryMarshaler`
+    Binary,
+    /// `gob.GobEncoder`
+    Gob,
+    /// `encoding.TextMarshaler`
+    Text,
+}
+
+#[derive(Default)]
+pub struct Field {
+    /// `#[gob(name = "...")]`, the Go type name of an opaque field
+    pub gob_name: Option<String>,
+    /// `#[gob(opaque)]` or `#[gob(opaque = "...")]`
+    pub opaque: Option<Opaque>,
+    /// `#[gob(interface)]`
+    pub interface: bool,
+    /// `#[gob(skip)]`
+    pub skip: bool,
+    /// `#[serde(rename = "...")]`
+    pub serde_name: Option<String>,
+    /// `#[serde(skip)]` or `#[serde(skip_serializing)]`
+    pub serde_skip: bool,
+    /// `#[serde(flatten)]`
+    pub flatten: bool,
+}
+
+impl Field {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Field> {
+        let mut field = Field::default();
+        for attr in attrs {
+            if attr.path().is_ident("gob") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("name") {
+                        field.gob_name = Some(parse_lit_str(&meta)?);
+                    } else if meta.path.is_ident("go_name") {
+                        return Err(meta.error(
+                            "the Go name is the serde name, set it with `#[serde(rename = \"...\")]`",
+                        ));
+                    } else if meta.path.is_ident("opaque") {
+                        field.opaque = Some(if meta.input.peek(Token![=]) {
+                            match &*parse_lit_str(&meta)? {
+                                "binary" => Opaque::Binary,
+                                "gob" => Opaque::Gob,
+                                "text" => Opaque::Text,
+                                _ => {
+                                    return Err(
+                                        meta.error("expected \"binary\", \"gob\" or \"text\"")
+                                    )
+                                }
+                            }
+                        } else {
+                            Opaque::Binary
+                        });
+                    } else if meta.path.is_ident("interface") {
+                        field.interface = true;
+                    } else if meta.path.is_ident("skip") {
+                        field.skip = true;
+                    } else {
+                        return Err(meta.error("unknown gob field attribute"));
+                    }
+                    Ok(())
+                })?;
+            } else if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        field.serde_name = parse_serialize_name(&meta)?;
+                    } else if meta.path.is_ident("skip") || meta.path.is_ident("skip_serializing") {
+                        field.serde_skip = true;
+                    } else if meta.path.is_ident("flatten") {
+                        field.flatten = true;
+                    } else {
+                        skip_meta(&meta)?;
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+
+        if field.opaque.is_some() as u8 + field.interface as u8 + field.skip as u8 > 1 {
+            return Err(syn::Error::new_spanned(
+                &attrs[0],
+                "only one of `opaque`, `interface` and `skip` can be used",
+            ));
+        }
+        if field.flatten && (field.opaque.is_some() || field.interface || field.skip) {
+            return Err(syn::Error::new_spanned(
+                &attrs[0],
+                "gob field attributes cannot be used on flattened fields",
+            ));
+        }
+        if field.gob_name.is_some() && field.opaque.is_none() {
+            return Err(syn::Error::new_spanned(
+                &attrs[0],
+                "`name` can only be set for `opaque` fields",
+            ));
+        }
+        Ok(field)
+    }
+}
+
+#[derive(Default)]
+pub struct Variant {
+    /// `#[serde(rename = "...")]`
+    pub serde_name: Option<String>,
+}
+
+impl Variant {
+    pub fn from_attrs(attrs: &[Attribute]) -> syn::Result<Variant> {
+        let mut variant = Variant::default();
+        for attr in attrs {
+            if attr.path().is_ident("gob") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("go_name") {
+                        Err(meta.error(
+                            "the Go name is the serde name, set it with `#[serde(rename = \"...\")]`",
+                        ))
+                    } else {
+                        Err(meta.error("unknown gob variant attribute"))
+                    }
+                })?;
+            } else if attr.path().is_ident("serde") {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        variant.serde_name = parse_serialize_name(&meta)?;
+                    } else {
+                        skip_meta(&meta)?;
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(variant)
+    }
+}
+
+fn parse_lit_str(meta: &ParseNestedMeta) -> syn::Result<String> {
+    let lit: LitStr = meta.value()?.parse()?;
+    Ok(lit.value())
+}
+
+/// Parses `rename = "..."` as well as
+/// `rename(serialize = "...", deserialize = "...")`.
+fn parse_serialize_name(meta: &ParseNestedMeta) -> syn::Result<Option<String>> {
+    if meta.input.peek(Token![=]) {
+        return parse_lit_str(meta).map(Some);
+    }
+    let mut name = None;
+    meta.parse_nested_meta(|inner| {
+        if inner.path.is_ident("serialize") {
+            name = Some(parse_lit_str(&inner)?);
+        } else {
+            skip_meta(&inner)?;
+        }
+        Ok(())
+    })?;
+    Ok(name)
+}
+
+/// Consumes an attribute which does not affect the schema.
+fn skip_meta(meta: &ParseNestedMeta) -> syn::Result<()> {
+    if meta.input.peek(Token![=]) {
+        meta.value()?.parse::<Expr>()?;
+    } else if meta.input.peek(syn::token::Paren) {
+        meta.parse_nested_meta(|inner| skip_meta(&inner))?;
+    }
+    Ok(())
+}
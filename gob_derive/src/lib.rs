@@ -0,0 +1,710 @@
+//! `#[derive(GobSchema)]`, which registers a type with a `gob::ser::Schema`.
+//!
+//! The derive implements both `gob::GobSchema` and `SchemaSerialize`.
+//! Field types are registered through `gob::GobSchema` where it is
+//! implemented, which covers types such as `Arc<T>` and those of some
+//! other crates, and through `SchemaSerialize` otherwise. Type parameters
+//! are required to implement `gob::GobSchema`.
+//!
+//! The derived schema follows the serde attributes which affect how a
+//! type is serialized (`rename`, `rename_all`, `skip` and
+//! `skip_serializing`). Fields and variants are sent under their serde
+//! names, as serde decodes them under those, so Go names are set with
+//! `#[serde(rename = "...")]`. Some gob specific attributes are supported:
+//!
+//! - `#[gob(name = "...")]` on a container sets the Go type name sent in
+//!   the type definition.
+//! - `#[gob(opaque)]` on a field declares it as a Go type which marshals
+//!   itself through `encoding.BinaryMarshaler`. `#[gob(opaque = "gob")]`
+//!   is for types implementing `gob.GobEncoder`, such as `time.Time`, and
+//!   `#[gob(opaque = "text")]` for `encoding.TextMarshaler`, as Go checks
+//!   that the interface matches. The field must serialize as bytes (for
+//!   example using `serde_bytes`), which are sent as they are. The Go type
+//!   name defaults to the name of the Rust type, and can be set with
+//!   `#[gob(opaque, name = "...")]`.
+//! - `#[gob(interface)]` on a field declares it as a Go interface. Only
+//!   nil interface values are supported, so the field should serialize as
+//!   `None`.
+//! - `#[gob(skip)]` on a field leaves it out of the Go type, while serde
+//!   still serializes it. Its value is dropped by the serializer.
+//! - `#[serde(flatten)]` on a field merges the fields of its type, which
+//!   must be a struct with named fields deriving `GobSchema`, into the Go
+//!   struct.
+//! - Internally tagged enums (`#[serde(tag = "...")]`) are sent as a Go
+//!   struct named after the enum, with a `string` field for the tag and
+//!   the fields of all variants, of which only those of the variant being
+//!   sent are set. Newtype variants must hold structs, whose fields are
+//!   merged in as if flattened. Adjacently tagged enums
+//!   (`#[serde(tag = "...", content = "...")]`) are sent as a struct with
+//!   the tag field and a content field of such a struct type.
+//! - `#[gob(repr = "int")]` on an enum whose variants have no fields sends
+//!   it as a Go `int`, like a Go type declared as `type Color int` with
+//!   constants. Each variant is sent as its discriminant, so explicit
+//!   values such as `Blue = 4` are respected, and discriminants which do
+//!   not fit in an `i64` fail to compile. This implements conversions
+//!   from and to `i64`, which serde has to use through
+//!   `#[serde(into = "i64", try_from = "i64")]`. Converting a value which
+//!   does not belong to any variant fails.
+//!
+//! ```ignore
+//! #[derive(Serialize, GobSchema)]
+//! #[gob(name = "Event")]
+//! struct Event {
+//!     #[serde(rename = "ID")]
+//!     id: u64,
+//!     #[gob(opaque = "gob", name = "Time")]
+//!     #[serde(with = "serde_bytes")]
+//!     at: Vec<u8>,
+//!     #[gob(skip)]
+//!     local_only: bool,
+//! }
+//! ```
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+#[macro_use]
+extern crate syn;
+
+use proc_macro2::{Span, TokenStream};
+use syn::{Data, DeriveInput, Fields, GenericParam, Ident, Type};
+
+mod attr;
+mod case;
+
+#[proc_macro_derive(GobSchema, attributes(gob))]
+pub fn derive_gob_schema(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match derive(input) {
+        Ok(expanded) => expanded.into(),
+        Err(err) => compile_error(err).into(),
+    }
+}
+
+/// Like `syn::Error::to_compile_error`, but without the `::core` path,
+/// which does not resolve in edition 2015 crates.
+fn compile_error(err: syn::Error) -> TokenStream {
+    err.into_iter()
+        .map(|err| {
+            let message = err.to_string();
+            quote_spanned!(err.span()=> compile_error!(#message);)
+        })
+        .collect()
+}
+
+fn derive(mut input: DeriveInput) -> syn::Result<TokenStream> {
+    let container = attr::Container::from_attrs(&input.attrs)?;
+    if container.repr_int {
+        return derive_int_enum(&input, &container);
+    }
+    if container.content.is_some() && container.tag.is_none() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "`content` can only be used together with `tag`",
+        ));
+    }
+    let name = container
+        .gob_name
+        .clone()
+        .or_else(|| container.serde_name.clone())
+        .unwrap_or_else(|| unraw(&input.ident));
+
+    let inner_impl = match input.data {
+        Data::Struct(ref data) => derive_struct(&name, &data.fields, &container)?,
+        Data::Enum(ref data) => {
+            let mut variants = Vec::new();
+            for variant in &data.variants {
+                variants.push((variant, attr::Variant::from_attrs(&variant.attrs)?));
+            }
+            match (container.tag.as_ref(), container.content.as_ref()) {
+                (Some(tag), Some(content)) => {
+                    derive_adjacently_tagged_enum(&name, tag, content, &variants)?
+                }
+                (Some(tag), None) => derive_internally_tagged_enum(&name, tag, &variants)?,
+                _ => derive_enum(&name, &variants, &container)?,
+            }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "unions are not supported",
+            ))
+        }
+    };
+
+    for param in input.generics.params.iter_mut() {
+        if let GenericParam::Type(ref mut type_param) = *param {
+            type_param.bounds.push(parse_quote!(::gob::GobSchema));
+        }
+    }
+
+    let flattened_impl = match input.data {
+        Data::Struct(ref data) if matches!(data.fields, Fields::Named(_)) => {
+            let push_fields = push_named_fields(&data.fields, &container)?;
+            quote! {
+                #[allow(unused_variables)]
+                fn register_flattened<S>(
+                    schema: &mut S,
+                    fields: &mut ::std::vec::Vec<(&'static str, S::TypeId)>,
+                ) -> Result<(), S::Error>
+                    where S: ::gob::__private::serde_schema::Schema
+                {
+                    #push_fields
+                    Ok(())
+                }
+            }
+        }
+        _ => TokenStream::new(),
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // `SchemaSerialize` requires `Serialize`, which serde may implement
+    // with different bounds
+    let mut serialize_generics = input.generics.clone();
+    serialize_generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(#ident #ty_generics: ::gob::__private::Serialize));
+    let serialize_where_clause = &serialize_generics.where_clause;
+
+    Ok(quote! {
+        impl #impl_generics ::gob::GobSchema for #ident #ty_generics #where_clause {
+            fn register<S>(schema: &mut S) -> Result<S::TypeId, S::Error>
+                where S: ::gob::__private::serde_schema::Schema
+            {
+                #inner_impl
+            }
+
+            #flattened_impl
+        }
+
+        impl #impl_generics ::gob::__private::serde_schema::SchemaSerialize
+            for #ident #ty_generics #serialize_where_clause
+        {
+            fn schema_register<S>(schema: &mut S) -> Result<S::TypeId, S::Error>
+                where S: ::gob::__private::serde_schema::Schema
+            {
+                <Self as ::gob::GobSchema>::register(schema)
+            }
+        }
+    })
+}
+
+fn derive_struct(
+    name: &str,
+    fields: &Fields,
+    container: &attr::Container,
+) -> syn::Result<TokenStream> {
+    match *fields {
+        Fields::Named(_) if has_flattened_fields(fields)? => {
+            let push_fields = push_named_fields(fields, container)?;
+            Ok(build_struct(name, &push_fields))
+        }
+        Fields::Named(_) => {
+            let fields = named_fields(0, fields, container.rename_all)?;
+            let type_ids = fields.iter().map(|field| &field.register);
+            // serde serializes the tag of a tagged struct as its first field
+            let tag = container.tag.iter().map(|tag| {
+                quote!(.field(#tag,
+                    <S::TypeId as ::gob::__private::serde_schema::types::TypeId>::STR))
+            });
+            let builder = fields.iter().map(|field| {
+                let field_name = &field.name;
+                let type_id = &field.type_id;
+                quote!(.field(#field_name, #type_id))
+            });
+            let len = fields.len() + container.tag.iter().len();
+            Ok(quote! {
+                #(#type_ids)*
+                ::gob::__private::serde_schema::Schema::register_type(schema,
+                    ::gob::__private::serde_schema::types::Type::build()
+                        .struct_type(#name, #len)
+                        #(#tag)*
+                        #(#builder)*
+                        .end())
+            })
+        }
+        Fields::Unnamed(ref unnamed) if unnamed.unnamed.len() == 1 => {
+            let field = &unnamed.unnamed[0];
+            let register =
+                register_field_type(0, 0, &field.ty, &attr::Field::from_attrs(&field.attrs)?);
+            let type_id = type_id_ident(0, 0);
+            Ok(quote! {
+                #register
+                ::gob::__private::serde_schema::Schema::register_type(schema,
+                    ::gob::__private::serde_schema::types::Type::build()
+                        .newtype_struct_type(#name, #type_id))
+            })
+        }
+        Fields::Unnamed(_) => {
+            let elements = unnamed_fields(0, fields)?;
+            let type_ids = elements.iter().map(|(register, _)| register);
+            let builder = elements
+                .iter()
+                .map(|(_, type_id)| quote!(.element(#type_id)));
+            let len = elements.len();
+            Ok(quote! {
+                #(#type_ids)*
+                ::gob::__private::serde_schema::Schema::register_type(schema,
+                    ::gob::__private::serde_schema::types::Type::build()
+                        .tuple_struct_type(#name, #len)
+                        #(#builder)*
+                        .end())
+            })
+        }
+        Fields::Unit => Ok(quote! {
+            ::gob::__private::serde_schema::Schema::register_type(schema,
+                ::gob::__private::serde_schema::types::Type::build().unit_struct_type(#name))
+        }),
+    }
+}
+
+fn derive_enum(
+    name: &str,
+    variants: &[(&syn::Variant, attr::Variant)],
+    container: &attr::Container,
+) -> syn::Result<TokenStream> {
+    let mut type_ids = Vec::new();
+    let mut builder = Vec::new();
+    for (variant_idx, &(variant, ref attrs)) in variants.iter().enumerate() {
+        // variants are looked up by index, so skipped variants are kept
+        let variant_name = attrs.serde_name.clone().unwrap_or_else(|| {
+            let name = unraw(&variant.ident);
+            match container.rename_all {
+                Some(rule) => rule.apply_to_variant(&name),
+                None => name,
+            }
+        });
+        match variant.fields {
+            Fields::Named(_) => {
+                let fields = named_fields(variant_idx, &variant.fields, None)?;
+                let len = fields.len();
+                let field_builder = fields.iter().map(|field| {
+                    let field_name = &field.name;
+                    let type_id = &field.type_id;
+                    quote!(.field(#field_name, #type_id))
+                });
+                builder.push(quote! {
+                    .struct_variant(#variant_name, #len)
+                    #(#field_builder)*
+                    .end()
+                });
+                type_ids.extend(fields.into_iter().map(|field| field.register));
+            }
+            Fields::Unnamed(ref unnamed) if unnamed.unnamed.len() == 1 => {
+                let field = &unnamed.unnamed[0];
+                type_ids.push(register_field_type(
+                    variant_idx,
+                    0,
+                    &field.ty,
+                    &attr::Field::from_attrs(&field.attrs)?,
+                ));
+                let type_id = type_id_ident(variant_idx, 0);
+                builder.push(quote!(.newtype_variant(#variant_name, #type_id)));
+            }
+            Fields::Unnamed(_) => {
+                let elements = unnamed_fields(variant_idx, &variant.fields)?;
+                let len = elements.len();
+                let element_builder = elements
+                    .iter()
+                    .map(|(_, type_id)| quote!(.element(#type_id)));
+                builder.push(quote! {
+                    .tuple_variant(#variant_name, #len)
+                    #(#element_builder)*
+                    .end()
+                });
+                type_ids.extend(elements.into_iter().map(|(register, _)| register));
+            }
+            Fields::Unit => builder.push(quote!(.unit_variant(#variant_name))),
+        }
+    }
+
+    let len = variants.len();
+    Ok(quote! {
+        #(#type_ids)*
+        ::gob::__private::serde_schema::Schema::register_type(schema,
+            ::gob::__private::serde_schema::types::Type::build()
+                .enum_type(#name, #len)
+                #(#builder)*
+                .end())
+    })
+}
+
+/// Registers an internally tagged enum as a keyed struct with the tag
+/// field and the fields of all variants.
+fn derive_internally_tagged_enum(
+    name: &str,
+    tag: &str,
+    variants: &[(&syn::Variant, attr::Variant)],
+) -> syn::Result<TokenStream> {
+    let push_fields = push_variant_fields(variants)?;
+    let build = build_struct(
+        name,
+        &quote! {
+            ::gob::__private::push_field(fields, #tag,
+                <S::TypeId as ::gob::__private::serde_schema::types::TypeId>::STR);
+            #push_fields
+        },
+    );
+    Ok(quote! {
+        let type_id = { #build }?;
+        ::gob::__private::serde_schema::Schema::register_type(schema,
+            ::gob::__private::serde_schema::types::Type::build()
+                .newtype_struct_type(::gob::__private::KEYED_NAME, type_id))
+    })
+}
+
+/// Registers an adjacently tagged enum as a struct with the tag and
+/// content fields, where the content is a keyed struct with the fields
+/// of all variants.
+fn derive_adjacently_tagged_enum(
+    name: &str,
+    tag: &str,
+    content: &str,
+    variants: &[(&syn::Variant, attr::Variant)],
+) -> syn::Result<TokenStream> {
+    let push_fields = push_variant_fields(variants)?;
+    let build_content = build_struct(&format!("{}Content", name), &push_fields);
+    Ok(quote! {
+        let content_id = { #build_content }?;
+        let content_id = ::gob::__private::serde_schema::Schema::register_type(schema,
+            ::gob::__private::serde_schema::types::Type::build()
+                .newtype_struct_type(::gob::__private::KEYED_NAME, content_id))?;
+        ::gob::__private::serde_schema::Schema::register_type(schema,
+            ::gob::__private::serde_schema::types::Type::build()
+                .struct_type(#name, 2)
+                .field(#tag,
+                    <S::TypeId as ::gob::__private::serde_schema::types::TypeId>::STR)
+                .field(#content, content_id)
+                .end())
+    })
+}
+
+/// Code which appends the fields of all variants of a tagged enum to
+/// `fields`.
+fn push_variant_fields(variants: &[(&syn::Variant, attr::Variant)]) -> syn::Result<TokenStream> {
+    let mut push_fields = Vec::new();
+    for (variant_idx, &(variant, _)) in variants.iter().enumerate() {
+        match variant.fields {
+            Fields::Named(_) => {
+                push_fields.push(push_fields_of(variant_idx, &variant.fields, None)?);
+            }
+            Fields::Unnamed(ref unnamed) if unnamed.unnamed.len() == 1 => {
+                let ty = &unnamed.unnamed[0].ty;
+                push_fields.push(quote! {
+                    <#ty as ::gob::GobSchema>::register_flattened(schema, fields)?;
+                });
+            }
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "tuple variants are not supported in tagged enums",
+                ))
+            }
+            Fields::Unit => {}
+        }
+    }
+    Ok(quote!(#(#push_fields)*))
+}
+
+/// Code which registers a struct from the fields appended to `fields` by
+/// `push_fields`.
+fn build_struct(name: &str, push_fields: &TokenStream) -> TokenStream {
+    quote! {
+        let fields = &mut ::std::vec::Vec::new();
+        #push_fields
+        let mut builder = ::gob::__private::serde_schema::types::Type::build()
+            .struct_type(#name, fields.len());
+        for (field_name, type_id) in fields.drain(..) {
+            builder = builder.field(field_name, type_id);
+        }
+        ::gob::__private::serde_schema::Schema::register_type(schema, builder.end())
+    }
+}
+
+/// Code which appends the fields of a struct, including its tag and the
+/// fields of flattened fields, to `fields`.
+fn push_named_fields(fields: &Fields, container: &attr::Container) -> syn::Result<TokenStream> {
+    let tag = container.tag.iter().map(|tag| {
+        quote! {
+            ::gob::__private::push_field(fields, #tag,
+                <S::TypeId as ::gob::__private::serde_schema::types::TypeId>::STR);
+        }
+    });
+    let push_fields = push_fields_of(0, fields, container.rename_all)?;
+    Ok(quote! {
+        #(#tag)*
+        #push_fields
+    })
+}
+
+/// Code which registers named fields under their serde names and appends
+/// them to `fields`, registering flattened fields through
+/// `GobSchema::register_flattened`.
+fn push_fields_of(
+    variant_idx: usize,
+    fields: &Fields,
+    rename_all: Option<case::RenameRule>,
+) -> syn::Result<TokenStream> {
+    let mut push_fields = Vec::new();
+    for (field_idx, field) in fields.iter().enumerate() {
+        let attrs = attr::Field::from_attrs(&field.attrs)?;
+        if attrs.serde_skip {
+            continue;
+        }
+        if attrs.flatten {
+            let ty = &field.ty;
+            push_fields.push(quote! {
+                <#ty as ::gob::GobSchema>::register_flattened(schema, fields)?;
+            });
+            continue;
+        }
+        let name = serde_field_name(field, &attrs, rename_all);
+        let register = register_field_type(variant_idx, field_idx, &field.ty, &attrs);
+        let type_id = type_id_ident(variant_idx, field_idx);
+        push_fields.push(quote! {
+            #register
+            ::gob::__private::push_field(fields, #name, #type_id);
+        });
+    }
+    Ok(quote!(#(#push_fields)*))
+}
+
+fn has_flattened_fields(fields: &Fields) -> syn::Result<bool> {
+    for field in fields {
+        if attr::Field::from_attrs(&field.attrs)?.flatten {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Implements `GobSchema` and conversions from and to `i64` for an enum
+/// which is sent as its discriminant.
+fn derive_int_enum(input: &DeriveInput, container: &attr::Container) -> syn::Result<TokenStream> {
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "`repr = \"int\"` can only be used on enums",
+            ))
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "`repr = \"int\"` enums cannot be generic",
+        ));
+    }
+    for variant in &data.variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+                variant,
+                "`repr = \"int\"` enums can only have unit variants",
+            ));
+        }
+    }
+
+    // serde must be told to go through the conversions, as deriving
+    // `Serialize` and `Deserialize` here would conflict with the user's.
+    // The impls are generated regardless, so that this is the only error.
+    let serde_error = if container.into.as_deref() != Some("i64")
+        || container.try_from.as_deref() != Some("i64")
+    {
+        Some(compile_error(syn::Error::new_spanned(
+            &input.ident,
+            "`repr = \"int\"` enums are serialized as `i64`, \
+             add `#[serde(into = \"i64\", try_from = \"i64\")]`",
+        )))
+    } else {
+        None
+    };
+
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let variants: Vec<_> = data.variants.iter().map(|variant| &variant.ident).collect();
+    let discriminant_checks = variants.iter().map(|variant| {
+        let message = format!(
+            "the discriminant of {}::{} does not fit in an i64",
+            ident, variant
+        );
+        quote!(assert!(#ident::#variant as i128 == #ident::#variant as i64 as i128, #message);)
+    });
+    let try_from_arms = variants
+        .iter()
+        .map(|variant| quote!(value if value == #ident::#variant as i64 => Ok(#ident::#variant)));
+
+    Ok(quote! {
+        impl ::gob::GobSchema for #ident {
+            fn register<S>(_schema: &mut S) -> Result<S::TypeId, S::Error>
+                where S: ::gob::__private::serde_schema::Schema
+            {
+                Ok(<S::TypeId as ::gob::__private::serde_schema::types::TypeId>::I64)
+            }
+        }
+
+        impl ::gob::__private::serde_schema::SchemaSerialize for #ident {
+            fn schema_register<S>(schema: &mut S) -> Result<S::TypeId, S::Error>
+                where S: ::gob::__private::serde_schema::Schema
+            {
+                <Self as ::gob::GobSchema>::register(schema)
+            }
+        }
+
+        #serde_error
+
+        const _: () = {
+            #(#discriminant_checks)*
+        };
+
+        impl ::std::convert::From<#ident> for i64 {
+            fn from(value: #ident) -> i64 {
+                value as i64
+            }
+        }
+
+        impl ::std::convert::TryFrom<i64> for #ident {
+            type Error = String;
+
+            fn try_from(value: i64) -> Result<Self, String> {
+                match value {
+                    #(#try_from_arms,)*
+                    value => Err(format!("unknown value {} for enum {}", value, #name)),
+                }
+            }
+        }
+    })
+}
+
+struct NamedField {
+    name: String,
+    type_id: Ident,
+    register: TokenStream,
+}
+
+fn named_fields(
+    variant_idx: usize,
+    fields: &Fields,
+    rename_all: Option<case::RenameRule>,
+) -> syn::Result<Vec<NamedField>> {
+    let mut named = Vec::new();
+    for (field_idx, field) in fields.iter().enumerate() {
+        let attrs = attr::Field::from_attrs(&field.attrs)?;
+        if attrs.serde_skip {
+            // serde does not serialize the field at all
+            continue;
+        }
+        let name = serde_field_name(field, &attrs, rename_all);
+        named.push(NamedField {
+            name,
+            type_id: type_id_ident(variant_idx, field_idx),
+            register: register_field_type(variant_idx, field_idx, &field.ty, &attrs),
+        });
+    }
+    Ok(named)
+}
+
+fn serde_field_name(
+    field: &syn::Field,
+    attrs: &attr::Field,
+    rename_all: Option<case::RenameRule>,
+) -> String {
+    attrs.serde_name.clone().unwrap_or_else(|| {
+        let name = unraw(field.ident.as_ref().unwrap());
+        match rename_all {
+            Some(rule) => rule.apply_to_field(&name),
+            None => name,
+        }
+    })
+}
+
+fn unnamed_fields(variant_idx: usize, fields: &Fields) -> syn::Result<Vec<(TokenStream, Ident)>> {
+    let mut elements = Vec::new();
+    for (field_idx, field) in fields.iter().enumerate() {
+        let attrs = attr::Field::from_attrs(&field.attrs)?;
+        if attrs.serde_skip {
+            continue;
+        }
+        elements.push((
+            register_field_type(variant_idx, field_idx, &field.ty, &attrs),
+            type_id_ident(variant_idx, field_idx),
+        ));
+    }
+    Ok(elements)
+}
+
+fn register_field_type(
+    variant_idx: usize,
+    field_idx: usize,
+    ty: &Type,
+    attrs: &attr::Field,
+) -> TokenStream {
+    let type_id = type_id_ident(variant_idx, field_idx);
+    let register_type = |ty: TokenStream| {
+        quote! {
+            let #type_id = ::gob::__private::serde_schema::Schema::register_type(schema,
+                ::gob::__private::serde_schema::types::Type::build()#ty)?;
+        }
+    };
+    if attrs.skip {
+        register_type(quote!(.unit_struct_type(::gob::__private::SKIP_NAME)))
+    } else if attrs.interface {
+        register_type(quote!(.unit_struct_type(::gob::__private::INTERFACE_NAME)))
+    } else if let Some(opaque) = attrs.opaque {
+        let go_name = attrs.gob_name.clone().unwrap_or_else(|| type_name(ty));
+        let marker = match opaque {
+            attr::Opaque::Binary => quote!(::gob::__private::OPAQUE_NAME),
+            attr::Opaque::Gob => quote!(::gob::__private::OPAQUE_GOB_NAME),
+            attr::Opaque::Text => quote!(::gob::__private::OPAQUE_TEXT_NAME),
+        };
+        register_type(quote! {
+            .struct_type(#marker, 1)
+            .field(#go_name,
+                <S::TypeId as ::gob::__private::serde_schema::types::TypeId>::BYTES)
+            .end()
+        })
+    } else {
+        quote! {
+            let #type_id = {
+                #[allow(unused_imports)]
+                use ::gob::__private::{RegisterGobSchema, RegisterSchemaSerialize};
+                (&&::gob::__private::TypeTag::<#ty>::new()).register(schema)?
+            };
+        }
+    }
+}
+
+fn type_id_ident(variant_idx: usize, field_idx: usize) -> Ident {
+    Ident::new(
+        &format!("type_id_{}_{}", variant_idx, field_idx),
+        Span::call_site(),
+    )
+}
+
+/// The name of the last path segment of a type, e.g. `Time` for
+/// `chrono::Time`.
+fn type_name(ty: &Type) -> String {
+    match *ty {
+        Type::Path(ref type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| unraw(&segment.ident))
+            .unwrap_or_default(),
+        Type::Group(ref group) => type_name(&group.elem),
+        Type::Paren(ref paren) => type_name(&paren.elem),
+        _ => String::new(),
+    }
+}
+
+fn unraw(ident: &Ident) -> String {
+    let name = ident.to_string();
+    match name.strip_prefix("r#") {
+        Some(name) => name.to_owned(),
+        None => name,
+    }
+}
@@ -0,0 +1,531 @@
+//! Arbitrary precision numbers, sent as Go's `math/big` types
+
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use serde_schema::types::{Type, TypeId};
+use serde_schema::{Schema, SchemaSerialize};
+
+use gob_schema::GobSchema;
+use schema::OPAQUE_GOB_NAME;
+
+// versions written as the first byte by the `GobEncode` methods
+const INT_GOB_VERSION: u8 = 1;
+const RAT_GOB_VERSION: u8 = 1;
+const FLOAT_GOB_VERSION: u8 = 1;
+
+// size of the words of mantissas, which Go decodes into `big.Word`s
+const WORD_BYTES: usize = 8;
+
+fn register_opaque<S: Schema>(schema: &mut S, name: &'static str) -> Result<S::TypeId, S::Error> {
+    schema.register_type(
+        Type::build()
+            .struct_type(OPAQUE_GOB_NAME, 1)
+            .field(name, S::TypeId::BYTES)
+            .end(),
+    )
+}
+
+/// Registers Go's `big.Int`, which `BigInt` as well as `i128` and `u128`
+/// are sent as.
+pub(crate) fn register_int<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+    register_opaque(schema, "Int")
+}
+
+fn trim_leading_zeros(bytes: &[u8]) -> &[u8] {
+    let start = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+/// Appends a `big.Int` in the format of `Int.GobEncode`, a byte of the
+/// version and the sign followed by the big-endian magnitude.
+pub(crate) fn encode_int(neg: bool, magnitude: &[u8], buf: &mut Vec<u8>) {
+    let magnitude = trim_leading_zeros(magnitude);
+    buf.push(INT_GOB_VERSION << 1 | (neg && !magnitude.is_empty()) as u8);
+    buf.extend_from_slice(magnitude);
+}
+
+/// Splits a `big.Int` encoded by `Int.GobEncode` into its sign and its
+/// big-endian magnitude.
+pub(crate) fn decode_int(buf: &[u8]) -> Result<(bool, &[u8]), String> {
+    match buf.split_first() {
+        // what a nil `*big.Int` encodes to
+        None => Ok((false, buf)),
+        Some((&b, magnitude)) if b >> 1 == INT_GOB_VERSION => {
+            let magnitude = trim_leading_zeros(magnitude);
+            Ok((b & 1 != 0 && !magnitude.is_empty(), magnitude))
+        }
+        Some((&b, _)) => Err(format!("big.Int encoding version {} not supported", b >> 1)),
+    }
+}
+
+pub(crate) fn encode_i128(v: i128, buf: &mut Vec<u8>) {
+    encode_int(v < 0, &v.unsigned_abs().to_be_bytes(), buf)
+}
+
+pub(crate) fn encode_u128(v: u128, buf: &mut Vec<u8>) {
+    encode_int(false, &v.to_be_bytes(), buf)
+}
+
+fn magnitude_to_u128(magnitude: &[u8]) -> Option<u128> {
+    if magnitude.len() > 16 {
+        return None;
+    }
+    Some(magnitude.iter().fold(0, |v, &b| v << 8 | u128::from(b)))
+}
+
+fn to_i128(neg: bool, magnitude: &[u8]) -> Option<i128> {
+    let abs = magnitude_to_u128(magnitude)?;
+    if neg {
+        0i128.checked_sub_unsigned(abs)
+    } else {
+        0i128.checked_add_unsigned(abs)
+    }
+}
+
+fn to_u128(neg: bool, magnitude: &[u8]) -> Option<u128> {
+    if neg {
+        None
+    } else {
+        magnitude_to_u128(magnitude)
+    }
+}
+
+pub(crate) fn decode_i128(buf: &[u8]) -> Result<i128, String> {
+    let (neg, magnitude) = decode_int(buf)?;
+    to_i128(neg, magnitude).ok_or_else(|| "big.Int value out of range for i128".to_owned())
+}
+
+pub(crate) fn decode_u128(buf: &[u8]) -> Result<u128, String> {
+    let (neg, magnitude) = decode_int(buf)?;
+    to_u128(neg, magnitude).ok_or_else(|| "big.Int value out of range for u128".to_owned())
+}
+
+struct GobBytesVisitor<F>(&'static str, F);
+
+impl<'de, T, F> Visitor<'de> for GobBytesVisitor<F>
+where
+    F: FnOnce(&[u8]) -> Result<T, String>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a Go {} encoded by its GobEncode method", self.0)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<T, E> {
+        (self.1)(v).map_err(E::custom)
+    }
+}
+
+macro_rules! gob_encoded_impl {
+    ($name:ident, $go:expr, $encode:ident, $decode:ident) => {
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                let mut buf = Vec::new();
+                self.$encode(&mut buf);
+                ser.serialize_bytes(&buf)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                de.deserialize_bytes(GobBytesVisitor(concat!("big.", $go), $name::$decode))
+            }
+        }
+
+        impl SchemaSerialize for $name {
+            fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                register_opaque(schema, $go)
+            }
+        }
+
+        impl GobSchema for $name {
+            fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                register_opaque(schema, $go)
+            }
+        }
+    };
+}
+
+/// An arbitrary precision integer, sent as Go's `big.Int` in the format
+/// of `Int.GobEncode`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    neg: bool,
+    // big-endian, without leading zeros
+    magnitude: Vec<u8>,
+}
+
+impl BigInt {
+    /// Creates an integer from its sign and its big-endian magnitude.
+    pub fn from_bytes_be(negative: bool, magnitude: &[u8]) -> BigInt {
+        let magnitude = trim_leading_zeros(magnitude);
+        BigInt {
+            neg: negative && !magnitude.is_empty(),
+            magnitude: magnitude.to_vec(),
+        }
+    }
+
+    /// Returns `true` for integers less than zero.
+    pub fn is_negative(&self) -> bool {
+        self.neg
+    }
+
+    /// The big-endian magnitude, which is empty for zero.
+    pub fn magnitude_be(&self) -> &[u8] {
+        &self.magnitude
+    }
+
+    pub fn to_i128(&self) -> Option<i128> {
+        to_i128(self.neg, &self.magnitude)
+    }
+
+    pub fn to_u128(&self) -> Option<u128> {
+        to_u128(self.neg, &self.magnitude)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    fn gob_encode(&self, buf: &mut Vec<u8>) {
+        encode_int(self.neg, &self.magnitude, buf)
+    }
+
+    fn gob_decode(buf: &[u8]) -> Result<BigInt, String> {
+        let (neg, magnitude) = decode_int(buf)?;
+        Ok(BigInt::from_bytes_be(neg, magnitude))
+    }
+}
+
+macro_rules! big_int_from {
+    ($($ty:ident)*) => {
+        $(
+            impl From<$ty> for BigInt {
+                fn from(v: $ty) -> BigInt {
+                    BigInt::from(v as i128)
+                }
+            }
+        )*
+    };
+}
+
+big_int_from!(i8 i16 i32 i64 isize u8 u16 u32 u64 usize);
+
+impl From<i128> for BigInt {
+    fn from(v: i128) -> BigInt {
+        BigInt::from_bytes_be(v < 0, &v.unsigned_abs().to_be_bytes())
+    }
+}
+
+impl From<u128> for BigInt {
+    fn from(v: u128) -> BigInt {
+        BigInt::from_bytes_be(false, &v.to_be_bytes())
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<::num_bigint::BigInt> for BigInt {
+    fn from(v: ::num_bigint::BigInt) -> BigInt {
+        let (sign, magnitude) = v.to_bytes_be();
+        BigInt::from_bytes_be(sign == ::num_bigint::Sign::Minus, &magnitude)
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<BigInt> for ::num_bigint::BigInt {
+    fn from(v: BigInt) -> ::num_bigint::BigInt {
+        let sign = if v.neg {
+            ::num_bigint::Sign::Minus
+        } else {
+            ::num_bigint::Sign::Plus
+        };
+        ::num_bigint::BigInt::from_bytes_be(sign, &v.magnitude)
+    }
+}
+
+gob_encoded_impl!(BigInt, "Int", gob_encode, gob_decode);
+
+/// An arbitrary precision fraction, sent as Go's `big.Rat` in the format
+/// of `Rat.GobEncode`.
+///
+/// Go expects fractions in lowest terms, which `BigRat::new` does not
+/// ensure, unlike conversions from `num_rational::BigRational`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BigRat {
+    numer: BigInt,
+    // positive
+    denom: BigInt,
+}
+
+impl BigRat {
+    /// Creates a fraction, moving the sign of the denominator to the
+    /// numerator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `denom` is zero.
+    pub fn new(numer: BigInt, denom: BigInt) -> BigRat {
+        assert!(!denom.is_zero(), "denominator of zero");
+        let neg = numer.neg != denom.neg;
+        BigRat {
+            numer: BigInt::from_bytes_be(neg, &numer.magnitude),
+            denom: BigInt::from_bytes_be(false, &denom.magnitude),
+        }
+    }
+
+    pub fn numer(&self) -> &BigInt {
+        &self.numer
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.denom
+    }
+
+    fn gob_encode(&self, buf: &mut Vec<u8>) {
+        let numer = &self.numer.magnitude;
+        buf.push(RAT_GOB_VERSION << 1 | self.numer.neg as u8);
+        buf.extend_from_slice(&(numer.len() as u32).to_be_bytes());
+        buf.extend_from_slice(numer);
+        buf.extend_from_slice(&self.denom.magnitude);
+    }
+
+    fn gob_decode(buf: &[u8]) -> Result<BigRat, String> {
+        if buf.is_empty() {
+            return Ok(BigRat::default());
+        }
+        if buf.len() < 5 {
+            return Err("big.Rat buffer too small".to_owned());
+        }
+        if buf[0] >> 1 != RAT_GOB_VERSION {
+            return Err(format!(
+                "big.Rat encoding version {} not supported",
+                buf[0] >> 1
+            ));
+        }
+        let numer_len = u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize;
+        if numer_len > buf.len() - 5 {
+            return Err("big.Rat invalid length".to_owned());
+        }
+        let (numer, denom) = buf[5..].split_at(numer_len);
+        let denom = BigInt::from_bytes_be(false, denom);
+        Ok(BigRat {
+            numer: BigInt::from_bytes_be(buf[0] & 1 != 0, numer),
+            // Go treats a zero denominator as one
+            denom: if denom.is_zero() {
+                BigInt::from(1)
+            } else {
+                denom
+            },
+        })
+    }
+}
+
+impl Default for BigRat {
+    fn default() -> BigRat {
+        BigRat::from(BigInt::default())
+    }
+}
+
+impl From<BigInt> for BigRat {
+    fn from(v: BigInt) -> BigRat {
+        BigRat {
+            numer: v,
+            denom: BigInt::from(1),
+        }
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<::num_rational::BigRational> for BigRat {
+    fn from(v: ::num_rational::BigRational) -> BigRat {
+        let (numer, denom) = v.into_raw();
+        BigRat::new(numer.into(), denom.into())
+    }
+}
+
+#[cfg(feature = "num-bigint")]
+impl From<BigRat> for ::num_rational::BigRational {
+    fn from(v: BigRat) -> ::num_rational::BigRational {
+        ::num_rational::BigRational::new(v.numer.into(), v.denom.into())
+    }
+}
+
+gob_encoded_impl!(BigRat, "Rat", gob_encode, gob_decode);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Form {
+    #[default]
+    Zero,
+    Finite,
+    Inf,
+}
+
+/// A floating-point number of arbitrary precision, sent as Go's
+/// `big.Float` in the format of `Float.GobEncode`.
+///
+/// Values decoded from Go keep their precision, rounding mode and
+/// accuracy, so that they are sent back unchanged.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BigFloat {
+    // Go's `big.RoundingMode`
+    mode: u8,
+    // Go's `big.Accuracy`
+    acc: i8,
+    form: Form,
+    neg: bool,
+    prec: u32,
+    exp: i32,
+    // the big-endian fraction bits of a finite number, starting with a
+    // set bit, in whole words
+    mantissa: Vec<u8>,
+}
+
+impl BigFloat {
+    /// Converts an `f64` exactly, with a precision of 53 bits like Go's
+    /// `big.NewFloat`. Returns `None` for NaN, which `big.Float` cannot
+    /// represent.
+    pub fn from_f64(v: f64) -> Option<BigFloat> {
+        if v.is_nan() {
+            return None;
+        }
+        let mut float = BigFloat {
+            neg: v.is_sign_negative(),
+            prec: 53,
+            ..BigFloat::default()
+        };
+        if v == 0.0 {
+            return Some(float);
+        }
+        if v.is_infinite() {
+            float.form = Form::Inf;
+            return Some(float);
+        }
+        let bits = v.to_bits();
+        let biased_exp = ((bits >> 52) & 0x7ff) as i32;
+        let fraction = bits & ((1 << 52) - 1);
+        let (mantissa, exp) = if biased_exp == 0 {
+            // subnormal
+            let shift = fraction.leading_zeros();
+            (fraction << shift, -1010 - shift as i32)
+        } else {
+            (1 << 63 | fraction << 11, biased_exp - 1022)
+        };
+        float.form = Form::Finite;
+        float.exp = exp;
+        float.mantissa = mantissa.to_be_bytes().to_vec();
+        Some(float)
+    }
+
+    /// Converts to an `f64`, rounding to the nearest one if the value
+    /// cannot be represented exactly.
+    pub fn to_f64(&self) -> f64 {
+        let abs = match self.form {
+            Form::Zero => 0.0,
+            Form::Inf => f64::INFINITY,
+            Form::Finite => {
+                let mut mantissa = [0; 8];
+                let len = self.mantissa.len().min(8);
+                mantissa[..len].copy_from_slice(&self.mantissa[..len]);
+                let mut mantissa = u64::from_be_bytes(mantissa);
+                // the bits which do not fit only matter for rounding
+                if self.mantissa[len..].iter().any(|&b| b != 0) {
+                    mantissa |= 1;
+                }
+                scale(mantissa as f64, self.exp - 64)
+            }
+        };
+        if self.neg {
+            -abs
+        } else {
+            abs
+        }
+    }
+
+    /// The precision in bits, which is zero only for the zero value of
+    /// Go's `big.Float`.
+    pub fn prec(&self) -> u32 {
+        self.prec
+    }
+
+    fn gob_encode(&self, buf: &mut Vec<u8>) {
+        buf.push(FLOAT_GOB_VERSION);
+        buf.push(
+            (self.mode & 7) << 5
+                | ((self.acc + 1) as u8 & 3) << 3
+                | (self.form as u8 & 3) << 1
+                | self.neg as u8,
+        );
+        buf.extend_from_slice(&self.prec.to_be_bytes());
+        if self.form == Form::Finite {
+            buf.extend_from_slice(&self.exp.to_be_bytes());
+            buf.extend_from_slice(&self.mantissa);
+        }
+    }
+
+    fn gob_decode(buf: &[u8]) -> Result<BigFloat, String> {
+        if buf.is_empty() {
+            return Ok(BigFloat::default());
+        }
+        if buf.len() < 6 {
+            return Err("big.Float buffer too small".to_owned());
+        }
+        if buf[0] != FLOAT_GOB_VERSION {
+            return Err(format!(
+                "big.Float encoding version {} not supported",
+                buf[0]
+            ));
+        }
+        let b = buf[1];
+        let mut float = BigFloat {
+            mode: (b >> 5) & 7,
+            acc: ((b >> 3) & 3) as i8 - 1,
+            form: match (b >> 1) & 3 {
+                0 => Form::Zero,
+                1 => Form::Finite,
+                2 => Form::Inf,
+                _ => return Err("big.Float invalid form".to_owned()),
+            },
+            neg: b & 1 != 0,
+            prec: u32::from_be_bytes([buf[2], buf[3], buf[4], buf[5]]),
+            ..BigFloat::default()
+        };
+        if float.form == Form::Finite {
+            if buf.len() < 10 {
+                return Err("big.Float buffer too small for finite form float".to_owned());
+            }
+            float.exp = i32::from_be_bytes([buf[6], buf[7], buf[8], buf[9]]);
+            let mantissa = &buf[10..];
+            if mantissa.first().is_none_or(|&b| b & 0x80 == 0) {
+                return Err("big.Float mantissa is not normalized".to_owned());
+            }
+            if float.prec == 0 {
+                return Err("big.Float zero precision finite number".to_owned());
+            }
+            // mantissas from platforms with smaller words are padded, so
+            // that they are sent back in whole words
+            float.mantissa = mantissa.to_vec();
+            let padded_len = mantissa.len().div_ceil(WORD_BYTES) * WORD_BYTES;
+            float.mantissa.resize(padded_len, 0);
+        }
+        Ok(float)
+    }
+}
+
+/// Multiplies by a power of two, in steps which do not overflow the
+/// exponent of an `f64`.
+fn scale(mut v: f64, mut exp: i32) -> f64 {
+    while exp > 1023 {
+        v *= f64::from_bits(2046 << 52);
+        exp -= 1023;
+    }
+    while exp < -1022 {
+        v *= f64::from_bits(1 << 52);
+        exp += 1022;
+    }
+    v * f64::from_bits(((exp + 1023) as u64) << 52)
+}
+
+gob_encoded_impl!(BigFloat, "Float", gob_encode, gob_decode);
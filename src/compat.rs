@@ -0,0 +1,532 @@
+//! Schema evolution checks
+//!
+//! Compares two versions of a set of type definitions under gob's
+//! decoding rules: types are matched by name, struct fields are matched
+//! by name, fields missing on either side are tolerated, and a value can
+//! only be decoded into a type of the same class (e.g. an `int` cannot
+//! be decoded as a `string`, and arrays must have the same length).
+
+use std::collections::BTreeSet;
+use std::fmt;
+
+use de::{Options, TypeDefinitions};
+use internal::types::{Types, WireType};
+use schema::TypeId;
+
+/// Which direction of communication a change is compatible with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compatibility {
+    /// New readers can decode old data, and old readers can decode new
+    /// data.
+    Both,
+    /// New readers can decode old data, but old readers cannot decode
+    /// data written by new writers.
+    ReadOld,
+    /// Old readers can decode new data, but new readers cannot decode
+    /// old data.
+    WriteOld,
+    /// The versions cannot interoperate.
+    Neither,
+}
+
+impl Compatibility {
+    fn new(can_read_old: bool, can_write_old: bool) -> Compatibility {
+        match (can_read_old, can_write_old) {
+            (true, true) => Compatibility::Both,
+            (true, false) => Compatibility::ReadOld,
+            (false, true) => Compatibility::WriteOld,
+            (false, false) => Compatibility::Neither,
+        }
+    }
+
+    /// Whether a reader using the new definitions can decode data
+    /// written with the old ones.
+    pub fn can_read_old(self) -> bool {
+        self == Compatibility::Both || self == Compatibility::ReadOld
+    }
+
+    /// Whether a reader using the old definitions can decode data
+    /// written with the new ones.
+    pub fn can_write_old(self) -> bool {
+        self == Compatibility::Both || self == Compatibility::WriteOld
+    }
+
+    fn and(self, other: Compatibility) -> Compatibility {
+        Compatibility::new(
+            self.can_read_old() && other.can_read_old(),
+            self.can_write_old() && other.can_write_old(),
+        )
+    }
+}
+
+impl fmt::Display for Compatibility {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Compatibility::Both => "compatible",
+            Compatibility::ReadOld => "can read old data only",
+            Compatibility::WriteOld => "can write to old readers only",
+            Compatibility::Neither => "incompatible",
+        })
+    }
+}
+
+/// A single difference between the old and new definitions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Change {
+    type_name: String,
+    field_name: Option<String>,
+    compatibility: Compatibility,
+    description: String,
+}
+
+impl Change {
+    /// The name of the type which changed.
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
+    /// The name of the struct field which changed, if the change is
+    /// confined to a single field.
+    pub fn field_name(&self) -> Option<&str> {
+        self.field_name.as_deref()
+    }
+
+    pub fn compatibility(&self) -> Compatibility {
+        self.compatibility
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.type_name)?;
+        if let Some(ref field_name) = self.field_name {
+            write!(f, ".{}", field_name)?;
+        }
+        write!(f, ": {} ({})", self.description, self.compatibility)
+    }
+}
+
+/// The result of comparing two versions of a set of type definitions.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    changes: Vec<Change>,
+}
+
+impl Report {
+    /// All differences found, in the order of the types' ids.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// The compatibility of the definitions as a whole.
+    pub fn compatibility(&self) -> Compatibility {
+        self.changes
+            .iter()
+            .fold(Compatibility::Both, |acc, change| {
+                acc.and(change.compatibility)
+            })
+    }
+
+    /// The compatibility of a single type, taking changes to all of its
+    /// fields into account.
+    pub fn type_compatibility(&self, type_name: &str) -> Compatibility {
+        self.changes
+            .iter()
+            .filter(|change| change.type_name == type_name)
+            .fold(Compatibility::Both, |acc, change| {
+                acc.and(change.compatibility)
+            })
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.changes.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        writeln!(f, "overall: {}", self.compatibility())
+    }
+}
+
+/// Compare two versions of a set of type definitions.
+///
+/// Definitions can be obtained from a stream with
+/// `StreamDeserializer::type_definitions`, or from a serializer's schema
+/// with `Schema::type_definitions`.
+pub fn check(old: &TypeDefinitions, new: &TypeDefinitions) -> Report {
+    let mut checker = Checker {
+        old: old.types(),
+        new: new.types(),
+        validation: None,
+        visited: BTreeSet::new(),
+        changes: Vec::new(),
+    };
+    checker.check_all();
+    Report {
+        changes: checker.changes,
+    }
+}
+
+/// Lists the reasons why values of the wire type `wire_id` cannot be
+/// decoded into the Rust type with id `rust_id`.
+pub(crate) fn check_value_type(
+    wire: &Types,
+    wire_id: TypeId,
+    rust: &Types,
+    rust_id: TypeId,
+    options: Options,
+) -> Vec<Change> {
+    let mut checker = Checker {
+        old: wire,
+        new: rust,
+        validation: Some(options),
+        visited: BTreeSet::new(),
+        changes: Vec::new(),
+    };
+    let type_name = wire.name(wire_id).unwrap_or("");
+    checker.check_type(type_name, None, wire_id, rust_id);
+    checker.changes
+}
+
+/// The kind of value a type id describes, which must agree between the
+/// sender and the receiver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Class {
+    Bool,
+    Int,
+    Uint,
+    Float,
+    Bytes,
+    String,
+    Complex,
+    Interface,
+    Array,
+    Slice,
+    Struct,
+    Map,
+    Marshaler,
+    Unknown,
+}
+
+impl Class {
+    fn of(types: &Types, id: TypeId) -> Class {
+        match id {
+            TypeId::BOOL => Class::Bool,
+            TypeId::INT => Class::Int,
+            TypeId::UINT => Class::Uint,
+            TypeId::FLOAT => Class::Float,
+            TypeId::BYTES => Class::Bytes,
+            TypeId::STRING => Class::String,
+            TypeId::COMPLEX => Class::Complex,
+            TypeId::INTERFACE => Class::Interface,
+            _ => match types.lookup(id) {
+                Some(WireType::Array(_)) => Class::Array,
+                Some(WireType::Slice(_)) => Class::Slice,
+                Some(WireType::Struct(_)) => Class::Struct,
+                Some(WireType::Map(_)) => Class::Map,
+                Some(wire_type) if wire_type.is_marshaler() => Class::Marshaler,
+                Some(_) => Class::Unknown,
+                None => Class::Unknown,
+            },
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Class::Bool => "bool",
+            Class::Int => "int",
+            Class::Uint => "uint",
+            Class::Float => "float",
+            Class::Bytes => "bytes",
+            Class::String => "string",
+            Class::Complex => "complex",
+            Class::Interface => "interface",
+            Class::Array => "array",
+            Class::Slice => "slice",
+            Class::Struct => "struct",
+            Class::Map => "map",
+            Class::Marshaler => "marshaled value",
+            Class::Unknown => "unknown type",
+        }
+    }
+}
+
+struct Checker<'a> {
+    old: &'a Types,
+    new: &'a Types,
+    // when checking whether a Rust type can decode a wire type, the
+    // options of the decoder
+    validation: Option<Options>,
+    visited: BTreeSet<(TypeId, TypeId)>,
+    changes: Vec<Change>,
+}
+
+impl<'a> Checker<'a> {
+    fn check_all(&mut self) {
+        for old_type in self.old.iter() {
+            let name = &old_type.common().name;
+            if name.is_empty() {
+                continue;
+            }
+            match self
+                .new
+                .iter()
+                .find(|new_type| new_type.common().name == *name)
+            {
+                Some(new_type) => {
+                    self.check_type(name, None, old_type.common().id, new_type.common().id)
+                }
+                None => self.change(
+                    name,
+                    None,
+                    Compatibility::WriteOld,
+                    "type removed, old data containing it cannot be read".to_owned(),
+                ),
+            }
+        }
+        for new_type in self.new.iter() {
+            let name = &new_type.common().name;
+            if name.is_empty()
+                || self
+                    .old
+                    .iter()
+                    .any(|old_type| old_type.common().name == *name)
+            {
+                continue;
+            }
+            self.change(
+                name,
+                None,
+                Compatibility::ReadOld,
+                "type added, old readers do not know it".to_owned(),
+            );
+        }
+    }
+
+    fn is_uint_seq(&self, types: &Types, id: TypeId) -> bool {
+        match types.lookup(id) {
+            Some(WireType::Slice(slice_type)) => slice_type.elem == TypeId::UINT,
+            Some(WireType::Array(array_type)) => array_type.elem == TypeId::UINT,
+            _ => false,
+        }
+    }
+
+    fn same_field_name(&self, old: &str, new: &str) -> bool {
+        match self.validation {
+            Some(options) => options.field_matching.matches(new, old),
+            None => old == new,
+        }
+    }
+
+    fn change(
+        &mut self,
+        type_name: &str,
+        field_name: Option<&str>,
+        compatibility: Compatibility,
+        description: String,
+    ) {
+        self.changes.push(Change {
+            type_name: type_name.to_owned(),
+            field_name: field_name.map(|name| name.to_owned()),
+            compatibility,
+            description,
+        });
+    }
+
+    /// Compares the types `old_id` and `new_id`, which are used by the
+    /// type `type_name` (in the field `field_name`, if given).
+    fn check_type(
+        &mut self,
+        type_name: &str,
+        field_name: Option<&str>,
+        old_id: TypeId,
+        new_id: TypeId,
+    ) {
+        let old_class = Class::of(self.old, old_id);
+        let new_class = Class::of(self.new, new_id);
+
+        // sets are sent as maps, and can be decoded into sequences of
+        // their members
+        if let (Some(_), Some(WireType::Map(old)), Some(WireType::Slice(new))) = (
+            self.validation,
+            self.old.lookup(old_id),
+            self.new.lookup(new_id),
+        ) {
+            if self.old.is_set_elem(old.elem) {
+                self.check_type(type_name, field_name, old.key, new.elem);
+                return;
+            }
+        }
+
+        // byte slices and sequences of unsigned integers can be decoded
+        // as each other
+        if self.validation.is_some()
+            && ((old_class == Class::Bytes && self.is_uint_seq(self.new, new_id))
+                || (self.is_uint_seq(self.old, old_id) && new_class == Class::Bytes))
+        {
+            return;
+        }
+
+        // strings and byte slices can be decoded as each other if enabled
+        if self
+            .validation
+            .is_some_and(|options| options.strings_as_bytes)
+            && matches!(
+                (old_class, new_class),
+                (Class::Bytes, Class::String) | (Class::String, Class::Bytes)
+            )
+        {
+            return;
+        }
+
+        if old_class != new_class {
+            self.change(
+                type_name,
+                field_name,
+                Compatibility::Neither,
+                if self.validation.is_some() {
+                    format!(
+                        "sent as {}, but declared as {}",
+                        old_class.name(),
+                        new_class.name()
+                    )
+                } else {
+                    format!(
+                        "type changed from {} to {}",
+                        old_class.name(),
+                        new_class.name()
+                    )
+                },
+            );
+            return;
+        }
+
+        if !self.visited.insert((old_id, new_id)) {
+            return;
+        }
+
+        match (self.old.lookup(old_id), self.new.lookup(new_id)) {
+            (Some(WireType::Array(old)), Some(WireType::Array(new))) => {
+                if old.len != new.len {
+                    self.change(
+                        type_name,
+                        field_name,
+                        Compatibility::Neither,
+                        if self.validation.is_some() {
+                            format!(
+                                "sent as array of length {}, but declared with length {}",
+                                old.len, new.len
+                            )
+                        } else {
+                            format!("array length changed from {} to {}", old.len, new.len)
+                        },
+                    );
+                }
+                self.check_type(type_name, field_name, old.elem, new.elem);
+            }
+            (Some(WireType::Slice(old)), Some(WireType::Slice(new))) => {
+                self.check_type(type_name, field_name, old.elem, new.elem);
+            }
+            (Some(WireType::Map(old)), Some(WireType::Map(new))) => {
+                self.check_type(type_name, field_name, old.key, new.key);
+                self.check_type(type_name, field_name, old.elem, new.elem);
+            }
+            (Some(WireType::Struct(old)), Some(WireType::Struct(new))) => {
+                let old_name = &old.common.name;
+                let new_name = &new.common.name;
+                if field_name.is_some() && old_name != new_name && self.validation.is_none() {
+                    self.change(
+                        type_name,
+                        field_name,
+                        Compatibility::Both,
+                        format!("struct renamed from {} to {}", old_name, new_name),
+                    );
+                }
+                // named structs are reported under their own name, which
+                // is the sender's name when validating
+                let own_name = if self.validation.is_some() {
+                    old_name
+                } else {
+                    new_name
+                };
+                let struct_name: &str = if own_name.is_empty() {
+                    type_name
+                } else {
+                    own_name
+                };
+
+                let (removed, added) = if self.validation.is_some() {
+                    (
+                        "field is sent but not declared by the Rust type",
+                        "field is not defined by the sender",
+                    )
+                } else {
+                    (
+                        "field removed, old readers will see its zero value",
+                        "field added, old data will decode to its zero value",
+                    )
+                };
+                let compatibility = if self.validation.is_some() {
+                    Compatibility::Neither
+                } else {
+                    Compatibility::Both
+                };
+
+                let mut in_common = false;
+                for old_field in old.fields.iter() {
+                    let new_field = new
+                        .fields
+                        .iter()
+                        .find(|f| self.same_field_name(&old_field.name, &f.name));
+                    match new_field {
+                        Some(new_field) => {
+                            in_common = true;
+                            self.check_type(
+                                struct_name,
+                                Some(&old_field.name),
+                                old_field.id,
+                                new_field.id,
+                            );
+                        }
+                        None => self.change(
+                            struct_name,
+                            Some(&old_field.name),
+                            compatibility,
+                            removed.to_owned(),
+                        ),
+                    }
+                }
+                for new_field in new.fields.iter() {
+                    let in_old = old
+                        .fields
+                        .iter()
+                        .any(|f| self.same_field_name(&f.name, &new_field.name));
+                    if !in_old {
+                        self.change(
+                            struct_name,
+                            Some(&new_field.name),
+                            compatibility,
+                            added.to_owned(),
+                        );
+                    }
+                }
+                if !in_common && !old.fields.is_empty() && !new.fields.is_empty() {
+                    self.change(
+                        struct_name,
+                        None,
+                        Compatibility::Neither,
+                        "no fields in common".to_owned(),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+}
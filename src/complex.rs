@@ -0,0 +1,105 @@
+//! Complex numbers
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTupleStruct, Serializer};
+use serde_schema::types::{Type, TypeId};
+use serde_schema::{Schema, SchemaSerialize};
+
+use gob_schema::GobSchema;
+
+/// Name of the tuple struct which complex values are represented as
+/// in serde. Registering a type with this name maps it onto gob's
+/// builtin complex type.
+pub(crate) const COMPLEX_NAME: &str = "__gob_complex";
+
+macro_rules! complex_impl {
+    ($name:ident, $float:ident, $id:ident, $go:expr) => {
+        #[doc = concat!("A complex number, corresponding to Go's `", $go, "`.")]
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        pub struct $name {
+            pub re: $float,
+            pub im: $float,
+        }
+
+        impl $name {
+            pub fn new(re: $float, im: $float) -> $name {
+                $name { re, im }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                let mut tup = ser.serialize_tuple_struct(COMPLEX_NAME, 2)?;
+                tup.serialize_field(&self.re)?;
+                tup.serialize_field(&self.im)?;
+                tup.end()
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                let (re, im) =
+                    de.deserialize_tuple_struct(COMPLEX_NAME, 2, ComplexVisitor(PhantomData))?;
+                Ok($name { re, im })
+            }
+        }
+
+        impl SchemaSerialize for $name {
+            fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                schema.register_type(
+                    Type::build()
+                        .tuple_struct_type(COMPLEX_NAME, 2)
+                        .element(S::TypeId::$id)
+                        .element(S::TypeId::$id)
+                        .end(),
+                )
+            }
+        }
+
+        impl GobSchema for $name {
+            fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                <$name as SchemaSerialize>::schema_register(schema)
+            }
+        }
+
+        #[cfg(feature = "num-complex")]
+        impl From<::num_complex::Complex<$float>> for $name {
+            fn from(c: ::num_complex::Complex<$float>) -> $name {
+                $name { re: c.re, im: c.im }
+            }
+        }
+
+        #[cfg(feature = "num-complex")]
+        impl From<$name> for ::num_complex::Complex<$float> {
+            fn from(c: $name) -> ::num_complex::Complex<$float> {
+                ::num_complex::Complex::new(c.re, c.im)
+            }
+        }
+    };
+}
+
+complex_impl!(Complex64, f32, F32, "complex64");
+complex_impl!(Complex128, f64, F64, "complex128");
+
+struct ComplexVisitor<F>(PhantomData<F>);
+
+impl<'de, F: Deserialize<'de>> Visitor<'de> for ComplexVisitor<F> {
+    type Value = (F, F);
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a complex number")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let re = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let im = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok((re, im))
+    }
+}
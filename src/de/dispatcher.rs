@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+
+use error::Error;
+
+use super::Deserializer;
+
+type Handler<T> = Box<dyn for<'de> Fn(Deserializer<'de>) -> Result<T, Error>>;
+
+/// Routes values to handlers based on the type name declared by the sender.
+///
+/// This allows consuming streams that carry values of several different
+/// types, as is common with Go peers.
+pub struct Dispatcher<T> {
+    handlers: HashMap<String, Handler<T>>,
+}
+
+impl<T> Default for Dispatcher<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Dispatcher<T> {
+    pub fn new() -> Self {
+        Dispatcher {
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Decode values of the type called `name` as `V`, and map them
+    /// to the output using `f`.
+    pub fn register<V, F>(&mut self, name: &str, f: F) -> &mut Self
+    where
+        V: DeserializeOwned,
+        F: Fn(V) -> T + 'static,
+    {
+        self.register_with(name, move |de| V::deserialize(de).map(&f))
+    }
+
+    /// Handle values of the type called `name` with a custom function
+    /// that drives the deserializer itself.
+    pub fn register_with<F>(&mut self, name: &str, f: F) -> &mut Self
+    where
+        F: for<'de> Fn(Deserializer<'de>) -> Result<T, Error> + 'static,
+    {
+        self.handlers.insert(name.to_owned(), Box::new(f));
+        self
+    }
+
+    /// Decode a value with the handler registered for its type name.
+    pub fn dispatch(&self, mut de: Deserializer) -> Result<T, Error> {
+        let handler = {
+            let type_id = de.type_id()?;
+            match de.type_name()? {
+                Some(name) => self.handlers.get(name).ok_or_else(|| {
+                    Error::deserialize(format!("no handler registered for type {:?}", name))
+                })?,
+                None => return Err(Error::deserialize(format!("unknown type id {:?}", type_id))),
+            }
+        };
+        handler(de)
+    }
+}
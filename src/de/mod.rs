@@ -1,34 +1,141 @@
 //! Deserialization
 
+use std::any;
+use std::collections::HashMap;
 use std::io::{Cursor, Read};
 
 use bytes::Buf;
 use serde::de::{IgnoredAny, Visitor};
 use serde::{self, Deserialize};
+use serde_schema::SchemaSerialize;
 
+use compat::{self, Change};
 use error::Error;
-use internal::gob::{Message, Stream};
-use internal::types::{TypeId, Types, WireType};
+use internal::gob::{Message, SectionHeader, Stream};
+use internal::types::{Types, WireType};
 use internal::utils::{Bow, Buffer};
 
 use internal::de::FieldValueDeserializer;
 use internal::de::ValueDeserializer;
 
+pub use schema::TypeId;
+
+mod dispatcher;
+pub use self::dispatcher::Dispatcher;
+mod options;
+pub(crate) use self::options::Options;
+pub use self::options::{FieldMatching, InvalidUtf8};
+mod type_definitions;
+pub use self::type_definitions::TypeDefinitions;
+
 pub struct StreamDeserializer<R> {
-    defs: Types,
+    defs: TypeDefinitions,
+    options: Options,
     stream: Stream<R>,
     buffer: Buffer,
     prev_len: usize,
+    cache_validation: bool,
+    validated: HashMap<(TypeId, any::TypeId), Vec<Change>>,
 }
 
 impl<R> StreamDeserializer<R> {
     pub fn new(read: R) -> Self {
+        StreamDeserializer::with_type_definitions(read, TypeDefinitions::new())
+    }
+
+    /// Create a stream deserializer which already knows the given type
+    /// definitions, so that values of those types can be decoded
+    /// without their definitions being sent on the stream.
+    pub fn with_type_definitions(read: R, defs: TypeDefinitions) -> Self {
         StreamDeserializer {
-            defs: Types::new(),
+            defs,
+            options: Options::default(),
             stream: Stream::new(read),
             buffer: Buffer::new(),
             prev_len: 0,
+            cache_validation: false,
+            validated: HashMap::new(),
+        }
+    }
+
+    /// Set how struct field names on the wire are matched against the
+    /// field names of Rust types.
+    pub fn set_field_matching(&mut self, field_matching: FieldMatching) {
+        self.options.field_matching = field_matching;
+    }
+
+    /// Set whether struct fields which are declared by the sender but
+    /// missing from a message are decoded as their Go zero value.
+    ///
+    /// Go omits fields holding a zero value (`0`, `""`, empty slices and
+    /// maps, ...) from messages entirely. With this enabled, such fields
+    /// are presented to serde as if they had been sent, so that Rust types
+    /// decode without `#[serde(default)]`. `Option` fields decode as
+    /// `None`.
+    pub fn set_fill_zero_values(&mut self, fill_zero_values: bool) {
+        self.options.fill_zero_values = fill_zero_values;
+    }
+
+    /// Set how strings which are not valid UTF-8 are decoded.
+    pub fn set_invalid_utf8(&mut self, invalid_utf8: InvalidUtf8) {
+        self.options.invalid_utf8 = invalid_utf8;
+    }
+
+    /// Set whether strings and byte slices can be decoded into each
+    /// other's Rust types, so that a `String` accepts a Go `[]byte` and a
+    /// `Vec<u8>` or byte buffer accepts a Go `string`, as Go code freely
+    /// converts between the two.
+    pub fn set_strings_as_bytes(&mut self, strings_as_bytes: bool) {
+        self.options.strings_as_bytes = strings_as_bytes;
+    }
+
+    /// Set whether the results of `validate` are remembered for each
+    /// pair of incoming type id and Rust type, so that each type is only
+    /// checked once for the lifetime of the stream.
+    pub fn set_cache_validation(&mut self, cache_validation: bool) {
+        self.cache_validation = cache_validation;
+    }
+
+    /// Check that the next value on the stream can be decoded as a `T`,
+    /// without decoding it.
+    ///
+    /// The Rust type's schema is compared with the sender's definition
+    /// of the value's type, and every incompatibility found (missing or
+    /// extra fields, mismatched kinds of values, array lengths) is
+    /// reported in the returned error.
+    pub fn validate<T>(&mut self) -> Result<(), Error>
+    where
+        R: Read,
+        T: SchemaSerialize + 'static,
+    {
+        let header = match self.next_value_header()? {
+            Some(header) => header,
+            None => return Ok(()),
+        };
+        let type_id = TypeId(header.type_id);
+        let key = (type_id, any::TypeId::of::<T>());
+
+        let changes = match self.validated.get(&key) {
+            Some(changes) => changes.clone(),
+            None => {
+                let changes = check_rust_type::<T>(self.defs.types(), type_id, self.options)?;
+                if self.cache_validation {
+                    self.validated.insert(key, changes.clone());
+                }
+                changes
+            }
+        };
+
+        if changes.is_empty() {
+            return Ok(());
         }
+        let reasons: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+        Err(Error::deserialize(format!(
+            "type {:?} cannot be decoded as {}: {}",
+            self.defs.types().name(type_id).unwrap_or(""),
+            any::type_name::<T>(),
+            reasons.join("; ")
+        )))
     }
 
     pub fn deserialize<'de, T>(&'de mut self) -> Result<Option<T>, Error>
@@ -43,7 +150,79 @@ impl<R> StreamDeserializer<R> {
         }
     }
 
+    /// Decode the next value on the stream with the handler that was
+    /// registered for its type name.
+    pub fn dispatch<T>(&mut self, dispatcher: &Dispatcher<T>) -> Result<Option<T>, Error>
+    where
+        R: Read,
+    {
+        if let Some(deserializer) = self.deserializer()? {
+            Ok(Some(dispatcher.dispatch(deserializer)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Look at the header of the next value on the stream without
+    /// decoding it.
+    ///
+    /// Type definitions preceding the value are consumed. Calling `peek`
+    /// repeatedly returns the same header until the value is decoded or
+    /// skipped.
+    pub fn peek(&mut self) -> Result<Option<MessageHeader<'_>>, Error>
+    where
+        R: Read,
+    {
+        let header = match self.next_value_header()? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let type_id = TypeId(header.type_id);
+        Ok(Some(MessageHeader {
+            type_id,
+            type_name: self.defs.types().name(type_id),
+            payload_len: header.payload_range.len(),
+        }))
+    }
+
+    /// Advance past the next value on the stream without decoding it.
+    ///
+    /// Type definitions preceding the value are still consumed, so that
+    /// later values can refer to them. Returns `false` if the end of the
+    /// stream has been reached.
+    pub fn skip(&mut self) -> Result<bool, Error>
+    where
+        R: Read,
+    {
+        match self.next_value_header()? {
+            Some(header) => {
+                self.buffer.advance(header.payload_range.end);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
     pub fn deserializer<'de>(&'de mut self) -> Result<Option<Deserializer<'de>>, Error>
+    where
+        R: Read,
+    {
+        let header = match self.next_value_header()? {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+        let slice = &self.buffer.bytes()[header.payload_range.clone()];
+        let msg = Message::new(Cursor::new(slice));
+        self.prev_len = header.payload_range.end;
+        Ok(Some(Deserializer {
+            defs: Bow::Borrowed(&mut self.defs),
+            options: self.options,
+            msg,
+            type_id: Some(TypeId(header.type_id)),
+        }))
+    }
+
+    fn next_value_header(&mut self) -> Result<Option<SectionHeader>, Error>
     where
         R: Read,
     {
@@ -58,20 +237,18 @@ impl<R> StreamDeserializer<R> {
             };
 
             if header.type_id >= 0 {
-                let slice = &self.buffer.bytes()[header.payload_range.clone()];
-                let msg = Message::new(Cursor::new(slice));
-                self.prev_len = header.payload_range.end;
-                return Ok(Some(Deserializer {
-                    defs: Bow::Borrowed(&mut self.defs),
-                    msg: msg,
-                    type_id: Some(TypeId(header.type_id)),
-                }));
+                return Ok(Some(header));
             }
 
             let wire_type = {
                 let slice = &self.buffer.bytes()[header.payload_range.clone()];
                 let mut msg = Message::new(Cursor::new(slice));
-                let de = FieldValueDeserializer::new(TypeId::WIRE_TYPE, &self.defs, &mut msg);
+                let de = FieldValueDeserializer::new(
+                    TypeId::WIRE_TYPE,
+                    self.defs.types(),
+                    Options::default(),
+                    &mut msg,
+                );
                 WireType::deserialize(de)
             }?;
 
@@ -79,11 +256,21 @@ impl<R> StreamDeserializer<R> {
                 return Err(Error::deserialize("type id mismatch"));
             }
 
-            self.defs.insert(wire_type);
+            let section = &self.buffer.bytes()[..header.payload_range.end];
+            self.defs.insert(wire_type, section);
             self.buffer.advance(header.payload_range.end);
         }
     }
 
+    /// The type definitions received so far.
+    pub fn type_definitions(&self) -> &TypeDefinitions {
+        &self.defs
+    }
+
+    pub(crate) fn into_type_definitions(self) -> TypeDefinitions {
+        self.defs
+    }
+
     pub fn get_ref(&self) -> &R {
         self.stream.get_ref()
     }
@@ -97,8 +284,52 @@ impl<R> StreamDeserializer<R> {
     }
 }
 
+fn check_rust_type<T: SchemaSerialize>(
+    wire_types: &Types,
+    type_id: TypeId,
+    options: Options,
+) -> Result<Vec<Change>, Error> {
+    let mut schema = ::schema::Schema::new();
+    let rust_id = T::schema_register(&mut schema)?;
+    let rust_defs = schema.type_definitions()?;
+    Ok(compat::check_value_type(
+        wire_types,
+        type_id,
+        rust_defs.types(),
+        rust_id,
+        options,
+    ))
+}
+
+/// Metadata about a value on a stream, as returned by
+/// `StreamDeserializer::peek`.
+#[derive(Clone, Debug)]
+pub struct MessageHeader<'a> {
+    type_id: TypeId,
+    type_name: Option<&'a str>,
+    payload_len: usize,
+}
+
+impl<'a> MessageHeader<'a> {
+    /// The id of the value's type.
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// The name the sender declared for the value's type, if known.
+    pub fn type_name(&self) -> Option<&'a str> {
+        self.type_name
+    }
+
+    /// The length in bytes of the encoded value, excluding the type id.
+    pub fn payload_len(&self) -> usize {
+        self.payload_len
+    }
+}
+
 pub struct Deserializer<'de> {
-    defs: Bow<'de, Types>,
+    defs: Bow<'de, TypeDefinitions>,
+    options: Options,
     msg: Message<Cursor<&'de [u8]>>,
     type_id: Option<TypeId>,
 }
@@ -106,41 +337,119 @@ pub struct Deserializer<'de> {
 impl<'de> Deserializer<'de> {
     pub fn from_slice(input: &'de [u8]) -> Deserializer<'de> {
         Deserializer {
-            defs: Bow::Owned(Types::new()),
+            defs: Bow::Owned(TypeDefinitions::new()),
+            options: Options::default(),
             msg: Message::new(Cursor::new(input)),
             type_id: None,
         }
     }
 
-    fn value_deserializer<'t>(&'t mut self) -> Result<ValueDeserializer<'t, 'de>, Error> {
+    /// Create a deserializer for a message whose type definitions were
+    /// received separately.
+    ///
+    /// Any type definitions preceding the value in `input` are added to
+    /// `defs`.
+    pub fn from_slice_with_type_definitions(
+        input: &'de [u8],
+        defs: &'de mut TypeDefinitions,
+    ) -> Deserializer<'de> {
+        Deserializer {
+            defs: Bow::Borrowed(defs),
+            options: Options::default(),
+            msg: Message::new(Cursor::new(input)),
+            type_id: None,
+        }
+    }
+
+    /// Set how struct field names on the wire are matched against the
+    /// field names of Rust types.
+    pub fn set_field_matching(&mut self, field_matching: FieldMatching) {
+        self.options.field_matching = field_matching;
+    }
+
+    /// Set whether struct fields which are declared by the sender but
+    /// missing from a message are decoded as their Go zero value.
+    ///
+    /// Go omits fields holding a zero value (`0`, `""`, empty slices and
+    /// maps, ...) from messages entirely. With this enabled, such fields
+    /// are presented to serde as if they had been sent, so that Rust types
+    /// decode without `#[serde(default)]`. `Option` fields decode as
+    /// `None`.
+    pub fn set_fill_zero_values(&mut self, fill_zero_values: bool) {
+        self.options.fill_zero_values = fill_zero_values;
+    }
+
+    /// Set how strings which are not valid UTF-8 are decoded.
+    pub fn set_invalid_utf8(&mut self, invalid_utf8: InvalidUtf8) {
+        self.options.invalid_utf8 = invalid_utf8;
+    }
+
+    /// Set whether strings and byte slices can be decoded into each
+    /// other's Rust types, so that a `String` accepts a Go `[]byte` and a
+    /// `Vec<u8>` or byte buffer accepts a Go `string`, as Go code freely
+    /// converts between the two.
+    pub fn set_strings_as_bytes(&mut self, strings_as_bytes: bool) {
+        self.options.strings_as_bytes = strings_as_bytes;
+    }
+
+    /// Returns the id of the type of the value about to be decoded.
+    ///
+    /// When reading from a slice, any type definitions preceding the
+    /// value are consumed in order to find it.
+    pub fn type_id(&mut self) -> Result<TypeId, Error> {
         if let Some(type_id) = self.type_id {
-            return Ok(ValueDeserializer::new(type_id, &self.defs, &mut self.msg));
+            return Ok(type_id);
         }
 
         loop {
+            let start = self.msg.get_ref().position() as usize;
             let _len = self.msg.read_bytes_len()?;
             let type_id = self.msg.read_int()?;
 
             if type_id >= 0 {
-                return Ok(ValueDeserializer::new(
-                    TypeId(type_id),
-                    &self.defs,
-                    &mut self.msg,
-                ));
+                self.type_id = Some(TypeId(type_id));
+                return Ok(TypeId(type_id));
             }
 
             let wire_type = {
-                let de = FieldValueDeserializer::new(TypeId::WIRE_TYPE, &self.defs, &mut self.msg);
+                let de = FieldValueDeserializer::new(
+                    TypeId::WIRE_TYPE,
+                    self.defs.types(),
+                    Options::default(),
+                    &mut self.msg,
+                );
                 WireType::deserialize(de)
             }?;
 
             if -type_id != wire_type.common().id.0 {
-                return Err(serde::de::Error::custom(format!("type id mismatch")));
+                return Err(serde::de::Error::custom("type id mismatch"));
             }
 
-            self.defs.insert(wire_type);
+            let end = self.msg.get_ref().position() as usize;
+            let section = &self.msg.get_ref().get_ref()[start..end];
+            self.defs.insert(wire_type, section);
         }
     }
+
+    /// Returns the name the sender declared for the type of the value
+    /// about to be decoded, e.g. `"Point"` or `"int"`.
+    ///
+    /// Unnamed composite types such as slices and maps may have an
+    /// empty name.
+    pub fn type_name(&mut self) -> Result<Option<&str>, Error> {
+        let type_id = self.type_id()?;
+        Ok(self.defs.types().name(type_id))
+    }
+
+    fn value_deserializer<'t>(&'t mut self) -> Result<ValueDeserializer<'t, 'de>, Error> {
+        let type_id = self.type_id()?;
+        Ok(ValueDeserializer::new(
+            type_id,
+            self.defs.types(),
+            self.options,
+            &mut self.msg,
+        ))
+    }
 }
 
 impl<'de> serde::Deserializer<'de> for Deserializer<'de> {
@@ -179,6 +488,62 @@ impl<'de> serde::Deserializer<'de> for Deserializer<'de> {
             .deserialize_struct(name, fields, visitor)
     }
 
+    fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple<V>(mut self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_str<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_str(visitor)
+    }
+
+    fn deserialize_string<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_string(visitor)
+    }
+
+    fn deserialize_bytes<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_i128<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_i128(visitor)
+    }
+
+    fn deserialize_u128<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_u128(visitor)
+    }
+
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
@@ -203,9 +568,17 @@ impl<'de> serde::Deserializer<'de> for Deserializer<'de> {
         visitor.visit_unit()
     }
 
+    #[inline]
+    fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value_deserializer()?.deserialize_option(visitor)
+    }
+
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 str string bytes
-        byte_buf option unit_struct newtype_struct seq tuple
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64
+        unit_struct newtype_struct
         tuple_struct map identifier ignored_any
     }
 }
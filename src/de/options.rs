@@ -0,0 +1,89 @@
+use std::borrow::Cow;
+
+use internal::utils::{eq_ignore_case_and_underscores, to_snake_case};
+
+/// Controls how struct field names on the wire are matched against the
+/// field names of the Rust type being decoded.
+///
+/// Go requires struct fields to be exported, so their names on the wire
+/// are capitalised (e.g. `UserID`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldMatching {
+    /// Names must match exactly, which is what Go does.
+    #[default]
+    Exact,
+    /// Names are matched ignoring case, so `Name` matches `name`.
+    CaseInsensitive,
+    /// Names are matched ignoring case and underscores, so that Rust
+    /// `snake_case` fields match Go `PascalCase` fields, e.g. `user_id`
+    /// matches both `UserId` and `UserID`.
+    ///
+    /// When the Rust field names are not known up front (for example when
+    /// decoding into a map), wire names are converted to `snake_case`.
+    SnakeCase,
+}
+
+impl FieldMatching {
+    /// Resolves the name of a field on the wire to the name that is
+    /// handed to serde.
+    ///
+    /// An exact match always takes precedence. Names which do not match
+    /// any of the given fields are passed through unchanged.
+    pub(crate) fn resolve<'a>(
+        self,
+        name: &'a str,
+        fields: Option<&'static [&'static str]>,
+    ) -> Cow<'a, str> {
+        let fields = match fields {
+            Some(fields) => fields,
+            None => {
+                return match self {
+                    FieldMatching::SnakeCase => Cow::Owned(to_snake_case(name)),
+                    _ => Cow::Borrowed(name),
+                };
+            }
+        };
+        if fields.contains(&name) {
+            return Cow::Borrowed(name);
+        }
+        let found = fields.iter().find(|field| self.matches(field, name));
+        Cow::Borrowed(found.map_or(name, |field| *field))
+    }
+
+    /// Returns `true` if the name of a field on the wire matches the
+    /// field `field`.
+    pub(crate) fn matches(self, field: &str, name: &str) -> bool {
+        match self {
+            FieldMatching::Exact => field == name,
+            FieldMatching::CaseInsensitive => field.eq_ignore_ascii_case(name),
+            FieldMatching::SnakeCase => eq_ignore_case_and_underscores(field, name),
+        }
+    }
+}
+
+/// Controls how strings which are not valid UTF-8 are decoded.
+///
+/// Go strings can hold arbitrary bytes, while Rust strings must be valid
+/// UTF-8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InvalidUtf8 {
+    /// Decoding the string fails.
+    #[default]
+    Reject,
+    /// Invalid sequences are replaced with U+FFFD, like
+    /// `String::from_utf8_lossy` does.
+    Replace,
+    /// The string is handed to serde as bytes, so that it can be decoded
+    /// into a byte buffer, or into a type such as a dynamic value type
+    /// which accepts both. Decoding it into a `String` still fails.
+    Bytes,
+}
+
+/// Decoding options which are shared by all values of a stream.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Options {
+    pub field_matching: FieldMatching,
+    pub fill_zero_values: bool,
+    pub invalid_utf8: InvalidUtf8,
+    pub strings_as_bytes: bool,
+}
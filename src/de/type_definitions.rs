@@ -0,0 +1,78 @@
+use error::Error;
+use internal::types::{TypeId, Types, WireType};
+
+use super::StreamDeserializer;
+
+/// The type definitions received on a gob stream.
+///
+/// Definitions are kept both in decoded form and as the raw gob
+/// messages they were sent as, so that they can be persisted with
+/// `as_bytes` and restored with `from_bytes`. This allows decoding
+/// messages which refer to types that were defined out-of-band.
+#[derive(Clone, Debug, Default)]
+pub struct TypeDefinitions {
+    types: Types,
+    bytes: Vec<u8>,
+    // the id and length of each message in `bytes`, in order
+    sections: Vec<(TypeId, usize)>,
+}
+
+impl TypeDefinitions {
+    pub fn new() -> TypeDefinitions {
+        TypeDefinitions::default()
+    }
+
+    /// Restore type definitions from a sequence of gob type definition
+    /// messages, such as the output of `as_bytes`.
+    ///
+    /// Fails if the input contains anything other than type definitions.
+    pub fn from_bytes(bytes: &[u8]) -> Result<TypeDefinitions, Error> {
+        let mut stream = StreamDeserializer::new(bytes);
+        if stream.skip()? {
+            return Err(Error::deserialize(
+                "expected only type definitions, found a value",
+            ));
+        }
+        Ok(stream.into_type_definitions())
+    }
+
+    /// The type definition messages, encoded as they were received.
+    ///
+    /// The bytes form a valid gob stream on their own, and can be
+    /// prepended to a stream of value-only messages.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns `true` if no types have been defined.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub(crate) fn types(&self) -> &Types {
+        &self.types
+    }
+
+    /// Adds a definition along with the message it was received as. A
+    /// type id which is defined again, as happens when reading a stream
+    /// from a new encoder, has its previous message replaced.
+    pub(crate) fn insert(&mut self, wire_type: WireType, section: &[u8]) {
+        let id = wire_type.common().id;
+        if self.types.lookup(id) == Some(&wire_type) {
+            return;
+        }
+        self.types.insert(wire_type);
+        let mut start = 0;
+        for &mut (section_id, ref mut len) in &mut self.sections {
+            if section_id == id {
+                self.bytes
+                    .splice(start..start + *len, section.iter().cloned());
+                *len = section.len();
+                return;
+            }
+            start += *len;
+        }
+        self.sections.push((id, section.len()));
+        self.bytes.extend_from_slice(section);
+    }
+}
@@ -31,6 +31,14 @@ impl Error {
     pub fn kind(&self) -> ErrorKind {
         self.kind
     }
+
+    fn kind_description(&self) -> &'static str {
+        match self.kind {
+            ErrorKind::Io(_) => "i/o error",
+            ErrorKind::Serialize => "serialize error",
+            ErrorKind::Deserialize => "deserialize error",
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -38,7 +46,7 @@ impl fmt::Display for Error {
         match self.inner {
             ErrorInner::Io(ref err) => write!(f, "i/o error: {}", err),
             ErrorInner::Other(ref msg) => {
-                write!(f, "{}: {}", ::std::error::Error::description(self), msg)
+                write!(f, "{}: {}", self.kind_description(), msg)
             }
         }
     }
@@ -46,11 +54,7 @@ impl fmt::Display for Error {
 
 impl ::std::error::Error for Error {
     fn description(&self) -> &str {
-        match self.kind {
-            ErrorKind::Io(_) => "i/o error",
-            ErrorKind::Serialize => "serialize error",
-            ErrorKind::Deserialize => "deserialize error",
-        }
+        self.kind_description()
     }
 }
 
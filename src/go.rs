@@ -0,0 +1,365 @@
+//! Adapters for Go standard library types, sent in the wire forms Go uses
+//! for them.
+//!
+//! | Rust | Go |
+//! |------|----|
+//! | `Duration` | `time.Duration` |
+//! | `Time` | `time.Time` |
+//! | `Ip` | `net.IP` |
+//! | `Uuid` (with the `uuid` feature) | `github.com/google/uuid.UUID` |
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str;
+use std::time;
+
+use byteorder::{BigEndian, ByteOrder};
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{self as ser, Serialize, Serializer};
+use serde_schema::types::{Type, TypeId};
+use serde_schema::{Schema, SchemaSerialize};
+
+use gob_schema::GobSchema;
+#[cfg(feature = "uuid")]
+use schema::OPAQUE_NAME;
+use schema::{OPAQUE_GOB_NAME, OPAQUE_TEXT_NAME};
+
+fn register_opaque<S: Schema>(
+    schema: &mut S,
+    marker: &'static str,
+    name: &'static str,
+) -> Result<S::TypeId, S::Error> {
+    schema.register_type(
+        Type::build()
+            .struct_type(marker, 1)
+            .field(name, S::TypeId::BYTES)
+            .end(),
+    )
+}
+
+/// A duration, sent as Go's `time.Duration`, an `int64` count of
+/// nanoseconds.
+///
+/// Durations longer than about 292 years do not fit and fail to
+/// serialize, as do negative Go durations when decoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration(pub time::Duration);
+
+impl Serialize for Duration {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        let nanos = self.0.as_nanos();
+        if nanos > i64::MAX as u128 {
+            return Err(ser::Error::custom(
+                "duration does not fit in a Go time.Duration",
+            ));
+        }
+        ser.serialize_i64(nanos as i64)
+    }
+}
+
+impl<'de> Deserialize<'de> for Duration {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let nanos = i64::deserialize(de)?;
+        if nanos < 0 {
+            return Err(de::Error::custom("negative Go time.Duration"));
+        }
+        Ok(Duration(time::Duration::from_nanos(nanos as u64)))
+    }
+}
+
+impl SchemaSerialize for Duration {
+    fn schema_register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::I64)
+    }
+}
+
+impl GobSchema for Duration {
+    fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::I64)
+    }
+}
+
+impl From<time::Duration> for Duration {
+    fn from(duration: time::Duration) -> Duration {
+        Duration(duration)
+    }
+}
+
+impl From<Duration> for time::Duration {
+    fn from(duration: Duration) -> time::Duration {
+        duration.0
+    }
+}
+
+// seconds from January 1 of year 1 UTC, where Go counts from, to the
+// Unix epoch
+const GO_UNIX_OFFSET: i64 = 62_135_596_800;
+
+/// A point in time, sent as Go's `time.Time` in the format of
+/// `Time.GobEncode`.
+///
+/// That is a version byte, the seconds since January 1 of year 1 UTC as a
+/// big-endian `i64`, the nanoseconds as a big-endian `i32` and the zone
+/// offset in minutes east of UTC as a big-endian `i16`, which is -1 for
+/// UTC. Version 2 adds a byte with the seconds of the zone offset. Go
+/// decodes an offset into a zone without a name, or into its local zone
+/// if that has the same offset.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Time {
+    /// The seconds since the Unix epoch.
+    pub secs: i64,
+    /// The nanoseconds within the second, less than 1,000,000,000.
+    pub nanos: u32,
+    /// The zone offset in seconds east of UTC, `None` for `time.UTC`.
+    ///
+    /// Offsets of -1 minute cannot be told apart from UTC, and fail to
+    /// serialize like in Go, as do offsets of more than about 22 days.
+    pub offset: Option<i32>,
+}
+
+impl Serialize for Time {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        if self.nanos >= 1_000_000_000 {
+            return Err(ser::Error::custom("nanoseconds out of range"));
+        }
+        let secs = self
+            .secs
+            .checked_add(GO_UNIX_OFFSET)
+            .ok_or_else(|| ser::Error::custom("time does not fit in a Go time.Time"))?;
+        let (minutes, seconds) = match self.offset {
+            None => (-1, 0),
+            Some(offset) => {
+                let minutes = offset / 60;
+                if minutes < i16::MIN as i32 || minutes == -1 || minutes > i16::MAX as i32 {
+                    return Err(ser::Error::custom("unexpected zone offset"));
+                }
+                (minutes as i16, (offset % 60) as i8)
+            }
+        };
+
+        let mut buf = [0; 16];
+        buf[0] = if seconds == 0 { 1 } else { 2 };
+        BigEndian::write_i64(&mut buf[1..9], secs);
+        BigEndian::write_i32(&mut buf[9..13], self.nanos as i32);
+        BigEndian::write_i16(&mut buf[13..15], minutes);
+        buf[15] = seconds as u8;
+        let len = if seconds == 0 { 15 } else { 16 };
+        ser.serialize_bytes(&buf[..len])
+    }
+}
+
+struct TimeVisitor;
+
+impl<'de> Visitor<'de> for TimeVisitor {
+    type Value = Time;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Go time.Time")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Time, E> {
+        match (v.first(), v.len()) {
+            (Some(&1), 15) | (Some(&2), 16) => {}
+            _ => return Err(E::invalid_value(de::Unexpected::Bytes(v), &self)),
+        }
+        let secs = BigEndian::read_i64(&v[1..9])
+            .checked_sub(GO_UNIX_OFFSET)
+            .ok_or_else(|| E::custom("Go time.Time out of range"))?;
+        let nanos = BigEndian::read_i32(&v[9..13]);
+        if !(0..1_000_000_000).contains(&nanos) {
+            return Err(E::custom("nanoseconds out of range"));
+        }
+        let mut offset = i32::from(BigEndian::read_i16(&v[13..15])) * 60;
+        if let Some(&seconds) = v.get(15) {
+            offset += i32::from(seconds as i8);
+        }
+        Ok(Time {
+            secs,
+            nanos: nanos as u32,
+            offset: if offset == -60 { None } else { Some(offset) },
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Time {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_bytes(TimeVisitor)
+    }
+}
+
+impl SchemaSerialize for Time {
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        register_opaque(schema, OPAQUE_GOB_NAME, "Time")
+    }
+}
+
+impl GobSchema for Time {
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        register_opaque(schema, OPAQUE_GOB_NAME, "Time")
+    }
+}
+
+/// An IP address, sent as Go's `net.IP` in the format of
+/// `IP.MarshalText`.
+///
+/// IPv4-mapped IPv6 addresses are sent in dotted IPv4 form, as Go does not
+/// tell them apart from IPv4 addresses, and decode as `IpAddr::V4`. Raw 4
+/// or 16 byte addresses are accepted when decoding as well.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ip(pub IpAddr);
+
+impl Serialize for Ip {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        let text = match self.0 {
+            IpAddr::V6(addr) => match addr.to_ipv4_mapped() {
+                Some(addr) => addr.to_string(),
+                None => addr.to_string(),
+            },
+            IpAddr::V4(addr) => addr.to_string(),
+        };
+        ser.serialize_bytes(text.as_bytes())
+    }
+}
+
+fn unmap_ipv4(addr: IpAddr) -> Ip {
+    Ip(match addr {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(addr, IpAddr::V4),
+        addr => addr,
+    })
+}
+
+struct IpVisitor;
+
+impl<'de> Visitor<'de> for IpVisitor {
+    type Value = Ip;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Go net.IP")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Ip, E> {
+        v.parse().map(unmap_ipv4).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Ip, E> {
+        if let Some(addr) = str::from_utf8(v).ok().and_then(|text| text.parse().ok()) {
+            return Ok(unmap_ipv4(addr));
+        }
+        match v.len() {
+            4 => Ok(Ip(IpAddr::V4(Ipv4Addr::new(v[0], v[1], v[2], v[3])))),
+            16 => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(v);
+                Ok(unmap_ipv4(IpAddr::V6(Ipv6Addr::from(octets))))
+            }
+            _ => Err(E::invalid_value(de::Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Ip {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_bytes(IpVisitor)
+    }
+}
+
+impl SchemaSerialize for Ip {
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        register_opaque(schema, OPAQUE_TEXT_NAME, "IP")
+    }
+}
+
+impl GobSchema for Ip {
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        register_opaque(schema, OPAQUE_TEXT_NAME, "IP")
+    }
+}
+
+impl From<IpAddr> for Ip {
+    fn from(addr: IpAddr) -> Ip {
+        Ip(addr)
+    }
+}
+
+impl From<Ip> for IpAddr {
+    fn from(ip: Ip) -> IpAddr {
+        ip.0
+    }
+}
+
+/// A UUID, sent as `github.com/google/uuid.UUID` in the format of
+/// `UUID.MarshalBinary`, its 16 bytes.
+///
+/// Plain `uuid::Uuid` values are sent as Go strings instead. The text form
+/// is accepted when decoding as well.
+#[cfg(feature = "uuid")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uuid(pub ::uuid::Uuid);
+
+#[cfg(feature = "uuid")]
+impl Serialize for Uuid {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_bytes(self.0.as_bytes())
+    }
+}
+
+#[cfg(feature = "uuid")]
+struct UuidVisitor;
+
+#[cfg(feature = "uuid")]
+impl<'de> Visitor<'de> for UuidVisitor {
+    type Value = Uuid;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Go uuid.UUID")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Uuid, E> {
+        ::uuid::Uuid::parse_str(v).map(Uuid).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Uuid, E> {
+        if let Ok(uuid) = ::uuid::Uuid::from_slice(v) {
+            return Ok(Uuid(uuid));
+        }
+        match str::from_utf8(v) {
+            Ok(text) => self.visit_str(text),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl<'de> Deserialize<'de> for Uuid {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_bytes(UuidVisitor)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl SchemaSerialize for Uuid {
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        register_opaque(schema, OPAQUE_NAME, "UUID")
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl GobSchema for Uuid {
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        register_opaque(schema, OPAQUE_NAME, "UUID")
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<::uuid::Uuid> for Uuid {
+    fn from(uuid: ::uuid::Uuid) -> Uuid {
+        Uuid(uuid)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<Uuid> for ::uuid::Uuid {
+    fn from(uuid: Uuid) -> ::uuid::Uuid {
+        uuid.0
+    }
+}
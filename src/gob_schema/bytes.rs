@@ -0,0 +1,21 @@
+use bytes::{Bytes, BytesMut};
+use serde_schema::types::TypeId;
+use serde_schema::Schema;
+
+use super::GobSchema;
+
+/// Sent as a Go `[]byte`.
+impl GobSchema for Bytes {
+    #[inline]
+    fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::BYTES)
+    }
+}
+
+/// Sent as a Go `[]byte`.
+impl GobSchema for BytesMut {
+    #[inline]
+    fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::BYTES)
+    }
+}
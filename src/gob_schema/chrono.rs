@@ -0,0 +1,41 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde_schema::types::TypeId;
+use serde_schema::Schema;
+
+use super::GobSchema;
+
+// Go's `time.Time` marshals itself in a binary format of its own, so
+// dates and times are sent as the ISO 8601 strings chrono serializes to,
+// which Go can parse with `time.Parse`.
+
+/// Sent as a Go `string` in RFC 3339 format, see `time.RFC3339Nano`.
+impl<Tz: TimeZone> GobSchema for DateTime<Tz> {
+    #[inline]
+    fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::STR)
+    }
+}
+
+/// Sent as a Go `string`, e.g. `"2015-09-18T23:56:04"`.
+impl GobSchema for NaiveDateTime {
+    #[inline]
+    fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::STR)
+    }
+}
+
+/// Sent as a Go `string`, e.g. `"2015-09-18"`.
+impl GobSchema for NaiveDate {
+    #[inline]
+    fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::STR)
+    }
+}
+
+/// Sent as a Go `string`, e.g. `"23:56:04"`.
+impl GobSchema for NaiveTime {
+    #[inline]
+    fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::STR)
+    }
+}
@@ -0,0 +1,25 @@
+use indexmap::{IndexMap, IndexSet};
+use serde_schema::types::Type;
+use serde_schema::Schema;
+
+use super::GobSchema;
+
+/// Sent as a Go `map[K]V`. Go maps are unordered, so the order of the
+/// entries is not preserved by Go receivers.
+impl<K: GobSchema, V: GobSchema, H> GobSchema for IndexMap<K, V, H> {
+    #[inline]
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let key = K::register(schema)?;
+        let value = V::register(schema)?;
+        schema.register_type(Type::build().map_type(key, value))
+    }
+}
+
+/// Sent as a Go `[]T`, in insertion order.
+impl<T: GobSchema, H> GobSchema for IndexSet<T, H> {
+    #[inline]
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let id = T::register(schema)?;
+        schema.register_type(Type::build().seq_type(None, id))
+    }
+}
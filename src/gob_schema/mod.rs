@@ -0,0 +1,261 @@
+//! Registration of Rust types as the Go types they are sent as
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, LinkedList, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use serde_bytes::{ByteBuf, Bytes};
+use serde_schema::types::{Type, TypeId};
+use serde_schema::Schema;
+
+#[cfg(feature = "bytes-serde")]
+mod bytes;
+#[cfg(feature = "chrono")]
+mod chrono;
+#[cfg(feature = "indexmap")]
+mod indexmap;
+#[cfg(feature = "uuid")]
+mod uuid;
+
+/// Registers the Go type which values of a Rust type are sent as.
+///
+/// This serves the same purpose as `SchemaSerialize`, but is owned by
+/// this crate, so that it can be implemented for types which
+/// `serde_schema` does not cover. `#[derive(GobSchema)]` implements both
+/// traits, and registers the types of fields through this trait if they
+/// implement it, falling back to `SchemaSerialize` otherwise.
+///
+/// | Rust | Go |
+/// |------|----|
+/// | `bool` | `bool` |
+/// | `i8` ... `i64`, `isize`, `char` | `int` (and sized variants) |
+/// | `u8` ... `u64`, `usize` | `uint` (and sized variants) |
+/// | `i128`, `u128` | `*big.Int`, see `WideIntegers` |
+/// | `f32`, `f64` | `float32`, `float64` |
+/// | `Complex64`, `Complex128` | `complex64`, `complex128` |
+/// | `BigInt`, `BigRat`, `BigFloat` | `*big.Int`, `*big.Rat`, `*big.Float` |
+/// | `str`, `String` | `string` |
+/// | `serde_bytes::Bytes`, `serde_bytes::ByteBuf` | `[]byte` |
+/// | `Vec<u8>`, `[u8; N]` and other sequences of `u8` | `[]byte`, see `ByteSequences` |
+/// | `[T]`, `Vec<T>`, `VecDeque<T>`, `LinkedList<T>`, `BinaryHeap<T>` | `[]T` |
+/// | `BTreeSet<T>`, `HashSet<T>` | `[]T` |
+/// | `StructMapSet<S>`, `BoolMapSet<S>` | `map[T]struct{}`, `map[T]bool` |
+/// | `[T; N]` | `[N]T` |
+/// | `(T, T, ...)` | `[N]T` |
+/// | `(T0, T1, ...)` | `struct { F0 T0; F1 T1; ... }` |
+/// | `BTreeMap<K, V>`, `HashMap<K, V>` | `map[K]V` |
+/// | `Option<T>` | `*T`, nil pointers are not sent, or `sql.Null[T]`, see `NoneElements` |
+/// | `NullString`, `NullInt64`, ... `NullTime` | `sql.NullString`, `sql.NullInt64`, ... `sql.NullTime` |
+/// | `go::Duration`, `go::Time`, `go::Ip` | `time.Duration`, `time.Time`, `net.IP` |
+/// | `&T`, `&mut T`, `Box<T>`, `Rc<T>`, `Arc<T>`, `Cow<T>` | `T` or `*T` |
+///
+/// Note that serde only implements `Serialize` for arrays of up to 32
+/// elements, and for `Rc` and `Arc` with its `rc` feature enabled.
+///
+/// With the corresponding cargo features, it is also implemented for:
+///
+/// | Feature | Rust | Go |
+/// |---------|------|----|
+/// | `uuid` | `uuid::Uuid` | `string` |
+/// | `uuid` | `go::Uuid` | `uuid.UUID` |
+/// | `chrono` | `chrono::DateTime<Tz>`, `NaiveDateTime`, `NaiveDate`, `NaiveTime` | `string` |
+/// | `bytes-serde` | `bytes::Bytes`, `bytes::BytesMut` | `[]byte` |
+/// | `indexmap` | `indexmap::IndexMap<K, V>` | `map[K]V` |
+/// | `indexmap` | `indexmap::IndexSet<T>` | `[]T` |
+pub trait GobSchema {
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error>;
+
+    /// Registers the types of the fields which values of the type add to
+    /// a struct they are flattened into with `#[serde(flatten)]`, and
+    /// appends them to `fields`.
+    ///
+    /// `#[derive(GobSchema)]` implements this for structs with named
+    /// fields. Other types cannot be flattened.
+    fn register_flattened<S: Schema>(
+        schema: &mut S,
+        fields: &mut Vec<(&'static str, S::TypeId)>,
+    ) -> Result<(), S::Error> {
+        let _ = (schema, fields);
+        Err(::serde::ser::Error::custom(
+            "only structs with named fields can be flattened",
+        ))
+    }
+}
+
+macro_rules! primitive_impl {
+    ($t:ty, $id:ident) => {
+        impl GobSchema for $t {
+            #[inline]
+            fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+                Ok(S::TypeId::$id)
+            }
+        }
+    };
+}
+
+primitive_impl!(bool, BOOL);
+primitive_impl!(i8, I8);
+primitive_impl!(i16, I16);
+primitive_impl!(i32, I32);
+primitive_impl!(i64, I64);
+primitive_impl!(isize, I64);
+primitive_impl!(u8, U8);
+primitive_impl!(u16, U16);
+primitive_impl!(u32, U32);
+primitive_impl!(u64, U64);
+primitive_impl!(usize, U64);
+primitive_impl!(f32, F32);
+primitive_impl!(f64, F64);
+primitive_impl!(char, CHAR);
+primitive_impl!(str, STR);
+primitive_impl!(String, STR);
+primitive_impl!(ByteBuf, BYTES);
+
+/// `i128` and `u128` are sent as `big.Int`, see `WideIntegers`.
+impl GobSchema for i128 {
+    #[inline]
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        ::big::register_int(schema)
+    }
+}
+
+impl GobSchema for u128 {
+    #[inline]
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        ::big::register_int(schema)
+    }
+}
+
+impl<'a> GobSchema for Bytes<'a> {
+    #[inline]
+    fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::BYTES)
+    }
+}
+
+impl<T: GobSchema> GobSchema for Option<T> {
+    #[inline]
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let id = T::register(schema)?;
+        schema.register_type(Type::build().option_type(id))
+    }
+
+    // serde leaves out the fields of a flattened `None`
+    #[inline]
+    fn register_flattened<S: Schema>(
+        schema: &mut S,
+        fields: &mut Vec<(&'static str, S::TypeId)>,
+    ) -> Result<(), S::Error> {
+        T::register_flattened(schema, fields)
+    }
+}
+
+impl<T: GobSchema, const N: usize> GobSchema for [T; N] {
+    #[inline]
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let id = T::register(schema)?;
+        schema.register_type(Type::build().seq_type(Some(N), id))
+    }
+}
+
+macro_rules! seq_impl {
+    ($($ty:ident)::+ <T $(, $param:ident)*>) => {
+        impl<T: GobSchema $(, $param)*> GobSchema for $($ty)::+<T $(, $param)*> {
+            #[inline]
+            fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                let id = T::register(schema)?;
+                schema.register_type(Type::build().seq_type(None, id))
+            }
+        }
+    };
+}
+
+seq_impl!(Vec<T>);
+seq_impl!(VecDeque<T>);
+seq_impl!(LinkedList<T>);
+seq_impl!(BinaryHeap<T>);
+seq_impl!(BTreeSet<T>);
+seq_impl!(HashSet<T, H>);
+
+impl<T: GobSchema> GobSchema for [T] {
+    #[inline]
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let id = T::register(schema)?;
+        schema.register_type(Type::build().seq_type(None, id))
+    }
+}
+
+macro_rules! map_impl {
+    ($($ty:ident)::+ <K, V $(, $param:ident)*>) => {
+        impl<K: GobSchema, V: GobSchema $(, $param)*> GobSchema for $($ty)::+<K, V $(, $param)*> {
+            #[inline]
+            fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                let key = K::register(schema)?;
+                let value = V::register(schema)?;
+                schema.register_type(Type::build().map_type(key, value))
+            }
+        }
+    };
+}
+
+map_impl!(BTreeMap<K, V>);
+map_impl!(HashMap<K, V, H>);
+
+macro_rules! tuple_impl {
+    ($len:expr => $($name:ident)+) => {
+        impl<$($name: GobSchema),+> GobSchema for ($($name,)+) {
+            #[inline]
+            fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                let builder = Type::build().tuple_type($len);
+                $(
+                    let builder = builder.element($name::register(schema)?);
+                )+
+                schema.register_type(builder.end())
+            }
+        }
+    };
+}
+
+tuple_impl!(1 => T0);
+tuple_impl!(2 => T0 T1);
+tuple_impl!(3 => T0 T1 T2);
+tuple_impl!(4 => T0 T1 T2 T3);
+tuple_impl!(5 => T0 T1 T2 T3 T4);
+tuple_impl!(6 => T0 T1 T2 T3 T4 T5);
+tuple_impl!(7 => T0 T1 T2 T3 T4 T5 T6);
+tuple_impl!(8 => T0 T1 T2 T3 T4 T5 T6 T7);
+tuple_impl!(9 => T0 T1 T2 T3 T4 T5 T6 T7 T8);
+tuple_impl!(10 => T0 T1 T2 T3 T4 T5 T6 T7 T8 T9);
+tuple_impl!(11 => T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10);
+tuple_impl!(12 => T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11);
+tuple_impl!(13 => T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12);
+tuple_impl!(14 => T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13);
+tuple_impl!(15 => T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14);
+tuple_impl!(16 => T0 T1 T2 T3 T4 T5 T6 T7 T8 T9 T10 T11 T12 T13 T14 T15);
+
+macro_rules! deref_impl {
+    (<$($lt:lifetime,)* T $(: $bound:ident)*> $ty:ty) => {
+        impl<$($lt,)* T: GobSchema $(+ $bound)* + ?Sized> GobSchema for $ty {
+            #[inline]
+            fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                T::register(schema)
+            }
+
+            #[inline]
+            fn register_flattened<S: Schema>(
+                schema: &mut S,
+                fields: &mut Vec<(&'static str, S::TypeId)>,
+            ) -> Result<(), S::Error> {
+                T::register_flattened(schema, fields)
+            }
+        }
+    };
+}
+
+deref_impl!(<'a, T> &'a T);
+deref_impl!(<'a, T> &'a mut T);
+deref_impl!(<T> Box<T>);
+deref_impl!(<T> Rc<T>);
+deref_impl!(<T> Arc<T>);
+deref_impl!(<'a, T: ToOwned> Cow<'a, T>);
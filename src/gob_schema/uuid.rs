@@ -0,0 +1,15 @@
+use serde_schema::types::TypeId;
+use serde_schema::Schema;
+use uuid::Uuid;
+
+use super::GobSchema;
+
+/// Sent as a Go `string` in its hyphenated form, e.g.
+/// `"67e55044-10b1-426f-9247-bb680e5fe0c8"`, which is what `uuid`
+/// serializes to in human readable formats.
+impl GobSchema for Uuid {
+    #[inline]
+    fn register<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+        Ok(S::TypeId::STR)
+    }
+}
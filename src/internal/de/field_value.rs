@@ -1,9 +1,12 @@
 use std::io::Cursor;
 
 use bytes::Buf;
+use serde::de::value::{BorrowedStrDeserializer, SeqDeserializer};
 use serde::de::{IgnoredAny, Visitor};
 use serde::{self, Deserialize};
 
+use big;
+use de::{InvalidUtf8, Options};
 use error::Error;
 use internal::gob::Message;
 use internal::types::{TypeId, Types, WireType};
@@ -12,6 +15,7 @@ use super::complex_value::ComplexValueDeserializer;
 use super::map_value::MapValueDeserializer;
 use super::seq_value::SeqValueDeserializer;
 use super::struct_value::StructValueDeserializer;
+use super::zero_value::ZeroValueDeserializer;
 
 pub(crate) struct FieldValueDeserializer<'t, 'de>
 where
@@ -19,6 +23,7 @@ where
 {
     type_id: TypeId,
     defs: &'t Types,
+    options: Options,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
 
@@ -26,9 +31,15 @@ impl<'t, 'de> FieldValueDeserializer<'t, 'de> {
     pub fn new(
         type_id: TypeId,
         defs: &'t Types,
+        options: Options,
         msg: &'t mut Message<Cursor<&'de [u8]>>,
     ) -> FieldValueDeserializer<'t, 'de> {
-        FieldValueDeserializer { type_id, defs, msg }
+        FieldValueDeserializer {
+            type_id,
+            defs,
+            options,
+            msg,
+        }
     }
 
     fn deserialize_byte_slice(&mut self) -> Result<&'de [u8], Error> {
@@ -39,9 +50,142 @@ impl<'t, 'de> FieldValueDeserializer<'t, 'de> {
         Ok(bytes)
     }
 
+    fn is_marshaler(&self) -> bool {
+        self.defs
+            .lookup(self.type_id)
+            .is_some_and(WireType::is_marshaler)
+    }
+
+    fn deserialize_nil_interface(&mut self) -> Result<(), Error> {
+        if self.deserialize_byte_slice()?.is_empty() {
+            Ok(())
+        } else {
+            Err(serde::de::Error::custom(
+                "only nil values can be received for interface types",
+            ))
+        }
+    }
+
+    /// The type of the value field of structs with the layout of Go's
+    /// `sql.Null[T]`, or of one of its predecessors such as
+    /// `sql.NullString { String string; Valid bool }`, which are decoded as
+    /// `Option<T>`.
+    fn nullable_value_type(&self) -> Option<TypeId> {
+        match self.defs.lookup(self.type_id) {
+            Some(WireType::Struct(struct_type)) => match *struct_type.fields {
+                [ref value, ref valid]
+                    if valid.name == "Valid"
+                        && valid.id == TypeId::BOOL
+                        && (value.name == "V"
+                            || struct_type.common.name.strip_prefix("Null")
+                                == Some(&value.name)) =>
+                {
+                    Some(value.id)
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Visits a `sql.Null[T]` struct as `None` unless its `Valid` field is
+    /// set.
+    fn deserialize_nullable<V>(self, value_id: TypeId, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        // `Valid` follows the value, so it is read first by skipping over
+        // the value, which is then decoded from its start
+        let mut value_pos = None;
+        let mut valid = false;
+        let mut field_no = -1;
+        loop {
+            let field_delta = self.msg.read_uint()?;
+            if field_delta == 0 {
+                break;
+            }
+            field_no += field_delta as i64;
+            match field_no {
+                0 => {
+                    value_pos = Some(self.msg.get_ref().position());
+                    let de =
+                        FieldValueDeserializer::new(value_id, self.defs, self.options, self.msg);
+                    IgnoredAny::deserialize(de)?;
+                }
+                1 => valid = self.msg.read_bool()?,
+                _ => {
+                    return Err(serde::de::Error::custom(format!(
+                        "field number overflow ({}) on a nullable struct",
+                        field_no
+                    )))
+                }
+            }
+        }
+        let end = self.msg.get_ref().position();
+
+        if !valid {
+            return visitor.visit_none();
+        }
+        let value_pos = match value_pos {
+            Some(value_pos) => value_pos,
+            None => {
+                let de = ZeroValueDeserializer::new(value_id, self.defs, self.options);
+                return visitor.visit_some(de);
+            }
+        };
+        self.msg.get_mut().set_position(value_pos);
+        let value = {
+            let de = FieldValueDeserializer::new(value_id, self.defs, self.options, self.msg);
+            visitor.visit_some(de)?
+        };
+        self.msg.get_mut().set_position(end);
+        Ok(value)
+    }
+
+    /// Whether values are slices or arrays of unsigned integers, which
+    /// can be decoded as bytes.
+    fn is_uint_seq(&self) -> bool {
+        match self.defs.lookup(self.type_id) {
+            Some(WireType::Slice(slice_type)) => slice_type.elem == TypeId::UINT,
+            Some(WireType::Array(array_type)) => array_type.elem == TypeId::UINT,
+            _ => false,
+        }
+    }
+
+    /// Visits a byte slice as a sequence of `u8`.
+    fn deserialize_bytes_as_seq<V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
+        let bytes = self.deserialize_byte_slice()?;
+        let mut seq = SeqDeserializer::<_, Error>::new(bytes.iter().cloned());
+        let value = visitor.visit_seq(&mut seq)?;
+        seq.end()?;
+        Ok(value)
+    }
+
     fn deserialize_str_slice(&mut self) -> Result<&'de str, Error> {
         let bytes = self.deserialize_byte_slice()?;
-        ::std::str::from_utf8(bytes).map_err(|err| serde::de::Error::custom(err))
+        ::std::str::from_utf8(bytes).map_err(serde::de::Error::custom)
+    }
+
+    /// Visits a string, handling invalid UTF-8 as set by `InvalidUtf8`.
+    fn deserialize_string_value<V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
+        let bytes = self.deserialize_byte_slice()?;
+        match ::std::str::from_utf8(bytes) {
+            Ok(s) => visitor.visit_borrowed_str(s),
+            Err(err) => match self.options.invalid_utf8 {
+                InvalidUtf8::Reject => Err(serde::de::Error::custom(err)),
+                InvalidUtf8::Replace => {
+                    visitor.visit_string(String::from_utf8_lossy(bytes).into_owned())
+                }
+                InvalidUtf8::Bytes => visitor.visit_borrowed_bytes(bytes),
+            },
+        }
+    }
+
+    /// Whether values are byte slices, or strings which can be decoded
+    /// as byte slices.
+    fn is_byte_slice(&self) -> bool {
+        self.type_id == TypeId::BYTES
+            || (self.type_id == TypeId::STRING && self.options.strings_as_bytes)
     }
 }
 
@@ -70,37 +214,58 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
             TypeId::UINT => visitor.visit_u64(self.msg.read_uint()?),
             TypeId::FLOAT => visitor.visit_f64(self.msg.read_float()?),
             TypeId::BYTES => visitor.visit_borrowed_bytes(self.deserialize_byte_slice()?),
-            TypeId::STRING => visitor.visit_borrowed_str(self.deserialize_str_slice()?),
+            TypeId::STRING => self.deserialize_string_value(visitor),
             TypeId::COMPLEX => ComplexValueDeserializer::new(self.msg).deserialize_any(visitor),
+            TypeId::INTERFACE => {
+                self.deserialize_nil_interface()?;
+                visitor.visit_unit()
+            }
             _ => {
                 if let Some(wire_type) = self.defs.lookup(self.type_id) {
                     match wire_type {
-                        &WireType::Struct(ref struct_type) => {
-                            let de = StructValueDeserializer::new(struct_type, self.defs, self.msg);
+                        WireType::Struct(struct_type) => {
+                            let de = StructValueDeserializer::new(
+                                struct_type,
+                                self.defs,
+                                self.options,
+                                self.msg,
+                            );
                             de.deserialize_any(visitor)
                         }
-                        &WireType::Slice(ref slice_type) => {
+                        WireType::Slice(slice_type) => {
                             let de = SeqValueDeserializer::new(
                                 None,
                                 slice_type.elem,
                                 self.defs,
+                                self.options,
                                 self.msg,
                             );
                             de.deserialize_any(visitor)
                         }
-                        &WireType::Array(ref array_type) => {
+                        WireType::Array(array_type) => {
                             let de = SeqValueDeserializer::new(
                                 Some(array_type.len as usize),
                                 array_type.elem,
                                 self.defs,
+                                self.options,
                                 self.msg,
                             );
                             de.deserialize_any(visitor)
                         }
-                        &WireType::Map(ref map_type) => {
-                            let de = MapValueDeserializer::new(map_type, self.defs, self.msg);
+                        WireType::Map(map_type) => {
+                            let de = MapValueDeserializer::new(
+                                map_type,
+                                self.defs,
+                                self.options,
+                                self.msg,
+                            );
                             de.deserialize_any(visitor)
                         }
+                        WireType::GobEncoder(_)
+                        | WireType::BinaryMarshaler(_)
+                        | WireType::TextMarshaler(_) => {
+                            visitor.visit_borrowed_bytes(self.deserialize_byte_slice()?)
+                        }
                     }
                 } else {
                     Err(serde::de::Error::custom(format!(
@@ -133,14 +298,42 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
     primitive!(deserialize_u64, u64, visit_u64, UINT, |d: Self| d.msg
         .read_uint());
 
+    fn deserialize_i128<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.type_id == TypeId::INT {
+            visitor.visit_i128(i128::from(self.msg.read_int()?))
+        } else if self.is_marshaler() {
+            let buf = self.deserialize_byte_slice()?;
+            visitor.visit_i128(big::decode_i128(buf).map_err(<Error as serde::de::Error>::custom)?)
+        } else {
+            Err(serde::de::Error::custom("expected i128"))
+        }
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.type_id == TypeId::UINT {
+            visitor.visit_u128(u128::from(self.msg.read_uint()?))
+        } else if self.is_marshaler() {
+            let buf = self.deserialize_byte_slice()?;
+            visitor.visit_u128(big::decode_u128(buf).map_err(<Error as serde::de::Error>::custom)?)
+        } else {
+            Err(serde::de::Error::custom("expected u128"))
+        }
+    }
+
     primitive!(deserialize_f32, f32, visit_f32, FLOAT, |d: Self| d.msg
         .read_float());
     primitive!(deserialize_f64, f64, visit_f64, FLOAT, |d: Self| d.msg
         .read_float());
 
     fn deserialize_str<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
-        if self.type_id == TypeId::STRING {
-            visitor.visit_borrowed_str(self.deserialize_str_slice()?)
+        if self.type_id == TypeId::STRING
+            || (self.type_id == TypeId::BYTES && self.options.strings_as_bytes)
+            || matches!(
+                self.defs.lookup(self.type_id),
+                Some(WireType::TextMarshaler(_))
+            )
+        {
+            self.deserialize_string_value(visitor)
         } else {
             Err(serde::de::Error::custom("expected str"))
         }
@@ -151,8 +344,10 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
     }
 
     fn deserialize_bytes<V: Visitor<'de>>(mut self, visitor: V) -> Result<V::Value, Self::Error> {
-        if self.type_id == TypeId::BYTES {
+        if self.is_byte_slice() || self.is_marshaler() {
             visitor.visit_borrowed_bytes(self.deserialize_byte_slice()?)
+        } else if self.is_uint_seq() {
+            self.deserialize_any(visitor)
         } else {
             Err(serde::de::Error::custom("expected bytes"))
         }
@@ -179,7 +374,7 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
 
     #[inline]
     fn deserialize_enum<V>(
-        self,
+        mut self,
         name: &'static str,
         variants: &'static [&'static str],
         visitor: V,
@@ -187,9 +382,13 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
     where
         V: Visitor<'de>,
     {
-        if let Some(&WireType::Struct(ref struct_type)) = self.defs.lookup(self.type_id) {
-            let de = StructValueDeserializer::new(struct_type, self.defs, self.msg);
+        if let Some(WireType::Struct(struct_type)) = self.defs.lookup(self.type_id) {
+            let de = StructValueDeserializer::new(struct_type, self.defs, self.options, self.msg);
             de.deserialize_enum(name, variants, visitor)
+        } else if self.type_id == TypeId::STRING {
+            // the tag of an adjacently tagged enum
+            let variant = self.deserialize_str_slice()?;
+            visitor.visit_enum(BorrowedStrDeserializer::<Error>::new(variant))
         } else {
             Err(serde::de::Error::custom("not an enum type"))
         }
@@ -205,14 +404,46 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
     where
         V: Visitor<'de>,
     {
-        if let Some(&WireType::Struct(ref struct_type)) = self.defs.lookup(self.type_id) {
-            let de = StructValueDeserializer::new(struct_type, self.defs, self.msg);
+        if let Some(WireType::Struct(struct_type)) = self.defs.lookup(self.type_id) {
+            let de = StructValueDeserializer::new(struct_type, self.defs, self.options, self.msg);
             de.deserialize_struct(name, fields, visitor)
         } else {
             Err(serde::de::Error::custom("not a struct type"))
         }
     }
 
+    #[inline]
+    fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.is_byte_slice() {
+            return self.deserialize_bytes_as_seq(visitor);
+        }
+        if let Some(WireType::Map(map_type)) = self.defs.lookup(self.type_id) {
+            let de = MapValueDeserializer::new(map_type, self.defs, self.options, self.msg);
+            de.deserialize_seq(visitor)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(mut self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.is_byte_slice() {
+            return self.deserialize_bytes_as_seq(visitor);
+        }
+        if let Some(WireType::Struct(struct_type)) = self.defs.lookup(self.type_id) {
+            let de = StructValueDeserializer::new(struct_type, self.defs, self.options, self.msg);
+            de.deserialize_tuple(len, visitor)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
     #[inline]
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
@@ -222,8 +453,24 @@ impl<'t, 'de> serde::Deserializer<'de> for FieldValueDeserializer<'t, 'de> {
         visitor.visit_unit()
     }
 
+    #[inline]
+    fn deserialize_option<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.type_id == TypeId::INTERFACE {
+            self.deserialize_nil_interface()?;
+            return visitor.visit_none();
+        }
+        if let Some(value_id) = self.nullable_value_type() {
+            return self.deserialize_nullable(value_id, visitor);
+        }
+        // values which are present on the wire are never nil
+        visitor.visit_some(self)
+    }
+
     forward_to_deserialize_any! {
-        option unit_struct newtype_struct seq tuple
+        unit_struct newtype_struct
         tuple_struct map identifier ignored_any
     }
 }
@@ -1,11 +1,12 @@
 use std::io::Cursor;
 
-use serde::de::{DeserializeSeed, Deserializer, MapAccess, Visitor};
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
 
 use super::FieldValueDeserializer;
+use de::Options;
 use error::Error;
 use internal::gob::Message;
-use internal::types::{MapType, Types};
+use internal::types::{MapType, TypeId, Types};
 
 struct MapMapAccess<'t, 'de>
 where
@@ -13,6 +14,7 @@ where
 {
     def: &'t MapType,
     defs: &'t Types,
+    options: Options,
     remaining_count: u64,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
@@ -21,6 +23,7 @@ impl<'t, 'de> MapMapAccess<'t, 'de> {
     fn new(
         def: &'t MapType,
         defs: &'t Types,
+        options: Options,
         msg: &'t mut Message<Cursor<&'de [u8]>>,
     ) -> Result<MapMapAccess<'t, 'de>, Error> {
         let remaining_count = msg.read_uint()?;
@@ -28,6 +31,7 @@ impl<'t, 'de> MapMapAccess<'t, 'de> {
         Ok(MapMapAccess {
             def,
             defs,
+            options,
             remaining_count,
             msg,
         })
@@ -45,7 +49,7 @@ impl<'f, 'de> MapAccess<'de> for MapMapAccess<'f, 'de> {
             return Ok(None);
         }
         self.remaining_count -= 1;
-        let de = FieldValueDeserializer::new(self.def.key, self.defs, &mut self.msg);
+        let de = FieldValueDeserializer::new(self.def.key, self.defs, self.options, self.msg);
         seed.deserialize(de).map(Some)
     }
 
@@ -53,7 +57,7 @@ impl<'f, 'de> MapAccess<'de> for MapMapAccess<'f, 'de> {
     where
         V: DeserializeSeed<'de>,
     {
-        let de = FieldValueDeserializer::new(self.def.elem, self.defs, &mut self.msg);
+        let de = FieldValueDeserializer::new(self.def.elem, self.defs, self.options, self.msg);
         seed.deserialize(de)
     }
 
@@ -62,12 +66,77 @@ impl<'f, 'de> MapAccess<'de> for MapMapAccess<'f, 'de> {
     }
 }
 
+/// The members of a Go set, i.e. the keys of a `map[T]struct{}`, or the
+/// keys of a `map[T]bool` which are mapped to `true`.
+struct SetMembersAccess<'t, 'de>
+where
+    'de: 't,
+{
+    inner: MapMapAccess<'t, 'de>,
+}
+
+impl<'t, 'de> SetMembersAccess<'t, 'de> {
+    fn skip_value(&mut self, type_id: TypeId) -> Result<(), Error> {
+        let de = FieldValueDeserializer::new(
+            type_id,
+            self.inner.defs,
+            self.inner.options,
+            self.inner.msg,
+        );
+        de.deserialize_ignored_any(IgnoredAny)?;
+        Ok(())
+    }
+}
+
+impl<'t, 'de> SeqAccess<'de> for SetMembersAccess<'t, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.inner.def.elem != TypeId::BOOL {
+            let elem = self.inner.next_key_seed(seed)?;
+            if elem.is_some() {
+                self.skip_value(self.inner.def.elem)?;
+            }
+            return Ok(elem);
+        }
+
+        // the key precedes the flag saying whether it is a member, so it
+        // is skipped over first and decoded once the flag has been read
+        while self.inner.remaining_count > 0 {
+            self.inner.remaining_count -= 1;
+            let key_pos = self.inner.msg.get_ref().position();
+            self.skip_value(self.inner.def.key)?;
+            if !self.inner.msg.read_bool()? {
+                continue;
+            }
+            let end_pos = self.inner.msg.get_ref().position();
+            self.inner.msg.get_mut().set_position(key_pos);
+            let elem = {
+                let de = FieldValueDeserializer::new(
+                    self.inner.def.key,
+                    self.inner.defs,
+                    self.inner.options,
+                    self.inner.msg,
+                );
+                seed.deserialize(de)?
+            };
+            self.inner.msg.get_mut().set_position(end_pos);
+            return Ok(Some(elem));
+        }
+        Ok(None)
+    }
+}
+
 pub(crate) struct MapValueDeserializer<'t, 'de>
 where
     'de: 't,
 {
     def: &'t MapType,
     defs: &'t Types,
+    options: Options,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
 
@@ -76,9 +145,15 @@ impl<'t, 'de> MapValueDeserializer<'t, 'de> {
     pub(crate) fn new(
         def: &'t MapType,
         defs: &'t Types,
+        options: Options,
         msg: &'t mut Message<Cursor<&'de [u8]>>,
     ) -> MapValueDeserializer<'t, 'de> {
-        MapValueDeserializer { def, defs, msg }
+        MapValueDeserializer {
+            def,
+            defs,
+            options,
+            msg,
+        }
     }
 }
 
@@ -90,12 +165,31 @@ impl<'t, 'de> Deserializer<'de> for MapValueDeserializer<'t, 'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_map(MapMapAccess::new(self.def, self.defs, self.msg)?)
+        visitor.visit_map(MapMapAccess::new(
+            self.def,
+            self.defs,
+            self.options,
+            self.msg,
+        )?)
+    }
+
+    /// Go has no set type, so sets are sent as maps, which are decoded
+    /// into sequences of their members.
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if !self.defs.is_set_elem(self.def.elem) {
+            return self.deserialize_any(visitor);
+        }
+        visitor.visit_seq(SetMembersAccess {
+            inner: MapMapAccess::new(self.def, self.defs, self.options, self.msg)?,
+        })
     }
 
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
-        byte_buf option unit unit_struct newtype_struct seq tuple
+        byte_buf option unit unit_struct newtype_struct tuple
         tuple_struct map struct enum identifier ignored_any
     }
 }
@@ -4,6 +4,7 @@ mod map_value;
 mod seq_value;
 mod struct_value;
 mod value;
+mod zero_value;
 
 pub(crate) use self::field_value::FieldValueDeserializer;
 pub(crate) use self::value::ValueDeserializer;
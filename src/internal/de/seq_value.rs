@@ -3,6 +3,7 @@ use std::io::Cursor;
 use serde::de::{self, DeserializeSeed, Deserializer, Visitor};
 
 use super::FieldValueDeserializer;
+use de::Options;
 use error::Error;
 use internal::gob::Message;
 use internal::types::{TypeId, Types};
@@ -13,6 +14,7 @@ where
 {
     element: TypeId,
     defs: &'t Types,
+    options: Options,
     remaining_count: u64,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
@@ -22,6 +24,7 @@ impl<'t, 'de> SeqAccess<'t, 'de> {
         len: Option<usize>,
         element: TypeId,
         defs: &'t Types,
+        options: Options,
         msg: &'t mut Message<Cursor<&'de [u8]>>,
     ) -> Result<SeqAccess<'t, 'de>, Error> {
         let remaining_count = msg.read_uint()?;
@@ -38,6 +41,7 @@ impl<'t, 'de> SeqAccess<'t, 'de> {
         Ok(SeqAccess {
             element,
             defs,
+            options,
             remaining_count,
             msg,
         })
@@ -55,7 +59,7 @@ impl<'f, 'de> de::SeqAccess<'de> for SeqAccess<'f, 'de> {
             return Ok(None);
         }
         self.remaining_count -= 1;
-        let de = FieldValueDeserializer::new(self.element, self.defs, &mut self.msg);
+        let de = FieldValueDeserializer::new(self.element, self.defs, self.options, self.msg);
         seed.deserialize(de).map(Some)
     }
 
@@ -71,6 +75,7 @@ where
     len: Option<usize>,
     element: TypeId,
     defs: &'t Types,
+    options: Options,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
 
@@ -80,12 +85,14 @@ impl<'t, 'de> SeqValueDeserializer<'t, 'de> {
         len: Option<usize>,
         element: TypeId,
         defs: &'t Types,
+        options: Options,
         msg: &'t mut Message<Cursor<&'de [u8]>>,
     ) -> SeqValueDeserializer<'t, 'de> {
         SeqValueDeserializer {
             len,
             element,
             defs,
+            options,
             msg,
         }
     }
@@ -99,7 +106,13 @@ impl<'t, 'de> Deserializer<'de> for SeqValueDeserializer<'t, 'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(SeqAccess::new(self.len, self.element, self.defs, self.msg)?)
+        visitor.visit_seq(SeqAccess::new(
+            self.len,
+            self.element,
+            self.defs,
+            self.options,
+            self.msg,
+        )?)
     }
 
     forward_to_deserialize_any! {
@@ -1,10 +1,13 @@
+use std::borrow::Cow;
 use std::io::Cursor;
 
 use serde;
 use serde::de::{DeserializeSeed, Deserializer, IntoDeserializer, Visitor};
-use serde::de::{EnumAccess, MapAccess, VariantAccess};
+use serde::de::{EnumAccess, MapAccess, SeqAccess, VariantAccess};
 
+use super::zero_value::ZeroValueDeserializer;
 use super::FieldValueDeserializer;
+use de::Options;
 use error::Error;
 use internal::gob::Message;
 use internal::types::{FieldType, StructType, TypeId, Types};
@@ -15,8 +18,14 @@ where
 {
     def: &'t StructType,
     defs: &'t Types,
+    options: Options,
+    fields: Option<&'static [&'static str]>,
+    fill_zero_values: bool,
     field_no: i64,
     field_id: TypeId,
+    wire_field_no: i64,
+    wire_ended: bool,
+    zero_value: bool,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
 
@@ -24,17 +33,55 @@ impl<'t, 'de> StructAccess<'t, 'de> {
     fn new(
         def: &'t StructType,
         defs: &'t Types,
+        options: Options,
+        fields: Option<&'static [&'static str]>,
+        fill_zero_values: bool,
         msg: &'t mut Message<Cursor<&'de [u8]>>,
     ) -> StructAccess<'t, 'de> {
         StructAccess {
             def,
             defs,
+            options,
+            fields,
+            fill_zero_values,
             field_no: -1,
             field_id: TypeId(0),
+            wire_field_no: -1,
+            wire_ended: false,
+            zero_value: false,
             msg,
         }
     }
 
+    /// Advances to the next field, which is either the next field present
+    /// in the message, or (when filling in zero values) the next field
+    /// declared by the struct type.
+    fn next_field(&mut self) -> Result<Option<&'t FieldType>, Error> {
+        if self.wire_field_no <= self.field_no && !self.wire_ended {
+            let field_delta = self.msg.read_uint()?;
+            if field_delta == 0 {
+                self.wire_ended = true;
+            } else {
+                self.wire_field_no += field_delta as i64;
+            }
+        }
+
+        if self.fill_zero_values {
+            self.field_no += 1;
+            if self.wire_ended && self.field_no as usize >= self.def.fields.len() {
+                return Ok(None);
+            }
+            self.zero_value = self.wire_ended || self.field_no < self.wire_field_no;
+        } else {
+            if self.wire_ended {
+                return Ok(None);
+            }
+            self.field_no = self.wire_field_no;
+        }
+
+        self.current_field().map(Some)
+    }
+
     fn current_field(&self) -> Result<&'t FieldType, Error> {
         let field_no = self.field_no as usize;
         self.def.fields.get(field_no).ok_or_else(|| {
@@ -53,17 +100,17 @@ impl<'t, 'de> MapAccess<'de> for StructAccess<'t, 'de> {
     where
         K: DeserializeSeed<'de>,
     {
-        let field_delta = self.msg.read_uint()?;
-
-        if field_delta == 0 {
-            return Ok(None);
-        }
-
-        self.field_no += field_delta as i64;
-        let field = self.current_field()?;
+        let field = match self.next_field()? {
+            Some(field) => field,
+            None => return Ok(None),
+        };
         self.field_id = field.id;
 
-        let de = <&str as IntoDeserializer<'_, Error>>::into_deserializer(&field.name);
+        let name = self
+            .options
+            .field_matching
+            .resolve(&field.name, self.fields);
+        let de = <Cow<str> as IntoDeserializer<'_, Error>>::into_deserializer(name);
         let value = seed.deserialize(de)?;
         Ok(Some(value))
     }
@@ -72,11 +119,36 @@ impl<'t, 'de> MapAccess<'de> for StructAccess<'t, 'de> {
     where
         V: DeserializeSeed<'de>,
     {
-        let de = FieldValueDeserializer::new(self.field_id, self.defs, &mut self.msg);
+        if self.zero_value {
+            let de = ZeroValueDeserializer::new(self.field_id, self.defs, self.options);
+            return seed.deserialize(de);
+        }
+        let de = FieldValueDeserializer::new(self.field_id, self.defs, self.options, self.msg);
         seed.deserialize(de)
     }
 }
 
+// the fields of a struct in declaration order, for decoding tuples
+impl<'t, 'de> SeqAccess<'de> for StructAccess<'t, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let field = match self.next_field()? {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        self.field_id = field.id;
+        self.next_value_seed(seed).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.def.fields.len())
+    }
+}
+
 impl<'t, 'de> EnumAccess<'de> for StructAccess<'t, 'de> {
     type Error = Error;
     type Variant = Self;
@@ -100,13 +172,13 @@ impl<'t, 'de> VariantAccess<'de> for StructAccess<'t, 'de> {
         Err(serde::de::Error::custom("unit variants not supported yet"))
     }
 
-    fn newtype_variant_seed<T>(mut self, seed: T) -> Result<T::Value, Error>
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
     where
         T: DeserializeSeed<'de>,
     {
         let field = self.current_field()?;
         let val = {
-            let de = FieldValueDeserializer::new(field.id, self.defs, &mut self.msg);
+            let de = FieldValueDeserializer::new(field.id, self.defs, self.options, self.msg);
             seed.deserialize(de)?
         };
         let field_delta = self.msg.read_uint()?;
@@ -119,13 +191,13 @@ impl<'t, 'de> VariantAccess<'de> for StructAccess<'t, 'de> {
         }
     }
 
-    fn tuple_variant<V>(mut self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
         let field = self.current_field()?;
         let val = {
-            let de = FieldValueDeserializer::new(field.id, self.defs, &mut self.msg);
+            let de = FieldValueDeserializer::new(field.id, self.defs, self.options, self.msg);
             de.deserialize_seq(visitor)?
         };
         let field_delta = self.msg.read_uint()?;
@@ -139,8 +211,8 @@ impl<'t, 'de> VariantAccess<'de> for StructAccess<'t, 'de> {
     }
 
     fn struct_variant<V>(
-        mut self,
-        _fields: &'static [&'static str],
+        self,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Error>
     where
@@ -148,8 +220,8 @@ impl<'t, 'de> VariantAccess<'de> for StructAccess<'t, 'de> {
     {
         let field = self.current_field()?;
         let val = {
-            let de = FieldValueDeserializer::new(field.id, self.defs, &mut self.msg);
-            de.deserialize_seq(visitor)?
+            let de = FieldValueDeserializer::new(field.id, self.defs, self.options, self.msg);
+            de.deserialize_struct("", fields, visitor)?
         };
         let field_delta = self.msg.read_uint()?;
         if field_delta != 0 {
@@ -168,6 +240,7 @@ where
 {
     def: &'t StructType,
     defs: &'t Types,
+    options: Options,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
 
@@ -176,9 +249,15 @@ impl<'t, 'de> StructValueDeserializer<'t, 'de> {
     pub(crate) fn new(
         def: &'t StructType,
         defs: &'t Types,
+        options: Options,
         msg: &'t mut Message<Cursor<&'de [u8]>>,
     ) -> StructValueDeserializer<'t, 'de> {
-        StructValueDeserializer { def, defs, msg }
+        StructValueDeserializer {
+            def,
+            defs,
+            options,
+            msg,
+        }
     }
 }
 
@@ -190,25 +269,80 @@ impl<'t, 'de> Deserializer<'de> for StructValueDeserializer<'t, 'de> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_map(StructAccess::new(self.def, self.defs, self.msg))
+        visitor.visit_map(StructAccess::new(
+            self.def,
+            self.defs,
+            self.options,
+            None,
+            self.options.fill_zero_values,
+            self.msg,
+        ))
     }
 
     #[inline]
     fn deserialize_enum<V>(
         self,
         _: &'static str,
-        _: &'static [&'static str],
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(StructAccess::new(
+            self.def,
+            self.defs,
+            self.options,
+            Some(variants),
+            false,
+            self.msg,
+        ))
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(StructAccess::new(self.def, self.defs, self.msg))
+        visitor.visit_map(StructAccess::new(
+            self.def,
+            self.defs,
+            self.options,
+            Some(fields),
+            self.options.fill_zero_values,
+            self.msg,
+        ))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if len != self.def.fields.len() {
+            return Err(serde::de::Error::custom(format!(
+                "expected a tuple of {} elements, found struct {:?} with {} fields",
+                len,
+                self.def.common.name,
+                self.def.fields.len()
+            )));
+        }
+        // omitted fields are zero values, which are part of the tuple
+        let mut access = StructAccess::new(self.def, self.defs, self.options, None, true, self.msg);
+        let value = visitor.visit_seq(&mut access)?;
+        if access.next_field()?.is_some() {
+            return Err(serde::de::Error::custom("too many elements for tuple"));
+        }
+        Ok(value)
     }
 
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
-        byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct identifier ignored_any
+        byte_buf option unit unit_struct newtype_struct seq
+        tuple_struct map identifier ignored_any
     }
 }
@@ -3,6 +3,7 @@ use std::io::Cursor;
 use serde;
 use serde::de::{Deserializer, IgnoredAny, Visitor};
 
+use de::Options;
 use error::Error;
 use internal::gob::Message;
 use internal::types::{TypeId, Types, WireType};
@@ -16,6 +17,7 @@ where
 {
     type_id: TypeId,
     defs: &'t Types,
+    options: Options,
     msg: &'t mut Message<Cursor<&'de [u8]>>,
 }
 
@@ -23,36 +25,42 @@ impl<'t, 'de> ValueDeserializer<'t, 'de> {
     pub fn new(
         type_id: TypeId,
         defs: &'t Types,
+        options: Options,
         msg: &'t mut Message<Cursor<&'de [u8]>>,
     ) -> ValueDeserializer<'t, 'de> {
-        ValueDeserializer { type_id, defs, msg }
+        ValueDeserializer {
+            type_id,
+            defs,
+            options,
+            msg,
+        }
     }
 }
 
 impl<'t, 'de> Deserializer<'de> for ValueDeserializer<'t, 'de> {
     type Error = Error;
 
-    fn deserialize_any<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        if let Some(&WireType::Struct(ref struct_type)) = self.defs.lookup(self.type_id) {
-            let de = StructValueDeserializer::new(struct_type, &self.defs, &mut self.msg);
+        if let Some(WireType::Struct(struct_type)) = self.defs.lookup(self.type_id) {
+            let de = StructValueDeserializer::new(struct_type, self.defs, self.options, self.msg);
             return de.deserialize_any(visitor);
         }
 
         if self.msg.read_uint()? != 0 {
-            return Err(serde::de::Error::custom(format!(
-                "neither a singleton nor a struct value"
-            )));
+            return Err(serde::de::Error::custom(
+                "neither a singleton nor a struct value",
+            ));
         }
 
-        let de = FieldValueDeserializer::new(self.type_id, &self.defs, &mut self.msg);
-        return de.deserialize_any(visitor);
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_any(visitor)
     }
 
     fn deserialize_enum<V>(
-        mut self,
+        self,
         name: &'static str,
         variants: &'static [&'static str],
         visitor: V,
@@ -60,23 +68,23 @@ impl<'t, 'de> Deserializer<'de> for ValueDeserializer<'t, 'de> {
     where
         V: Visitor<'de>,
     {
-        if let Some(&WireType::Struct(ref struct_type)) = self.defs.lookup(self.type_id) {
-            let de = StructValueDeserializer::new(struct_type, &self.defs, &mut self.msg);
+        if let Some(WireType::Struct(struct_type)) = self.defs.lookup(self.type_id) {
+            let de = StructValueDeserializer::new(struct_type, self.defs, self.options, self.msg);
             return de.deserialize_enum(name, variants, visitor);
         }
 
         if self.msg.read_uint()? != 0 {
-            return Err(serde::de::Error::custom(format!(
-                "neither a singleton nor a struct value"
-            )));
+            return Err(serde::de::Error::custom(
+                "neither a singleton nor a struct value",
+            ));
         }
 
-        let de = FieldValueDeserializer::new(self.type_id, &self.defs, &mut self.msg);
-        return de.deserialize_enum(name, variants, visitor);
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_enum(name, variants, visitor)
     }
 
     fn deserialize_struct<V>(
-        mut self,
+        self,
         name: &'static str,
         fields: &'static [&'static str],
         visitor: V,
@@ -84,19 +92,135 @@ impl<'t, 'de> Deserializer<'de> for ValueDeserializer<'t, 'de> {
     where
         V: Visitor<'de>,
     {
-        if let Some(&WireType::Struct(ref struct_type)) = self.defs.lookup(self.type_id) {
-            let de = StructValueDeserializer::new(struct_type, &self.defs, &mut self.msg);
+        if let Some(WireType::Struct(struct_type)) = self.defs.lookup(self.type_id) {
+            let de = StructValueDeserializer::new(struct_type, self.defs, self.options, self.msg);
             return de.deserialize_struct(name, fields, visitor);
         }
 
         if self.msg.read_uint()? != 0 {
-            return Err(serde::de::Error::custom(format!(
-                "neither a singleton nor a struct value"
-            )));
+            return Err(serde::de::Error::custom(
+                "neither a singleton nor a struct value",
+            ));
+        }
+
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Some(WireType::Struct(struct_type)) = self.defs.lookup(self.type_id) {
+            let de = StructValueDeserializer::new(struct_type, self.defs, self.options, self.msg);
+            return de.deserialize_any(visitor);
+        }
+
+        if self.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom(
+                "neither a singleton nor a struct value",
+            ));
+        }
+
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Some(WireType::Struct(struct_type)) = self.defs.lookup(self.type_id) {
+            let de = StructValueDeserializer::new(struct_type, self.defs, self.options, self.msg);
+            return de.deserialize_tuple(len, visitor);
+        }
+
+        if self.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom(
+                "neither a singleton nor a struct value",
+            ));
+        }
+
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom("expected a singleton value"));
+        }
+
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_str(visitor)
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom("expected a singleton value"));
+        }
+
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom("expected a singleton value"));
+        }
+
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_i128(visitor)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom("expected a singleton value"));
+        }
+
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_u128(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // struct values have no singleton marker
+        let is_struct = matches!(self.defs.lookup(self.type_id), Some(WireType::Struct(_)));
+        if !is_struct && self.msg.read_uint()? != 0 {
+            return Err(serde::de::Error::custom(
+                "neither a singleton nor a struct value",
+            ));
         }
 
-        let de = FieldValueDeserializer::new(self.type_id, &self.defs, &mut self.msg);
-        return de.deserialize_struct(name, fields, visitor);
+        let de = FieldValueDeserializer::new(self.type_id, self.defs, self.options, self.msg);
+        de.deserialize_option(visitor)
     }
 
     #[inline]
@@ -109,8 +233,8 @@ impl<'t, 'de> Deserializer<'de> for ValueDeserializer<'t, 'de> {
     }
 
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
-        byte_buf option unit_struct newtype_struct seq tuple
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char
+        unit_struct newtype_struct
         tuple_struct map identifier ignored_any
     }
 }
@@ -0,0 +1,316 @@
+use serde::de::{DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor};
+use serde::{self, de};
+
+use de::Options;
+use error::Error;
+use internal::types::{StructType, TypeId, Types, WireType};
+
+/// Produces the Go zero value of a type, i.e. what Go observes for a
+/// value which was omitted from a message.
+pub(crate) struct ZeroValueDeserializer<'t> {
+    type_id: TypeId,
+    defs: &'t Types,
+    options: Options,
+}
+
+impl<'t> ZeroValueDeserializer<'t> {
+    pub(crate) fn new(type_id: TypeId, defs: &'t Types, options: Options) -> Self {
+        ZeroValueDeserializer {
+            type_id,
+            defs,
+            options,
+        }
+    }
+
+    fn deserialize_struct_type<'de, V>(
+        self,
+        def: &'t StructType,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(ZeroStructAccess {
+            def,
+            defs: self.defs,
+            options: self.options,
+            fields,
+            field_no: 0,
+        })
+    }
+}
+
+impl<'t, 'de> Deserializer<'de> for ZeroValueDeserializer<'t> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.type_id {
+            TypeId::BOOL => visitor.visit_bool(false),
+            TypeId::INT => visitor.visit_i64(0),
+            TypeId::UINT => visitor.visit_u64(0),
+            TypeId::FLOAT => visitor.visit_f64(0.0),
+            TypeId::BYTES => visitor.visit_bytes(&[]),
+            TypeId::STRING => visitor.visit_str(""),
+            TypeId::INTERFACE => visitor.visit_unit(),
+            TypeId::COMPLEX => visitor.visit_seq(ZeroSeqAccess {
+                elem: TypeId::FLOAT,
+                remaining: 2,
+                defs: self.defs,
+                options: self.options,
+            }),
+            _ => match self.defs.lookup(self.type_id) {
+                Some(WireType::Struct(struct_type)) => {
+                    self.deserialize_struct_type(struct_type, None, visitor)
+                }
+                Some(WireType::Slice(slice_type)) => visitor.visit_seq(ZeroSeqAccess {
+                    elem: slice_type.elem,
+                    remaining: 0,
+                    defs: self.defs,
+                    options: self.options,
+                }),
+                Some(WireType::Array(array_type)) => visitor.visit_seq(ZeroSeqAccess {
+                    elem: array_type.elem,
+                    remaining: array_type.len as usize,
+                    defs: self.defs,
+                    options: self.options,
+                }),
+                Some(WireType::Map(_)) => visitor.visit_map(EmptyMapAccess),
+                Some(WireType::GobEncoder(_))
+                | Some(WireType::BinaryMarshaler(_))
+                | Some(WireType::TextMarshaler(_)) => visitor.visit_bytes(&[]),
+                None => Err(de::Error::custom(format!(
+                    "unknown type id {:?}",
+                    self.type_id
+                ))),
+            },
+        }
+    }
+
+    fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // the zero value of an int or a `big.Int`
+        visitor.visit_i128(0)
+    }
+
+    fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_u128(0)
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_char('\0')
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if let Some(WireType::Struct(struct_type)) = self.defs.lookup(self.type_id) {
+            self.deserialize_struct_type(struct_type, Some(fields), visitor)
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.defs.lookup(self.type_id) {
+            // an empty set, see `MapValueDeserializer::deserialize_seq`
+            Some(WireType::Map(map_type)) => visitor.visit_seq(ZeroSeqAccess {
+                elem: map_type.key,
+                remaining: 0,
+                defs: self.defs,
+                options: self.options,
+            }),
+            // an empty byte slice, decoded as a sequence of `u8`
+            None if self.type_id == TypeId::BYTES => visitor.visit_seq(ZeroSeqAccess {
+                elem: TypeId::UINT,
+                remaining: 0,
+                defs: self.defs,
+                options: self.options,
+            }),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.defs.lookup(self.type_id) {
+            Some(WireType::Struct(struct_type)) if struct_type.fields.len() == len => visitor
+                .visit_seq(ZeroStructAccess {
+                    def: struct_type,
+                    defs: self.defs,
+                    options: self.options,
+                    fields: None,
+                    field_no: 0,
+                }),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::custom("enums do not have a zero value"))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 str string bytes
+        byte_buf unit_struct newtype_struct
+        tuple_struct map identifier ignored_any
+    }
+}
+
+struct ZeroSeqAccess<'t> {
+    elem: TypeId,
+    remaining: usize,
+    defs: &'t Types,
+    options: Options,
+}
+
+impl<'t, 'de> SeqAccess<'de> for ZeroSeqAccess<'t> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let de = ZeroValueDeserializer::new(self.elem, self.defs, self.options);
+        seed.deserialize(de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+struct ZeroStructAccess<'t> {
+    def: &'t StructType,
+    defs: &'t Types,
+    options: Options,
+    fields: Option<&'static [&'static str]>,
+    field_no: usize,
+}
+
+impl<'t, 'de> MapAccess<'de> for ZeroStructAccess<'t> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let field = match self.def.fields.get(self.field_no) {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        let name = self
+            .options
+            .field_matching
+            .resolve(&field.name, self.fields);
+        let de = IntoDeserializer::<Error>::into_deserializer(name);
+        seed.deserialize(de).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let field = &self.def.fields[self.field_no];
+        self.field_no += 1;
+        seed.deserialize(ZeroValueDeserializer::new(
+            field.id,
+            self.defs,
+            self.options,
+        ))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.def.fields.len() - self.field_no)
+    }
+}
+
+impl<'t, 'de> SeqAccess<'de> for ZeroStructAccess<'t> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.field_no == self.def.fields.len() {
+            return Ok(None);
+        }
+        self.next_value_seed(seed).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.def.fields.len() - self.field_no)
+    }
+}
+
+struct EmptyMapAccess;
+
+impl<'de> MapAccess<'de> for EmptyMapAccess {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, _seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, _seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        Err(serde::de::Error::custom("empty map has no values"))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(0)
+    }
+}
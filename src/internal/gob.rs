@@ -120,12 +120,11 @@ impl<B: BufMut> Message<B> {
 
     #[inline]
     pub fn write_int(&mut self, n: i64) {
-        let u: u64;
-        if n < 0 {
-            u = (!(n as u64) << 1) | 1;
+        let u = if n < 0 {
+            (!(n as u64) << 1) | 1
         } else {
-            u = (n as u64) << 1;
-        }
+            (n as u64) << 1
+        };
         self.write_uint(u);
     }
 
@@ -187,7 +186,7 @@ impl<Io: Read> Stream<Io> {
         }
         let type_id = msg.read_int()?;
         let payload_offset = msg.get_ref().position() as usize;
-        let payload_length = msg_length as usize - (payload_offset - msg_offset);
+        let payload_length = msg_length - (payload_offset - msg_offset);
         Ok(SectionHeader {
             type_id,
             payload_range: Range {
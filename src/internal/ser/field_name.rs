@@ -0,0 +1,175 @@
+use serde::ser::{self, Impossible, Serialize};
+
+use error::Error;
+
+/// Extracts the key of a map entry which is sent as a struct field.
+pub(crate) struct FieldNameSerializer;
+
+fn not_a_field_name() -> Error {
+    ser::Error::custom("expected a string key in a map sent as a struct")
+}
+
+impl ser::Serializer for FieldNameSerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, _v: bool) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_char(self, _v: char) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, Error> {
+        Ok(v.to_owned())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_none(self) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<String, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_unit(self) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<String, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<String, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(not_a_field_name())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(not_a_field_name())
+    }
+}
@@ -0,0 +1,612 @@
+use serde::ser::{self, Serialize};
+use serde_schema::types::Type;
+
+use big;
+use complex::COMPLEX_NAME;
+use error::Error;
+use internal::types::TypeId;
+
+/// The shape of a value, as observed by `ShapeSerializer`.
+#[derive(Debug)]
+enum Shape {
+    // a nil value, or a field which was skipped
+    Unknown,
+    Known(TypeId),
+    // an `i128` or `u128`, sent as a `big.Int`
+    BigInt,
+    Seq {
+        len: Option<usize>,
+        elem: Box<Shape>,
+    },
+    Map {
+        key: Box<Shape>,
+        value: Box<Shape>,
+    },
+    Struct {
+        name: &'static str,
+        fields: Vec<(&'static str, Shape)>,
+    },
+    // variants which have not been observed are `None`
+    Enum {
+        name: &'static str,
+        variants: Vec<Option<VariantShape>>,
+    },
+}
+
+#[derive(Debug)]
+enum VariantShape {
+    Newtype(&'static str, Shape),
+    Struct(&'static str, Vec<(&'static str, Shape)>),
+}
+
+/// Registers the type of `value`, as discovered by serializing it.
+///
+/// Struct fields whose type cannot be determined, because they are nil
+/// or skipped, are left out of the registered type. Enum variants which
+/// do not occur in the value are left out as well.
+pub(crate) fn infer_type<T, S>(value: &T, schema: &mut S) -> Result<TypeId, Error>
+where
+    T: ?Sized + Serialize,
+    S: ::serde_schema::Schema<TypeId = TypeId, Error = Error>,
+{
+    let shape = value.serialize(ShapeSerializer)?;
+    match shape {
+        Shape::Unknown => Err(ser::Error::custom("cannot infer the type of a nil value")),
+        shape => register(&shape, schema),
+    }
+}
+
+fn register<S>(shape: &Shape, schema: &mut S) -> Result<TypeId, Error>
+where
+    S: ::serde_schema::Schema<TypeId = TypeId, Error = Error>,
+{
+    match *shape {
+        Shape::Unknown => Ok(TypeId::SKIP),
+        Shape::Known(id) => Ok(id),
+        Shape::BigInt => big::register_int(schema),
+        Shape::Seq { len, ref elem } => {
+            if let Shape::Unknown = **elem {
+                return Err(ser::Error::custom(
+                    "cannot infer the element type of an empty sequence",
+                ));
+            }
+            let elem = register(elem, schema)?;
+            schema.register_type(Type::build().seq_type(len, elem))
+        }
+        Shape::Map { ref key, ref value } => {
+            if let (&Shape::Unknown, _) | (_, &Shape::Unknown) = (&**key, &**value) {
+                return Err(ser::Error::custom(
+                    "cannot infer the key and value types of an empty map",
+                ));
+            }
+            let key = register(key, schema)?;
+            let value = register(value, schema)?;
+            schema.register_type(Type::build().map_type(key, value))
+        }
+        Shape::Struct { name, ref fields } => {
+            let mut field_ids = Vec::with_capacity(fields.len());
+            for (_, field) in fields {
+                field_ids.push(register(field, schema)?);
+            }
+            let mut builder = Type::build().struct_type(name, fields.len());
+            for (&(field_name, _), id) in fields.iter().zip(field_ids) {
+                builder = builder.field(field_name, id);
+            }
+            schema.register_type(builder.end())
+        }
+        Shape::Enum { name, ref variants } => {
+            let mut variant_ids = Vec::with_capacity(variants.len());
+            for variant in variants {
+                let ids = match *variant {
+                    Some(VariantShape::Newtype(_, Shape::Unknown)) => {
+                        return Err(ser::Error::custom("cannot infer the type of a nil value"));
+                    }
+                    Some(VariantShape::Newtype(_, ref inner)) => vec![register(inner, schema)?],
+                    Some(VariantShape::Struct(_, ref fields)) => {
+                        let mut ids = Vec::with_capacity(fields.len());
+                        for (_, field) in fields {
+                            ids.push(register(field, schema)?);
+                        }
+                        ids
+                    }
+                    None => vec![],
+                };
+                variant_ids.push(ids);
+            }
+            let mut builder = Type::build().enum_type(name, variants.len());
+            for (variant, ids) in variants.iter().zip(variant_ids) {
+                builder = match *variant {
+                    Some(VariantShape::Newtype(variant_name, _)) => {
+                        builder.newtype_variant(variant_name, ids[0])
+                    }
+                    Some(VariantShape::Struct(variant_name, ref fields)) => {
+                        let mut variant_builder =
+                            builder.struct_variant(variant_name, fields.len());
+                        for (&(field_name, _), id) in fields.iter().zip(ids) {
+                            variant_builder = variant_builder.field(field_name, id);
+                        }
+                        variant_builder.end()
+                    }
+                    None => builder.newtype_variant("", TypeId::SKIP),
+                };
+            }
+            schema.register_type(builder.end())
+        }
+    }
+}
+
+fn conflicting_shapes() -> Error {
+    ser::Error::custom("cannot infer a single type for values of different shapes")
+}
+
+impl Shape {
+    /// Combines the shapes of two values which must have the same type,
+    /// such as the elements of a sequence.
+    fn merge(self, other: Shape) -> Result<Shape, Error> {
+        match (self, other) {
+            (Shape::Unknown, shape) | (shape, Shape::Unknown) => Ok(shape),
+            (Shape::Known(a), Shape::Known(b)) if a == b => Ok(Shape::Known(a)),
+            (Shape::BigInt, Shape::BigInt) => Ok(Shape::BigInt),
+            (
+                Shape::Seq { len, elem },
+                Shape::Seq {
+                    len: other_len,
+                    elem: other_elem,
+                },
+            ) if len == other_len => Ok(Shape::Seq {
+                len,
+                elem: Box::new(elem.merge(*other_elem)?),
+            }),
+            (
+                Shape::Map { key, value },
+                Shape::Map {
+                    key: other_key,
+                    value: other_value,
+                },
+            ) => Ok(Shape::Map {
+                key: Box::new(key.merge(*other_key)?),
+                value: Box::new(value.merge(*other_value)?),
+            }),
+            (
+                Shape::Struct { name, fields },
+                Shape::Struct {
+                    name: other_name,
+                    fields: other_fields,
+                },
+            ) if name == other_name => Ok(Shape::Struct {
+                name,
+                fields: merge_fields(fields, other_fields)?,
+            }),
+            (
+                Shape::Enum { name, variants },
+                Shape::Enum {
+                    name: other_name,
+                    variants: other_variants,
+                },
+            ) if name == other_name => {
+                let mut merged = Vec::new();
+                let mut other_variants = other_variants.into_iter();
+                for variant in variants {
+                    let other_variant = other_variants.next().and_then(|variant| variant);
+                    merged.push(merge_variants(variant, other_variant)?);
+                }
+                merged.extend(other_variants);
+                Ok(Shape::Enum {
+                    name,
+                    variants: merged,
+                })
+            }
+            _ => Err(conflicting_shapes()),
+        }
+    }
+}
+
+fn merge_fields(
+    fields: Vec<(&'static str, Shape)>,
+    other_fields: Vec<(&'static str, Shape)>,
+) -> Result<Vec<(&'static str, Shape)>, Error> {
+    if fields.len() != other_fields.len() {
+        return Err(conflicting_shapes());
+    }
+    let mut merged = Vec::with_capacity(fields.len());
+    for ((name, shape), (other_name, other_shape)) in fields.into_iter().zip(other_fields) {
+        if name != other_name {
+            return Err(conflicting_shapes());
+        }
+        merged.push((name, shape.merge(other_shape)?));
+    }
+    Ok(merged)
+}
+
+fn merge_variants(
+    variant: Option<VariantShape>,
+    other_variant: Option<VariantShape>,
+) -> Result<Option<VariantShape>, Error> {
+    match (variant, other_variant) {
+        (None, variant) | (variant, None) => Ok(variant),
+        (Some(VariantShape::Newtype(name, inner)), Some(VariantShape::Newtype(_, other_inner))) => {
+            Ok(Some(VariantShape::Newtype(name, inner.merge(other_inner)?)))
+        }
+        (Some(VariantShape::Struct(name, fields)), Some(VariantShape::Struct(_, other_fields))) => {
+            Ok(Some(VariantShape::Struct(
+                name,
+                merge_fields(fields, other_fields)?,
+            )))
+        }
+        _ => Err(conflicting_shapes()),
+    }
+}
+
+fn enum_with_variant(name: &'static str, variant_index: u32, variant: VariantShape) -> Shape {
+    let mut variants: Vec<_> = (0..variant_index).map(|_| None).collect();
+    variants.push(Some(variant));
+    Shape::Enum { name, variants }
+}
+
+/// A serializer which records the shape of a value instead of writing
+/// it out.
+struct ShapeSerializer;
+
+impl ser::Serializer for ShapeSerializer {
+    type Ok = Shape;
+    type Error = Error;
+
+    type SerializeSeq = SerializeSeqShape;
+    type SerializeTuple = SerializeSeqShape;
+    type SerializeTupleStruct = SerializeComplexShape;
+    type SerializeTupleVariant = ser::Impossible<Shape, Error>;
+    type SerializeMap = SerializeMapShape;
+    type SerializeStruct = SerializeStructShape;
+    type SerializeStructVariant = SerializeStructShape;
+
+    fn serialize_bool(self, _v: bool) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::BOOL))
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::INT))
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::INT))
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::INT))
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::INT))
+    }
+
+    fn serialize_i128(self, _v: i128) -> Result<Shape, Error> {
+        Ok(Shape::BigInt)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::BYTE))
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::UINT))
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::UINT))
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::UINT))
+    }
+
+    fn serialize_u128(self, _v: u128) -> Result<Shape, Error> {
+        Ok(Shape::BigInt)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::FLOAT))
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::FLOAT))
+    }
+
+    fn serialize_char(self, _v: char) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::INT))
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::STRING))
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::BYTES))
+    }
+
+    fn serialize_none(self) -> Result<Shape, Error> {
+        Ok(Shape::Unknown)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Shape, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Shape, Error> {
+        Err(ser::Error::custom("cannot infer a type for unit values"))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Shape, Error> {
+        Err(ser::Error::custom(format!(
+            "cannot infer a type for unit struct {}",
+            name
+        )))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Shape, Error> {
+        Err(ser::Error::custom(format!(
+            "cannot infer a type for unit variant {}::{}",
+            name, variant
+        )))
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<Shape, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Shape, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let inner = value.serialize(ShapeSerializer)?;
+        Ok(enum_with_variant(
+            name,
+            variant_index,
+            VariantShape::Newtype(variant, inner),
+        ))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SerializeSeqShape, Error> {
+        Ok(SerializeSeqShape {
+            len: None,
+            elem: Shape::Unknown,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeSeqShape, Error> {
+        Ok(SerializeSeqShape {
+            len: Some(len),
+            elem: Shape::Unknown,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<SerializeComplexShape, Error> {
+        if name == COMPLEX_NAME {
+            Ok(SerializeComplexShape)
+        } else {
+            Err(ser::Error::custom(format!(
+                "cannot infer a type for tuple struct {}",
+                name
+            )))
+        }
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(ser::Error::custom(format!(
+            "cannot infer a type for tuple variant {}::{}",
+            name, variant
+        )))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMapShape, Error> {
+        Ok(SerializeMapShape {
+            key: Shape::Unknown,
+            value: Shape::Unknown,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeStructShape, Error> {
+        Ok(SerializeStructShape {
+            name,
+            variant: None,
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeStructShape, Error> {
+        Ok(SerializeStructShape {
+            name,
+            variant: Some((variant_index, variant)),
+            fields: Vec::with_capacity(len),
+        })
+    }
+}
+
+struct SerializeSeqShape {
+    len: Option<usize>,
+    elem: Shape,
+}
+
+impl ser::SerializeSeq for SerializeSeqShape {
+    type Ok = Shape;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let elem = ::std::mem::replace(&mut self.elem, Shape::Unknown);
+        self.elem = elem.merge(value.serialize(ShapeSerializer)?)?;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Shape, Error> {
+        Ok(Shape::Seq {
+            len: self.len,
+            elem: Box::new(self.elem),
+        })
+    }
+}
+
+impl ser::SerializeTuple for SerializeSeqShape {
+    type Ok = Shape;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Shape, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeComplexShape;
+
+impl ser::SerializeTupleStruct for SerializeComplexShape {
+    type Ok = Shape;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, _value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(())
+    }
+
+    fn end(self) -> Result<Shape, Error> {
+        Ok(Shape::Known(TypeId::COMPLEX))
+    }
+}
+
+struct SerializeMapShape {
+    key: Shape,
+    value: Shape,
+}
+
+impl ser::SerializeMap for SerializeMapShape {
+    type Ok = Shape;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let shape = ::std::mem::replace(&mut self.key, Shape::Unknown);
+        self.key = shape.merge(key.serialize(ShapeSerializer)?)?;
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let shape = ::std::mem::replace(&mut self.value, Shape::Unknown);
+        self.value = shape.merge(value.serialize(ShapeSerializer)?)?;
+        Ok(())
+    }
+
+    fn end(self) -> Result<Shape, Error> {
+        Ok(Shape::Map {
+            key: Box::new(self.key),
+            value: Box::new(self.value),
+        })
+    }
+}
+
+struct SerializeStructShape {
+    name: &'static str,
+    variant: Option<(u32, &'static str)>,
+    fields: Vec<(&'static str, Shape)>,
+}
+
+impl ser::SerializeStruct for SerializeStructShape {
+    type Ok = Shape;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.fields.push((key, value.serialize(ShapeSerializer)?));
+        Ok(())
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Error> {
+        self.fields.push((key, Shape::Unknown));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Shape, Error> {
+        match self.variant {
+            Some((variant_index, variant)) => Ok(enum_with_variant(
+                self.name,
+                variant_index,
+                VariantShape::Struct(variant, self.fields),
+            )),
+            None => Ok(Shape::Struct {
+                name: self.name,
+                fields: self.fields,
+            }),
+        }
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeStructShape {
+    type Ok = Shape;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Error> {
+        ser::SerializeStruct::skip_field(self, key)
+    }
+
+    fn end(self) -> Result<Shape, Error> {
+        ser::SerializeStruct::end(self)
+    }
+}
@@ -1,4 +1,4 @@
-use std::borrow::{Borrow, BorrowMut};
+use std::borrow::Borrow;
 
 use serde::ser::{self, Impossible};
 use serde::Serialize;
@@ -6,9 +6,11 @@ use serde::Serialize;
 use internal::gob::Message;
 use internal::types::TypeId;
 
+use big;
+use complex::COMPLEX_NAME;
 use error::Error;
-use schema::Schema;
-use ser::{Output, OutputPart};
+use schema::{GoCompatibility, Schema, WideIntegers, GO_MAX_MESSAGE_LEN};
+use ser::{Options, Output, OutputPart, ZeroValues};
 
 mod serialize_struct;
 pub(crate) use self::serialize_struct::SerializeStructValue;
@@ -22,8 +24,16 @@ mod serialize_variant;
 pub(crate) use self::serialize_variant::{SerializeStructVariantValue, SerializeVariantValue};
 mod serialize_empty;
 pub(crate) use self::serialize_empty::SerializeEmptyValue;
+mod byte_value;
+pub(crate) use self::byte_value::ByteValueSerializer;
+mod field_name;
+pub(crate) use self::field_name::FieldNameSerializer;
+mod none_probe;
+pub(crate) use self::none_probe::is_none;
 mod serialize_wire_types;
-pub(crate) use self::serialize_wire_types::SerializeWireTypes;
+pub(crate) use self::serialize_wire_types::{encode_wire_type, SerializeWireTypes};
+mod infer_type;
+pub(crate) use self::infer_type::infer_type;
 
 pub(crate) struct SerializationOk<S> {
     pub ctx: SerializationCtx<S>,
@@ -32,13 +42,19 @@ pub(crate) struct SerializationOk<S> {
 
 pub(crate) struct SerializationCtx<S> {
     pub schema: S,
+    pub options: Options,
     pub value: Message<Vec<u8>>,
 }
 
 impl<S> SerializationCtx<S> {
     pub(crate) fn with_schema(schema: S) -> Self {
+        SerializationCtx::with_options(schema, Options::default())
+    }
+
+    pub(crate) fn with_options(schema: S, options: Options) -> Self {
         SerializationCtx {
             schema,
+            options,
             value: Message::new(Vec::new()),
         }
     }
@@ -49,10 +65,11 @@ impl<S> SerializationCtx<S> {
         F: FnOnce(SerializationCtx<&Schema>) -> Result<SerializationOk<&Schema>, E>,
     {
         let (is_empty, msg) = {
-            let buf = ::std::mem::replace(self.value.get_mut(), Vec::new());
+            let buf = std::mem::take(self.value.get_mut());
             let msg = Message::new(buf);
             let ctx = SerializationCtx {
                 schema: self.schema.borrow(),
+                options: self.options,
                 value: msg,
             };
             let ok = f(ctx)?;
@@ -64,10 +81,17 @@ impl<S> SerializationCtx<S> {
 
     pub(crate) fn flush<O: Output>(&mut self, mut out: O) -> Result<(), Error>
     where
-        S: BorrowMut<Schema>,
+        S: Borrow<Schema>,
     {
-        self.schema.borrow_mut().write_pending(&mut out)?;
-        let buffer = ::std::mem::replace(self.value.get_mut(), Vec::new());
+        let buffer = std::mem::take(self.value.get_mut());
+        if self.schema.borrow().go_compatibility() != GoCompatibility::Unchecked
+            && buffer.len() > GO_MAX_MESSAGE_LEN
+        {
+            return Err(ser::Error::custom(format!(
+                "message of {} bytes is larger than Go decodes",
+                buffer.len()
+            )));
+        }
         out.serialize_part(OutputPart::new(buffer))
     }
 }
@@ -79,7 +103,11 @@ pub(crate) struct FieldValueSerializer<S> {
 
 impl<S> FieldValueSerializer<S> {
     fn check_type(&self, got: TypeId) -> Result<(), Error> {
-        if self.type_id != got {
+        if self.type_id == TypeId::INTERFACE {
+            Err(ser::Error::custom(
+                "only nil values can be sent for interface types",
+            ))
+        } else if self.type_id.on_wire() != got {
             Err(ser::Error::custom(format!(
                 "type id mismatch: got {}, expected {}",
                 got.0, self.type_id.0
@@ -90,6 +118,21 @@ impl<S> FieldValueSerializer<S> {
     }
 }
 
+impl<S: Borrow<Schema>> FieldValueSerializer<S> {
+    /// Sends an `i128` or `u128`, encoded as a `big.Int`.
+    fn serialize_big_int(self, buf: &[u8], is_zero: bool) -> Result<SerializationOk<S>, Error> {
+        if self.ctx.schema.borrow().wide_integers() != WideIntegers::BigInt {
+            return Err(ser::Error::custom(
+                "i128 and u128 values can only be sent as big.Int, see `WideIntegers`",
+            ));
+        }
+        let mut ok = ser::Serializer::serialize_bytes(self, buf)?;
+        // Go leaves out zero `big.Int`s like other zero values
+        ok.is_empty = is_zero;
+        Ok(ok)
+    }
+}
+
 impl<S> ser::Serializer for FieldValueSerializer<S>
 where
     S: Borrow<Schema>,
@@ -99,7 +142,7 @@ where
 
     type SerializeSeq = SerializeSeqValue<S>;
     type SerializeTuple = SerializeTupleValue<S>;
-    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = SerializeTupleValue<S>;
     type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
     type SerializeMap = SerializeMapValue<S>;
     type SerializeStruct = SerializeStructValue<S>;
@@ -111,7 +154,7 @@ where
         self.ctx.value.write_bool(v);
         Ok(SerializationOk {
             ctx: self.ctx,
-            is_empty: v == false,
+            is_empty: !v,
         })
     }
 
@@ -136,6 +179,12 @@ where
         })
     }
 
+    fn serialize_i128(self, v: i128) -> Result<Self::Ok, Self::Error> {
+        let mut buf = Vec::new();
+        big::encode_i128(v, &mut buf);
+        self.serialize_big_int(&buf, v == 0)
+    }
+
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
         self.serialize_u64(v as u64)
     }
@@ -157,6 +206,12 @@ where
         })
     }
 
+    fn serialize_u128(self, v: u128) -> Result<Self::Ok, Self::Error> {
+        let mut buf = Vec::new();
+        big::encode_u128(v, &mut buf);
+        self.serialize_big_int(&buf, v == 0)
+    }
+
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         self.serialize_f64(v as f64)
     }
@@ -179,24 +234,35 @@ where
         self.ctx.value.write_bytes(v.as_bytes());
         Ok(SerializationOk {
             ctx: self.ctx,
-            is_empty: v.len() == 0,
+            is_empty: v.is_empty(),
         })
     }
 
     fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        self.check_type(TypeId::BYTES)?;
+        if !self.ctx.schema.borrow().is_opaque(self.type_id) {
+            self.check_type(TypeId::BYTES)?;
+        }
         self.ctx.value.write_bytes(v);
         Ok(SerializationOk {
             ctx: self.ctx,
-            is_empty: v.len() == 0,
+            is_empty: v.is_empty(),
         })
     }
 
-    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+    fn serialize_none(mut self) -> Result<Self::Ok, Self::Error> {
+        if self.type_id == TypeId::INTERFACE {
+            // a nil interface is sent as an empty type name
+            self.ctx.value.write_uint(0);
+            return Ok(SerializationOk {
+                ctx: self.ctx,
+                is_empty: true,
+            });
+        }
         let value = {
             let ser = FieldValueSerializer {
                 ctx: SerializationCtx {
                     schema: self.ctx.schema.borrow(),
+                    options: self.ctx.options,
                     value: self.ctx.value,
                 },
                 type_id: self.type_id,
@@ -207,17 +273,29 @@ where
         Ok(SerializationOk {
             ctx: SerializationCtx {
                 schema: self.ctx.schema,
+                options: self.ctx.options,
                 value,
             },
             is_empty: true,
         })
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        if self.ctx.schema.borrow().is_nullable(self.type_id) {
+            let mut inner = SerializeStructValue::new(self.ctx, self.type_id)?;
+            // the value field is followed by `Valid`
+            inner.serialize_current_field(value)?;
+            inner.serialize_current_field(&true)?;
+            return ser::SerializeStruct::end(inner);
+        }
+        let mut ok = value.serialize(self)?;
+        if ok.ctx.options.zero_values == ZeroValues::EmitSome {
+            ok.is_empty = false;
+        }
+        Ok(ok)
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
@@ -232,23 +310,28 @@ where
         self,
         _name: &'static str,
         _variant_index: u32,
-        _variant: &'static str,
+        variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
+        // the tag of an adjacently tagged enum
+        if self.type_id == TypeId::STRING {
+            return self.serialize_str(variant);
+        }
         Err(ser::Error::custom("not implemented yet"))
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         _name: &'static str,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        Err(ser::Error::custom("not implemented yet"))
+        // newtypes are sent as the type they wrap
+        value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
         variant_index: u32,
@@ -256,7 +339,7 @@ where
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let ser = SerializeVariantValue::new(self.ctx, self.type_id, variant_index)?;
         ser.serialize_newtype(value)
@@ -267,14 +350,18 @@ where
     }
 
     fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        SerializeTupleValue::homogeneous(self.ctx, self.type_id)
+        SerializeTupleValue::new(self.ctx, self.type_id)
     }
 
     fn serialize_tuple_struct(
         self,
-        _name: &'static str,
+        name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        if name == COMPLEX_NAME {
+            self.check_type(TypeId::COMPLEX)?;
+            return Ok(SerializeTupleValue::complex(self.ctx));
+        }
         Err(ser::Error::custom("not implemented yet"))
     }
 
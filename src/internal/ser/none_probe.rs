@@ -0,0 +1,196 @@
+use std::error;
+use std::fmt;
+
+use serde::ser::{self, Impossible, Serialize};
+
+/// Finds out whether a value serializes as `None`, returning an error as
+/// soon as it turns out to be something else.
+struct NoneProbe;
+
+/// Returns `true` if the value serializes as `None`, possibly wrapped in
+/// newtypes.
+pub(crate) fn is_none<T: ?Sized + Serialize>(value: &T) -> bool {
+    value.serialize(NoneProbe).is_ok()
+}
+
+#[derive(Debug)]
+struct NotNone;
+
+impl fmt::Display for NotNone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("not none")
+    }
+}
+
+impl error::Error for NotNone {}
+
+impl ser::Error for NotNone {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        NotNone
+    }
+}
+
+impl ser::Serializer for NoneProbe {
+    type Ok = ();
+    type Error = NotNone;
+
+    type SerializeSeq = Impossible<(), NotNone>;
+    type SerializeTuple = Impossible<(), NotNone>;
+    type SerializeTupleStruct = Impossible<(), NotNone>;
+    type SerializeTupleVariant = Impossible<(), NotNone>;
+    type SerializeMap = Impossible<(), NotNone>;
+    type SerializeStruct = Impossible<(), NotNone>;
+    type SerializeStructVariant = Impossible<(), NotNone>;
+
+    fn serialize_bool(self, _v: bool) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_i8(self, _v: i8) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_i16(self, _v: i16) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_i32(self, _v: i32) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_i64(self, _v: i64) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_u8(self, _v: u8) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_u16(self, _v: u16) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_u32(self, _v: u32) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_u64(self, _v: u64) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_char(self, _v: char) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_str(self, _v: &str) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_none(self) -> Result<(), NotNone> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<(), NotNone>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(NotNone)
+    }
+
+    fn serialize_unit(self) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), NotNone>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), NotNone>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(NotNone)
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, NotNone> {
+        Err(NotNone)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, NotNone> {
+        Err(NotNone)
+    }
+}
@@ -3,6 +3,8 @@ use std::borrow::Borrow;
 use serde::{self, Serialize, Serializer};
 use serde_schema::types::Type;
 
+use complex::Complex128;
+use schema::opaque_wire_type;
 use ser::{Schema, TypeId};
 
 pub struct SerializeEmptyValue<S> {
@@ -21,11 +23,11 @@ impl<S: Borrow<Schema>> SerializeEmptyValue<S> {
         ser: Z,
     ) -> Result<Z::Ok, Z::Error> {
         match ty {
-            &Type::Option(ref option_type) => {
+            Type::Option(option_type) => {
                 SerializeEmptyValue::new(self.schema.borrow(), *option_type.inner_type())
                     .serialize(ser)
             }
-            &Type::NewtypeStruct(ref newtype_struct_type) => {
+            Type::NewtypeStruct(newtype_struct_type) => {
                 let value = SerializeEmptyValue::new(
                     self.schema.borrow(),
                     *newtype_struct_type.inner_type(),
@@ -36,7 +38,7 @@ impl<S: Borrow<Schema>> SerializeEmptyValue<S> {
                 let ser_seq = ser.serialize_seq(Some(0))?;
                 serde::ser::SerializeSeq::end(ser_seq)
             }
-            &Type::Tuple(ref tuple_type) => {
+            Type::Tuple(tuple_type) => {
                 let mut ser_tup = ser.serialize_tuple(tuple_type.element_types().len())?;
                 for element_type in tuple_type.element_types() {
                     let value = SerializeEmptyValue::new(self.schema.borrow(), *element_type);
@@ -44,7 +46,7 @@ impl<S: Borrow<Schema>> SerializeEmptyValue<S> {
                 }
                 serde::ser::SerializeTuple::end(ser_tup)
             }
-            &Type::TupleStruct(ref tuple_struct_type) => {
+            Type::TupleStruct(tuple_struct_type) => {
                 let mut ser_tup =
                     ser.serialize_tuple_struct("", tuple_struct_type.element_types().len())?;
                 for element_type in tuple_struct_type.element_types() {
@@ -57,7 +59,10 @@ impl<S: Borrow<Schema>> SerializeEmptyValue<S> {
                 let ser_map = ser.serialize_map(Some(0))?;
                 serde::ser::SerializeMap::end(ser_map)
             }
-            &Type::Struct(ref struct_type) => {
+            Type::Struct(struct_type) if opaque_wire_type(struct_type.name()).is_some() => {
+                ser.serialize_bytes(&[])
+            }
+            Type::Struct(struct_type) => {
                 let mut ser_struct = ser.serialize_struct("", struct_type.fields().len())?;
                 for _field in struct_type.fields() {
                     serde::ser::SerializeStruct::skip_field(&mut ser_struct, "")?;
@@ -74,16 +79,18 @@ impl<S: Borrow<Schema>> SerializeEmptyValue<S> {
 
 impl<S: Borrow<Schema>> Serialize for SerializeEmptyValue<S> {
     fn serialize<Z: Serializer>(&self, ser: Z) -> Result<Z::Ok, Z::Error> {
-        match self.type_id {
+        match self.type_id.on_wire() {
             TypeId::BOOL => ser.serialize_bool(false),
             TypeId::INT => ser.serialize_i8(0),
             TypeId::UINT => ser.serialize_u8(0),
             TypeId::FLOAT => ser.serialize_f32(0.0),
             TypeId::BYTES => ser.serialize_bytes(&[]),
             TypeId::STRING => ser.serialize_str(""),
+            TypeId::COMPLEX => Complex128::default().serialize(ser),
+            TypeId::INTERFACE => ser.serialize_none(),
             _ => {
                 if let Some(ty) = self.schema.borrow().lookup(self.type_id) {
-                    self.serialize_with_type(&*ty, ser)
+                    self.serialize_with_type(&ty, ser)
                 } else {
                     Err(serde::ser::Error::custom(format!(
                         "empty representation not available for type with id {}",
@@ -5,16 +5,27 @@ use serde_schema::types::Type;
 
 use error::Error;
 use internal::types::TypeId;
-use schema::Schema;
+use schema::{NoneElements, Schema};
 
-use super::{FieldValueSerializer, SerializationCtx, SerializationOk};
+use super::SerializeStructValue;
+use super::{
+    is_none, FieldNameSerializer, FieldValueSerializer, SerializationCtx, SerializationOk,
+};
 
-pub(crate) struct SerializeMapValue<S> {
-    needs_init: bool,
-    ctx: SerializationCtx<S>,
-    len: usize,
-    key: TypeId,
-    value: TypeId,
+pub(crate) enum SerializeMapValue<S> {
+    Map {
+        needs_init: bool,
+        ctx: SerializationCtx<S>,
+        len: usize,
+        key: TypeId,
+        value: TypeId,
+    },
+    // a map with string keys sent as a struct, as serde serializes
+    // structs with flattened fields
+    Struct {
+        inner: SerializeStructValue<S>,
+        key: Option<String>,
+    },
 }
 
 impl<S: Borrow<Schema>> SerializeMapValue<S> {
@@ -23,8 +34,12 @@ impl<S: Borrow<Schema>> SerializeMapValue<S> {
         ser_len: Option<usize>,
         type_id: TypeId,
     ) -> Result<Self, Error> {
+        if ctx.schema.borrow().is_struct(type_id) {
+            let inner = SerializeStructValue::new(ctx, type_id)?;
+            return Ok(SerializeMapValue::Struct { inner, key: None });
+        }
         let (len, key, value) = if let Some(schema_type) = ctx.schema.borrow().lookup(type_id) {
-            if let &Type::Map(ref map_type) = &*schema_type {
+            if let Type::Map(map_type) = &*schema_type {
                 if let Some(len) = ser_len {
                     (len, *map_type.key_type(), *map_type.value_type())
                 } else {
@@ -39,7 +54,7 @@ impl<S: Borrow<Schema>> SerializeMapValue<S> {
             return Err(ser::Error::custom("type not found"));
         };
 
-        Ok(SerializeMapValue {
+        Ok(SerializeMapValue::Map {
             needs_init: true,
             ctx,
             len,
@@ -53,44 +68,96 @@ impl<S: Borrow<Schema>> ser::SerializeMap for SerializeMapValue<S> {
     type Ok = SerializationOk<S>;
     type Error = Error;
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        if self.needs_init {
-            self.ctx.value.write_uint(self.len as u64);
-            self.needs_init = false;
+        match *self {
+            SerializeMapValue::Map {
+                ref mut needs_init,
+                ref mut ctx,
+                len,
+                key: type_id,
+                ..
+            } => {
+                reject_none(ctx, type_id, key)?;
+                if *needs_init {
+                    ctx.value.write_uint(len as u64);
+                    *needs_init = false;
+                }
+                ctx.with_borrow(|ctx| {
+                    let de = FieldValueSerializer { ctx, type_id };
+                    key.serialize(de)
+                })?;
+            }
+            SerializeMapValue::Struct {
+                key: ref mut field_name,
+                ..
+            } => {
+                *field_name = Some(key.serialize(FieldNameSerializer)?);
+            }
         }
-        let type_id = self.key;
-        self.ctx.with_borrow(|ctx| {
-            let de = FieldValueSerializer { ctx, type_id };
-            key.serialize(de)
-        })?;
         Ok(())
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        let type_id = self.value;
-        self.ctx.with_borrow(|ctx| {
-            let de = FieldValueSerializer { ctx, type_id };
-            value.serialize(de)
-        })?;
-        Ok(())
+        match *self {
+            SerializeMapValue::Map {
+                ref mut ctx,
+                value: type_id,
+                ..
+            } => {
+                reject_none(ctx, type_id, value)?;
+                ctx.with_borrow(|ctx| {
+                    let de = FieldValueSerializer { ctx, type_id };
+                    value.serialize(de)
+                })?;
+                Ok(())
+            }
+            SerializeMapValue::Struct {
+                ref mut inner,
+                ref mut key,
+            } => {
+                let key = key.take().ok_or_else(|| {
+                    <Error as ser::Error>::custom("map value serialized before its key")
+                })?;
+                inner.serialize_named_field(&key, value)
+            }
+        }
     }
 
-    fn end(mut self) -> Result<Self::Ok, Self::Error> {
-        let is_empty = self.len == 0;
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        match self {
+            SerializeMapValue::Map { mut ctx, len, .. } => {
+                let is_empty = len == 0;
+
+                if is_empty {
+                    ctx.value.write_uint(0);
+                }
 
-        if is_empty {
-            self.ctx.value.write_uint(0);
+                Ok(SerializationOk { ctx, is_empty })
+            }
+            SerializeMapValue::Struct { inner, .. } => ser::SerializeStruct::end(inner),
         }
+    }
+}
 
-        Ok(SerializationOk {
-            ctx: self.ctx,
-            is_empty,
-        })
+fn reject_none<S, T>(ctx: &SerializationCtx<S>, type_id: TypeId, value: &T) -> Result<(), Error>
+where
+    S: Borrow<Schema>,
+    T: ?Sized + Serialize,
+{
+    let schema = ctx.schema.borrow();
+    if schema.none_elements() == NoneElements::Reject
+        && !schema.is_nullable(type_id)
+        && is_none(value)
+    {
+        return Err(ser::Error::custom(
+            "None cannot be sent as a key or value of a map, see `NoneElements`",
+        ));
     }
+    Ok(())
 }
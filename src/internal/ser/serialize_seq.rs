@@ -5,15 +5,19 @@ use serde_schema::types::Type;
 
 use error::Error;
 use internal::types::TypeId;
-use schema::Schema;
+use schema::{NoneElements, Schema};
 
-use super::{FieldValueSerializer, SerializationCtx, SerializationOk};
+use super::{
+    is_none, ByteValueSerializer, FieldValueSerializer, SerializationCtx, SerializationOk,
+};
 
 pub(crate) struct SerializeSeqValue<S> {
     needs_init: bool,
     ctx: SerializationCtx<S>,
     len: usize,
     elem: TypeId,
+    // the elements of a sequence of `u8` which is sent as bytes
+    bytes: Option<Vec<u8>>,
 }
 
 impl<S: Borrow<Schema>> SerializeSeqValue<S> {
@@ -22,8 +26,18 @@ impl<S: Borrow<Schema>> SerializeSeqValue<S> {
         ser_len: Option<usize>,
         type_id: TypeId,
     ) -> Result<Self, Error> {
+        if type_id == TypeId::BYTES {
+            return Ok(SerializeSeqValue {
+                needs_init: false,
+                ctx,
+                len: 0,
+                elem: TypeId::BYTE,
+                bytes: Some(Vec::with_capacity(ser_len.unwrap_or(0))),
+            });
+        }
+
         let (len, elem) = if let Some(schema_type) = ctx.schema.borrow().lookup(type_id) {
-            if let &Type::Seq(ref seq_type) = &*schema_type {
+            if let Type::Seq(seq_type) = &*schema_type {
                 if let Some(len) = seq_type.len().or(ser_len) {
                     (len, *seq_type.element_type())
                 } else {
@@ -43,6 +57,7 @@ impl<S: Borrow<Schema>> SerializeSeqValue<S> {
             ctx,
             len,
             elem,
+            bytes: None,
         })
     }
 }
@@ -51,10 +66,22 @@ impl<S: Borrow<Schema>> ser::SerializeSeq for SerializeSeqValue<S> {
     type Ok = SerializationOk<S>;
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
+        if let Some(bytes) = &mut self.bytes {
+            bytes.push(value.serialize(ByteValueSerializer)?);
+            return Ok(());
+        }
+        if self.ctx.schema.borrow().none_elements() == NoneElements::Reject
+            && !self.ctx.schema.borrow().is_nullable(self.elem)
+            && is_none(value)
+        {
+            return Err(ser::Error::custom(
+                "None cannot be sent as an element of a slice or array, see `NoneElements`",
+            ));
+        }
         if self.needs_init {
             self.ctx.value.write_uint(self.len as u64);
             self.needs_init = false;
@@ -68,6 +95,14 @@ impl<S: Borrow<Schema>> ser::SerializeSeq for SerializeSeqValue<S> {
     }
 
     fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        if let Some(bytes) = self.bytes {
+            self.ctx.value.write_bytes(&bytes);
+            return Ok(SerializationOk {
+                ctx: self.ctx,
+                is_empty: bytes.is_empty(),
+            });
+        }
+
         let is_empty = self.len == 0;
 
         if is_empty {
@@ -7,6 +7,7 @@ use serde_schema::types::{StructField, Type};
 use error::Error;
 use internal::types::TypeId;
 use schema::{Schema, SchemaType};
+use ser::ZeroValues;
 
 use super::{FieldValueSerializer, SerializationCtx, SerializationOk};
 
@@ -14,7 +15,11 @@ pub(crate) struct SerializeStructValue<S> {
     ctx: SerializationCtx<S>,
     fields: OwningRef<SchemaType, [StructField<TypeId>]>,
     current_field_idx: usize,
+    // fields marked as skipped are not counted
+    current_wire_idx: usize,
     last_serialized_field_idx: i64,
+    // fields are looked up by name, see `KEYED_NAME`
+    keyed: bool,
 }
 
 impl<S: Borrow<Schema>> SerializeStructValue<S> {
@@ -22,7 +27,7 @@ impl<S: Borrow<Schema>> SerializeStructValue<S> {
         let fields;
         if let Some(schema_type) = ctx.schema.borrow().lookup(type_id) {
             fields = OwningRef::new(schema_type).try_map::<_, _, Error>(|typ| {
-                if let &Type::Struct(ref struct_type) = typ {
+                if let Type::Struct(struct_type) = typ {
                     Ok(struct_type.fields())
                 } else {
                     Err(ser::Error::custom("schema mismatch, not a struct"))
@@ -31,7 +36,10 @@ impl<S: Borrow<Schema>> SerializeStructValue<S> {
         } else {
             return Err(ser::Error::custom("type not found"));
         }
-        Ok(SerializeStructValue::from_parts(ctx, fields))
+        let keyed = ctx.schema.borrow().is_keyed(type_id);
+        let mut inner = SerializeStructValue::from_parts(ctx, fields);
+        inner.keyed = keyed;
+        Ok(inner)
     }
 
     pub(crate) fn from_parts(
@@ -42,47 +50,93 @@ impl<S: Borrow<Schema>> SerializeStructValue<S> {
             ctx,
             fields,
             current_field_idx: 0,
+            current_wire_idx: 0,
             last_serialized_field_idx: -1,
+            keyed: false,
         }
     }
-}
 
-impl<S: Borrow<Schema>> ser::SerializeStruct for SerializeStructValue<S> {
-    type Ok = SerializationOk<S>;
-    type Error = Error;
+    /// Serializes the field named `key`, skipping all fields before it
+    /// which have not been serialized yet.
+    pub(crate) fn serialize_named_field<T>(&mut self, key: &str, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let skipped = self.fields[self.current_field_idx..]
+            .iter()
+            .position(|field| field.name() == key)
+            .ok_or_else(|| {
+                <Error as ser::Error>::custom(format!(
+                    "field {:?} is not part of the registered type, or out of order",
+                    key
+                ))
+            })?;
+        for _ in 0..skipped {
+            self.skip_current_field();
+        }
+        self.serialize_current_field(value)
+    }
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        _key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    pub(crate) fn serialize_current_field<T>(&mut self, value: &T) -> Result<(), Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
+        let type_id = *self.fields[self.current_field_idx].field_type();
+        if type_id == TypeId::SKIP {
+            self.current_field_idx += 1;
+            return Ok(());
+        }
+
         let pre_pos = self.ctx.value.get_ref().len();
-        let field_delta = self.current_field_idx as i64 - self.last_serialized_field_idx;
+        let field_delta = self.current_wire_idx as i64 - self.last_serialized_field_idx;
         self.ctx.value.write_uint(field_delta as u64);
 
-        let type_id = *self.fields[self.current_field_idx].field_type();
         let is_empty = self.ctx.with_borrow(|ctx| {
             let de = FieldValueSerializer { ctx, type_id };
             value.serialize(de)
         })?;
 
-        if !is_empty {
-            self.last_serialized_field_idx = self.current_field_idx as i64;
+        if !is_empty || self.ctx.options.zero_values == ZeroValues::Emit {
+            self.last_serialized_field_idx = self.current_wire_idx as i64;
         } else {
             // reset the buffer to the previous position
             self.ctx.value.get_mut().truncate(pre_pos);
         }
 
         self.current_field_idx += 1;
+        self.current_wire_idx += 1;
 
         Ok(())
     }
 
-    fn skip_field(&mut self, _key: &'static str) -> Result<(), Self::Error> {
+    fn skip_current_field(&mut self) {
+        if *self.fields[self.current_field_idx].field_type() != TypeId::SKIP {
+            self.current_wire_idx += 1;
+        }
         self.current_field_idx += 1;
+    }
+}
+
+impl<S: Borrow<Schema>> ser::SerializeStruct for SerializeStructValue<S> {
+    type Ok = SerializationOk<S>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.keyed {
+            self.serialize_named_field(key, value)
+        } else {
+            self.serialize_current_field(value)
+        }
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), Self::Error> {
+        // keyed structs skip over the fields which are not serialized
+        if !self.keyed {
+            self.skip_current_field();
+        }
         Ok(())
     }
 
@@ -1,42 +1,104 @@
 use std::borrow::Borrow;
+use std::io::Cursor;
 
 use serde::ser::{self, Serialize};
 
 use error::Error;
+use internal::gob::Message;
 use internal::types::TypeId;
 use schema::Schema;
 
-use super::{SerializationCtx, SerializationOk, SerializeSeqValue};
+use super::{FieldValueSerializer, SerializationCtx, SerializationOk};
+use super::{SerializeSeqValue, SerializeStructValue};
 
 pub(crate) enum SerializeTupleValue<S> {
     Homogeneous(SerializeSeqValue<S>),
+    // a tuple with elements of different types, sent as a struct
+    Struct(SerializeStructValue<S>),
+    Complex {
+        ctx: SerializationCtx<S>,
+        is_empty: bool,
+    },
 }
 
 impl<S: Borrow<Schema>> SerializeTupleValue<S> {
-    pub(crate) fn homogeneous(ctx: SerializationCtx<S>, type_id: TypeId) -> Result<Self, Error> {
+    pub(crate) fn new(ctx: SerializationCtx<S>, type_id: TypeId) -> Result<Self, Error> {
+        if ctx.schema.borrow().is_struct(type_id) {
+            let inner = SerializeStructValue::new(ctx, type_id)?;
+            return Ok(SerializeTupleValue::Struct(inner));
+        }
         let inner = SerializeSeqValue::new(ctx, None, type_id)?;
         Ok(SerializeTupleValue::Homogeneous(inner))
     }
+
+    /// A complex number, encoded as its real and imaginary parts.
+    pub(crate) fn complex(ctx: SerializationCtx<S>) -> Self {
+        SerializeTupleValue::Complex {
+            ctx,
+            is_empty: true,
+        }
+    }
 }
 
 impl<S: Borrow<Schema>> ser::SerializeTuple for SerializeTupleValue<S> {
     type Ok = SerializationOk<S>;
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        match self {
-            &mut SerializeTupleValue::Homogeneous(ref mut inner) => {
+        match *self {
+            SerializeTupleValue::Homogeneous(ref mut inner) => {
                 ser::SerializeSeq::serialize_element(inner, value)
             }
+            SerializeTupleValue::Struct(ref mut inner) => {
+                ser::SerializeStruct::serialize_field(inner, "", value)
+            }
+            SerializeTupleValue::Complex {
+                ref mut ctx,
+                ref mut is_empty,
+            } => {
+                let start = ctx.value.get_ref().len();
+                ctx.with_borrow(|ctx| {
+                    let ser = FieldValueSerializer {
+                        ctx,
+                        type_id: TypeId::FLOAT,
+                    };
+                    value.serialize(ser)
+                })?;
+                // float fields are sent even when zero, so the parts are
+                // never empty; read them back to leave out zero complex
+                // fields like Go does
+                let part = &ctx.value.get_ref()[start..];
+                let part = Message::new(Cursor::new(part)).read_float();
+                *is_empty &= part.ok() == Some(0.0);
+                Ok(())
+            }
         }
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
         match self {
             SerializeTupleValue::Homogeneous(inner) => ser::SerializeSeq::end(inner),
+            SerializeTupleValue::Struct(inner) => ser::SerializeStruct::end(inner),
+            SerializeTupleValue::Complex { ctx, is_empty } => Ok(SerializationOk { ctx, is_empty }),
         }
     }
 }
+
+impl<S: Borrow<Schema>> ser::SerializeTupleStruct for SerializeTupleValue<S> {
+    type Ok = SerializationOk<S>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeTuple::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeTuple::end(self)
+    }
+}
@@ -8,13 +8,15 @@ use error::Error;
 use internal::types::TypeId;
 use schema::{Schema, SchemaType};
 
+use super::serialize_wire_types::is_skipped_variant;
 use super::SerializeStructValue;
 use super::{FieldValueSerializer, SerializationCtx, SerializationOk};
 
 pub(crate) struct SerializeVariantValue<S> {
     ctx: SerializationCtx<S>,
     variant: OwningRef<SchemaType, EnumVariant<TypeId>>,
-    variant_idx: u32,
+    // the index among the variants which are part of the type
+    wire_idx: u32,
 }
 
 impl<S: Borrow<Schema>> SerializeVariantValue<S> {
@@ -24,14 +26,28 @@ impl<S: Borrow<Schema>> SerializeVariantValue<S> {
         variant_idx: u32,
     ) -> Result<Self, Error> {
         let variant;
+        let mut wire_idx = variant_idx;
 
         if let Some(schema_type) = ctx.schema.borrow().lookup(type_id) {
             variant = OwningRef::new(schema_type).try_map::<_, _, Error>(|typ| {
-                if let &Type::Enum(ref enum_type) = typ {
-                    if let Some(enum_variant) = enum_type.variant(variant_idx) {
-                        Ok(enum_variant)
-                    } else {
-                        Err(ser::Error::custom("unknown enum variant type"))
+                if let Type::Enum(enum_type) = typ {
+                    match enum_type.variant(variant_idx) {
+                        Some(enum_variant) if is_skipped_variant(enum_variant) => {
+                            Err(ser::Error::custom(format!(
+                                "variant {} of {} is not part of the registered type",
+                                variant_idx,
+                                enum_type.name()
+                            )))
+                        }
+                        Some(enum_variant) => {
+                            let skipped = enum_type.variants()[..variant_idx as usize]
+                                .iter()
+                                .filter(|variant| is_skipped_variant(variant))
+                                .count();
+                            wire_idx -= skipped as u32;
+                            Ok(enum_variant)
+                        }
+                        None => Err(ser::Error::custom("unknown enum variant type")),
                     }
                 } else {
                     Err(ser::Error::custom("schema mismatch, not an enum"))
@@ -44,7 +60,7 @@ impl<S: Borrow<Schema>> SerializeVariantValue<S> {
         Ok(SerializeVariantValue {
             ctx,
             variant,
-            variant_idx,
+            wire_idx,
         })
     }
 
@@ -58,14 +74,11 @@ impl<S: Borrow<Schema>> SerializeVariantValue<S> {
         Ok(())
     }
 
-    pub(crate) fn serialize_newtype<T: ?Sized>(
-        mut self,
-        value: &T,
-    ) -> Result<SerializationOk<S>, Error>
+    pub(crate) fn serialize_newtype<T>(mut self, value: &T) -> Result<SerializationOk<S>, Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        Self::write_header(&mut self.ctx, self.variant_idx)?;
+        Self::write_header(&mut self.ctx, self.wire_idx)?;
 
         let type_id = if let Some(newtype_variant) = self.variant.as_newtype_variant() {
             *newtype_variant.inner_type()
@@ -90,7 +103,7 @@ impl<S: Borrow<Schema>> SerializeVariantValue<S> {
     }
 
     pub(crate) fn serialize_struct(mut self) -> Result<SerializeStructVariantValue<S>, Error> {
-        Self::write_header(&mut self.ctx, self.variant_idx)?;
+        Self::write_header(&mut self.ctx, self.wire_idx)?;
         let struct_variant = self.variant.clone().try_map::<_, _, Error>(|variant| {
             if let Some(struct_variant) = variant.as_struct_variant() {
                 Ok(struct_variant.fields())
@@ -115,13 +128,9 @@ impl<S: Borrow<Schema>> ser::SerializeStructVariant for SerializeStructVariantVa
     type Ok = SerializationOk<S>;
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
     }
@@ -1,22 +1,27 @@
+use std::borrow::Cow;
+
 use serde::ser::{SerializeSeq, SerializeStruct};
 use serde::{Serialize, Serializer};
 use serde_schema::types::{EnumVariant, StructField, Type};
 
 use error::Error;
-use schema::{Schema, TypeId};
+use internal::types::WireType;
+use schema::{opaque_wire_type, FieldNaming, Schema, TypeId};
 
 use super::{FieldValueSerializer, SerializationCtx};
 
 pub(crate) struct SerializeWireTypes<'a> {
     len_pre: usize,
     wire_types: &'a mut Vec<Vec<u8>>,
+    naming: FieldNaming,
 }
 
 impl<'a> SerializeWireTypes<'a> {
-    pub fn new(wire_types: &'a mut Vec<Vec<u8>>) -> Self {
+    pub fn new(wire_types: &'a mut Vec<Vec<u8>>, naming: FieldNaming) -> Self {
         SerializeWireTypes {
             len_pre: wire_types.len(),
             wire_types,
+            naming,
         }
     }
 
@@ -34,7 +39,20 @@ impl<'a> SerializeWireTypes<'a> {
             type_id: TypeId::WIRE_TYPE,
         };
         let ok = match ty {
-            &Type::Struct(ref struct_type) => ser.serialize_newtype_variant(
+            Type::Struct(struct_type) if opaque_wire_type(struct_type.name()).is_some() => {
+                let (variant_index, variant) = opaque_wire_type(struct_type.name()).unwrap();
+                let go_name = match struct_type.fields() {
+                    [field] => field.name(),
+                    _ => return Err(::serde::ser::Error::custom("invalid opaque type")),
+                };
+                ser.serialize_newtype_variant(
+                    "WireType",
+                    variant_index,
+                    variant,
+                    &SerializeGobEncoderType { id, name: go_name },
+                )?
+            }
+            Type::Struct(struct_type) => ser.serialize_newtype_variant(
                 "WireType",
                 2,
                 "StructT",
@@ -42,9 +60,10 @@ impl<'a> SerializeWireTypes<'a> {
                     id,
                     name: struct_type.name(),
                     fields: struct_type.fields(),
+                    naming: self.naming,
                 },
             )?,
-            &Type::Seq(ref seq_type) => {
+            Type::Seq(seq_type) => {
                 if let Some(len) = seq_type.len() {
                     ser.serialize_newtype_variant(
                         "WireType",
@@ -68,7 +87,7 @@ impl<'a> SerializeWireTypes<'a> {
                     )?
                 }
             }
-            &Type::Map(ref map_type) => ser.serialize_newtype_variant(
+            Type::Map(map_type) => ser.serialize_newtype_variant(
                 "WireType",
                 3,
                 "MapT",
@@ -78,7 +97,7 @@ impl<'a> SerializeWireTypes<'a> {
                     elem: *map_type.value_type(),
                 },
             )?,
-            &Type::Enum(ref enum_type) => ser.serialize_newtype_variant(
+            Type::Enum(enum_type) => ser.serialize_newtype_variant(
                 "WireType",
                 2,
                 "StructT",
@@ -86,6 +105,7 @@ impl<'a> SerializeWireTypes<'a> {
                     id,
                     name: enum_type.name(),
                     variants: enum_type.variants(),
+                    naming: self.naming,
                 },
             )?,
             _ => {
@@ -101,13 +121,13 @@ impl<'a> SerializeWireTypes<'a> {
         mut next_id: TypeId,
         ty: &Type<TypeId>,
     ) -> Result<(), Error> {
-        if let &Type::Enum(ref enum_type) = ty {
+        if let Type::Enum(enum_type) = ty {
             for variant in enum_type.variants() {
                 if let Some(struct_variant) = variant.as_struct_variant() {
                     let mut ctx = SerializationCtx::with_schema(Schema::new());
                     ctx.value.write_int(-next_id.0);
                     let ok = {
-                        let mut ser = FieldValueSerializer {
+                        let ser = FieldValueSerializer {
                             ctx,
                             type_id: TypeId::WIRE_TYPE,
                         };
@@ -119,6 +139,7 @@ impl<'a> SerializeWireTypes<'a> {
                                 id: next_id,
                                 name: struct_variant.name(),
                                 fields: struct_variant.fields(),
+                                naming: self.naming,
                             },
                         )?
                     };
@@ -131,10 +152,23 @@ impl<'a> SerializeWireTypes<'a> {
     }
 }
 
+/// Encodes the message defining a type which has been decoded before.
+pub(crate) fn encode_wire_type(wire_type: &WireType) -> Result<Vec<u8>, Error> {
+    let mut ctx = SerializationCtx::with_schema(Schema::new());
+    ctx.value.write_int(-wire_type.common().id.0);
+    let ser = FieldValueSerializer {
+        ctx,
+        type_id: TypeId::WIRE_TYPE,
+    };
+    let ok = wire_type.serialize(ser)?;
+    Ok(ok.ctx.value.into_inner())
+}
+
 struct SerializeEnumStructType<'a> {
     id: TypeId,
     name: &'a str,
     variants: &'a [EnumVariant<TypeId>],
+    naming: FieldNaming,
 }
 
 impl<'a> Serialize for SerializeEnumStructType<'a> {
@@ -152,6 +186,7 @@ impl<'a> Serialize for SerializeEnumStructType<'a> {
             &SerializeEnumStructFields {
                 id: self.id,
                 variants: self.variants,
+                naming: self.naming,
             },
         )?;
         s.end()
@@ -161,23 +196,28 @@ impl<'a> Serialize for SerializeEnumStructType<'a> {
 struct SerializeEnumStructFields<'a> {
     id: TypeId,
     variants: &'a [EnumVariant<TypeId>],
+    naming: FieldNaming,
 }
 
 impl<'a> Serialize for SerializeEnumStructFields<'a> {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut s = serializer.serialize_seq(Some(self.variants.len()))?;
+        let variants = self
+            .variants
+            .iter()
+            .filter(|variant| !is_skipped_variant(variant));
+        let mut s = serializer.serialize_seq(Some(variants.clone().count()))?;
         let mut next_id = self.id.next();
-        for variant in self.variants {
+        for variant in variants {
             match variant {
-                &EnumVariant::Newtype(ref newtype_variant) => {
+                EnumVariant::Newtype(newtype_variant) => {
                     s.serialize_element(&SerializeStructField {
-                        name: newtype_variant.name(),
+                        name: self.naming.apply(newtype_variant.name()),
                         id: *newtype_variant.inner_type(),
                     })?
                 }
-                &EnumVariant::Struct(ref struct_variant) => {
+                EnumVariant::Struct(struct_variant) => {
                     s.serialize_element(&SerializeStructField {
-                        name: struct_variant.name(),
+                        name: self.naming.apply(struct_variant.name()),
                         id: next_id,
                     })?;
                     next_id = next_id.next();
@@ -191,6 +231,14 @@ impl<'a> Serialize for SerializeEnumStructFields<'a> {
     }
 }
 
+/// Placeholders for variants which are not part of the type, see
+/// `infer_type`.
+pub(crate) fn is_skipped_variant(variant: &EnumVariant<TypeId>) -> bool {
+    variant
+        .as_newtype_variant()
+        .is_some_and(|newtype_variant| *newtype_variant.inner_type() == TypeId::SKIP)
+}
+
 struct SerializeMapType {
     id: TypeId,
     key: TypeId,
@@ -259,6 +307,7 @@ struct SerializeStructType<'a> {
     id: TypeId,
     name: &'a str,
     fields: &'a [StructField<TypeId>],
+    naming: FieldNaming,
 }
 
 impl<'a> Serialize for SerializeStructType<'a> {
@@ -275,6 +324,7 @@ impl<'a> Serialize for SerializeStructType<'a> {
             "Fields",
             &SerializeStructFields {
                 fields: self.fields,
+                naming: self.naming,
             },
         )?;
         s.end()
@@ -283,14 +333,19 @@ impl<'a> Serialize for SerializeStructType<'a> {
 
 struct SerializeStructFields<'a> {
     fields: &'a [StructField<TypeId>],
+    naming: FieldNaming,
 }
 
 impl<'a> Serialize for SerializeStructFields<'a> {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let mut s = serializer.serialize_seq(Some(self.fields.len()))?;
-        for field in self.fields {
+        let fields = self
+            .fields
+            .iter()
+            .filter(|field| *field.field_type() != TypeId::SKIP);
+        let mut s = serializer.serialize_seq(Some(fields.clone().count()))?;
+        for field in fields {
             s.serialize_element(&SerializeStructField {
-                name: field.name(),
+                name: self.naming.apply(field.name()),
                 id: *field.field_type(),
             })?;
         }
@@ -299,7 +354,7 @@ impl<'a> Serialize for SerializeStructFields<'a> {
 }
 
 struct SerializeStructField<'a> {
-    name: &'a str,
+    name: Cow<'a, str>,
     id: TypeId,
 }
 
@@ -312,6 +367,25 @@ impl<'a> Serialize for SerializeStructField<'a> {
     }
 }
 
+struct SerializeGobEncoderType<'a> {
+    id: TypeId,
+    name: &'a str,
+}
+
+impl<'a> Serialize for SerializeGobEncoderType<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("gobEncoderType", 1)?;
+        s.serialize_field(
+            "common",
+            &SerializeCommonType {
+                id: self.id,
+                name: self.name,
+            },
+        )?;
+        s.end()
+    }
+}
+
 struct SerializeCommonType<'a> {
     id: TypeId,
     name: &'a str,
@@ -20,9 +20,12 @@ pub(crate) use self::struct_type::{FieldType, StructType};
 mod map_type;
 pub(crate) use self::map_type::MapType;
 
+mod gob_encoder_type;
+pub(crate) use self::gob_encoder_type::GobEncoderType;
+
 pub use schema::TypeId;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Types {
     map: BTreeMap<TypeId, WireType>,
 }
@@ -37,6 +40,7 @@ pub(crate) fn lookup_builtin(id: TypeId) -> Option<&'static Type<TypeId>> {
         TypeId::STRUCT_TYPE => Some(&self::struct_type::STRUCT_TYPE_DEF),
         TypeId::WIRE_TYPE => Some(&self::wire_type::WIRE_TYPE_DEF),
         TypeId::COMMON_TYPE => Some(&self::common_type::COMMON_TYPE_DEF),
+        TypeId::GOB_ENCODER_TYPE => Some(&self::gob_encoder_type::GOB_ENCODER_TYPE_DEF),
         _ => None,
     }
 }
@@ -51,22 +55,44 @@ pub(crate) fn lookup_builtin2(id: TypeId) -> Option<&'static WireType> {
         TypeId::STRUCT_TYPE => Some(&self::struct_type::STRUCT_TYPE_DEF_2),
         TypeId::WIRE_TYPE => Some(&self::wire_type::WIRE_TYPE_DEF_2),
         TypeId::COMMON_TYPE => Some(&self::common_type::COMMON_TYPE_DEF_2),
+        TypeId::GOB_ENCODER_TYPE => Some(&self::gob_encoder_type::GOB_ENCODER_TYPE_DEF_2),
         _ => None,
     }
 }
 
 impl Types {
-    pub fn new() -> Types {
-        Types {
-            map: BTreeMap::new(),
-        }
-    }
-
     pub(crate) fn insert(&mut self, def: WireType) {
         self.map.insert(def.common().id, def);
     }
 
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &WireType> {
+        self.map.values()
+    }
+
     pub(crate) fn lookup(&self, id: TypeId) -> Option<&WireType> {
         lookup_builtin2(id).or_else(|| self.map.get(&id))
     }
+
+    /// Returns `true` if maps with elements of the given type are Go
+    /// sets, i.e. `map[T]struct{}` or `map[T]bool`.
+    pub(crate) fn is_set_elem(&self, id: TypeId) -> bool {
+        match self.lookup(id) {
+            Some(WireType::Struct(struct_type)) => struct_type.fields.is_empty(),
+            _ => id == TypeId::BOOL,
+        }
+    }
+
+    pub(crate) fn name(&self, id: TypeId) -> Option<&str> {
+        match id {
+            TypeId::BOOL => Some("bool"),
+            TypeId::INT => Some("int"),
+            TypeId::UINT => Some("uint"),
+            TypeId::FLOAT => Some("float"),
+            TypeId::BYTES => Some("bytes"),
+            TypeId::STRING => Some("string"),
+            TypeId::COMPLEX => Some("complex"),
+            TypeId::INTERFACE => Some("interface"),
+            _ => self.lookup(id).map(|wire_type| &*wire_type.common().name),
+        }
+    }
 }
@@ -13,23 +13,23 @@ impl<'a, T> Deref for Bow<'a, T> {
     type Target = T;
     fn deref(&self) -> &T {
         match self {
-            &Bow::Borrowed(ref t) => t,
-            &Bow::Owned(ref t) => t,
+            Bow::Borrowed(t) => t,
+            Bow::Owned(t) => t,
         }
     }
 }
 
 impl<'a, T> Borrow<T> for Bow<'a, T> {
     fn borrow(&self) -> &T {
-        &*self
+        self
     }
 }
 
 impl<'a, T> DerefMut for Bow<'a, T> {
     fn deref_mut(&mut self) -> &mut T {
-        match self {
-            &mut Bow::Borrowed(ref mut t) => t,
-            &mut Bow::Owned(ref mut t) => t,
+        match *self {
+            Bow::Borrowed(ref mut t) => t,
+            Bow::Owned(ref mut t) => t,
         }
     }
 }
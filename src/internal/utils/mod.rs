@@ -6,3 +6,6 @@ pub use self::buffer::Buffer;
 
 mod bufvec;
 pub use self::bufvec::BufVec;
+
+mod names;
+pub use self::names::{eq_ignore_case_and_underscores, to_pascal_case, to_snake_case};
@@ -0,0 +1,74 @@
+/// Converts a Rust-style `snake_case` name into a Go-style exported
+/// `PascalCase` name, e.g. `user_id` becomes `UserId`.
+pub fn to_pascal_case(name: &str) -> String {
+    let mut pascal = String::with_capacity(name.len());
+    for word in name.split('_').filter(|word| !word.is_empty()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            pascal.extend(first.to_uppercase());
+            pascal.push_str(chars.as_str());
+        }
+    }
+    pascal
+}
+
+/// Converts a Go-style `PascalCase` name into a Rust-style `snake_case`
+/// name, keeping initialisms together, e.g. `UserID` becomes `user_id`.
+pub fn to_snake_case(name: &str) -> String {
+    let chars = name.chars().collect::<Vec<_>>();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (idx, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && idx > 0 {
+            let prev = chars[idx - 1];
+            let next_is_lower = chars.get(idx + 1).is_some_and(|c| c.is_lowercase());
+            if prev != '_' && (!prev.is_uppercase() || next_is_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+/// Compares two names ignoring case and underscores, so that `user_id`,
+/// `UserId` and `UserID` are all considered equal.
+pub fn eq_ignore_case_and_underscores(a: &str, b: &str) -> bool {
+    let mut a = a.chars().filter(|&c| c != '_').flat_map(char::to_lowercase);
+    let mut b = b.chars().filter(|&c| c != '_').flat_map(char::to_lowercase);
+    loop {
+        match (a.next(), b.next()) {
+            (Some(x), Some(y)) if x == y => continue,
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eq_ignore_case_and_underscores, to_pascal_case, to_snake_case};
+
+    #[test]
+    fn pascal_case() {
+        assert_eq!(to_pascal_case("x"), "X");
+        assert_eq!(to_pascal_case("user_id"), "UserId");
+        assert_eq!(to_pascal_case("_private_field"), "PrivateField");
+        assert_eq!(to_pascal_case("Already"), "Already");
+    }
+
+    #[test]
+    fn snake_case() {
+        assert_eq!(to_snake_case("X"), "x");
+        assert_eq!(to_snake_case("UserID"), "user_id");
+        assert_eq!(to_snake_case("HTTPServer"), "http_server");
+        assert_eq!(to_snake_case("ServiceMethod"), "service_method");
+        assert_eq!(to_snake_case("already_snake"), "already_snake");
+    }
+
+    #[test]
+    fn compare_ignoring_case_and_underscores() {
+        assert!(eq_ignore_case_and_underscores("user_id", "UserID"));
+        assert!(eq_ignore_case_and_underscores("user_id", "UserId"));
+        assert!(!eq_ignore_case_and_underscores("user", "UserID"));
+    }
+}
@@ -8,26 +8,117 @@ extern crate quickcheck;
 
 extern crate byteorder;
 extern crate bytes;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "indexmap")]
+extern crate indexmap;
 extern crate iovec;
 #[macro_use]
 extern crate lazy_static;
+#[cfg(feature = "num-bigint")]
+extern crate num_bigint;
+#[cfg(feature = "num-complex")]
+extern crate num_complex;
+#[cfg(feature = "num-bigint")]
+extern crate num_rational;
 extern crate owning_ref;
 extern crate safemem;
 #[macro_use]
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+extern crate serde_bytes;
 extern crate serde_schema;
+#[cfg(feature = "uuid")]
+extern crate uuid;
 
+mod big;
+mod complex;
+mod gob_schema;
 mod internal;
 mod schema;
+mod set;
+mod sql;
 
+pub mod compat;
 pub mod error;
+pub mod go;
 
 pub mod de;
 pub mod ser;
 
+pub use big::{BigFloat, BigInt, BigRat};
+pub use complex::{Complex128, Complex64};
 pub use error::Error;
+pub use gob_schema::GobSchema;
+pub use set::{BoolMapSet, StructMapSet};
+pub use sql::{
+    NullBool, NullByte, NullFloat64, NullInt16, NullInt32, NullInt64, NullString, NullTime,
+};
 
 pub use de::{Deserializer, StreamDeserializer};
 pub use ser::StreamSerializer;
+
+// used by the code generated by `#[derive(GobSchema)]`
+#[doc(hidden)]
+pub mod __private {
+    use std::marker::PhantomData;
+
+    use serde_schema::{Schema, SchemaSerialize};
+
+    use gob_schema::GobSchema;
+
+    pub use schema::{
+        INTERFACE_NAME, KEYED_NAME, OPAQUE_GOB_NAME, OPAQUE_NAME, OPAQUE_TEXT_NAME, SKIP_NAME,
+    };
+    pub use serde::Serialize;
+    pub extern crate serde;
+    pub extern crate serde_schema;
+
+    /// Registers a field type through `GobSchema` if it is implemented,
+    /// and through `SchemaSerialize` otherwise, when called as
+    /// `(&&TypeTag::<T>::new()).register(schema)` with both
+    /// `RegisterGobSchema` and `RegisterSchemaSerialize` in scope.
+    pub struct TypeTag<T: ?Sized>(PhantomData<T>);
+
+    impl<T: ?Sized> TypeTag<T> {
+        pub fn new() -> Self {
+            TypeTag(PhantomData)
+        }
+    }
+
+    impl<T: ?Sized> Default for TypeTag<T> {
+        fn default() -> Self {
+            TypeTag::new()
+        }
+    }
+
+    /// Appends a field to the fields of a struct which is built at
+    /// registration time. A field which is already present is kept, as
+    /// serde serializes it under a single key.
+    pub fn push_field<T>(fields: &mut Vec<(&'static str, T)>, name: &'static str, id: T) {
+        if fields.iter().all(|&(field_name, _)| field_name != name) {
+            fields.push((name, id));
+        }
+    }
+
+    pub trait RegisterGobSchema {
+        fn register<S: Schema>(&self, schema: &mut S) -> Result<S::TypeId, S::Error>;
+    }
+
+    impl<T: ?Sized + GobSchema> RegisterGobSchema for &TypeTag<T> {
+        fn register<S: Schema>(&self, schema: &mut S) -> Result<S::TypeId, S::Error> {
+            T::register(schema)
+        }
+    }
+
+    pub trait RegisterSchemaSerialize {
+        fn register<S: Schema>(&self, schema: &mut S) -> Result<S::TypeId, S::Error>;
+    }
+
+    impl<T: ?Sized + SchemaSerialize> RegisterSchemaSerialize for TypeTag<T> {
+        fn register<S: Schema>(&self, schema: &mut S) -> Result<S::TypeId, S::Error> {
+            T::schema_register(schema)
+        }
+    }
+}
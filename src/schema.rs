@@ -1,16 +1,25 @@
 //! Schema management
 
-use std::collections::BTreeMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::Cursor;
 use std::ops::Deref;
 use std::sync::Arc;
 
 use owning_ref::{CloneStableAddress, StableAddress};
 use serde::{Deserialize, Deserializer};
 use serde::{Serialize, Serializer};
-use serde_schema::types::Type;
+use serde_schema::types::{EnumVariant, Type};
 
+use complex::COMPLEX_NAME;
+use de::{Options, TypeDefinitions};
 use error::Error;
-use internal::ser::SerializeWireTypes;
+use internal::de::FieldValueDeserializer;
+use internal::gob::Message;
+use internal::ser::{encode_wire_type, infer_type, SerializeWireTypes};
+use internal::types::{Types, WireType};
+use internal::utils::to_pascal_case;
 use ser::{Output, OutputPart};
 
 #[derive(Clone)]
@@ -35,23 +44,368 @@ impl Deref for SchemaType {
 
 const CUSTOM_TYPE_ID_OFFSET: i64 = 65;
 
+/// Names of the marker types registered by `#[derive(GobSchema)]` for
+/// fields that have no serde data model equivalent.
+pub const SKIP_NAME: &str = "__gob_skip";
+pub const INTERFACE_NAME: &str = "__gob_interface";
+// a struct with a single bytes field, named after the Go type, which
+// implements `encoding.BinaryMarshaler`
+pub const OPAQUE_NAME: &str = "__gob_opaque";
+// as `OPAQUE_NAME`, for Go types implementing `GobEncoder`
+pub const OPAQUE_GOB_NAME: &str = "__gob_opaque_gob";
+// as `OPAQUE_NAME`, for Go types implementing `encoding.TextMarshaler`
+pub const OPAQUE_TEXT_NAME: &str = "__gob_opaque_text";
+// a newtype wrapping a struct whose fields are serialized by name, in any
+// subset, as happens for flattened fields and tagged enums
+pub const KEYED_NAME: &str = "__gob_keyed";
+// a newtype wrapping a struct with the layout of Go's `sql.Null[T]`, which
+// values are serialized into as an `Option<T>`
+pub const NULLABLE_NAME: &str = "__gob_nullable";
+
+/// Returns the `WireType` variant which opaque types registered under the
+/// marker name are described by.
+pub(crate) fn opaque_wire_type(name: &str) -> Option<(u32, &'static str)> {
+    match name {
+        OPAQUE_GOB_NAME => Some((4, "GobEncoderT")),
+        OPAQUE_NAME => Some((5, "BinaryMarshalerT")),
+        OPAQUE_TEXT_NAME => Some((6, "TextMarshalerT")),
+        _ => None,
+    }
+}
+
+// field names of the Go structs which heterogeneous tuples are sent as
+const TUPLE_FIELD_NAMES: [&str; 16] = [
+    "F0", "F1", "F2", "F3", "F4", "F5", "F6", "F7", "F8", "F9", "F10", "F11", "F12", "F13", "F14",
+    "F15",
+];
+
+/// Controls how Rust field names are written to the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FieldNaming {
+    /// Field names are written as declared (after applying any serde
+    /// renames).
+    #[default]
+    Verbatim,
+    /// Field names are converted from `snake_case` to `PascalCase`, so
+    /// that they match the exported field names of Go structs, e.g.
+    /// `user_id` is written as `UserId`.
+    PascalCase,
+}
+
+/// Controls how sequences of `u8` are sent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ByteSequences {
+    /// `Vec<u8>`, `[u8; N]` and other sequences of `u8` are sent as Go
+    /// `[]byte`, like `serde_bytes` values. Go cannot decode them into
+    /// `[N]byte` arrays.
+    #[default]
+    Bytes,
+    /// Sequences of `u8` are sent as slices or arrays of `uint`, which
+    /// Go can only decode into `[]uint8` or `[N]uint8` if they are
+    /// arrays, and not into `[]byte`.
+    Uints,
+}
+
+/// Controls how `None` is sent where Go has no way to express it, as an
+/// element of a slice or array, or as a key or value of a map.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NoneElements {
+    /// Serializing such a `None` fails. `Some` values are sent as the
+    /// value they hold.
+    #[default]
+    Reject,
+    /// `None` is sent as the zero value of the inner type, which is
+    /// decoded as `Some` of that value.
+    ZeroValue,
+    /// Every `Option<T>`, including those of struct fields, is sent as a
+    /// struct with the layout of Go's `sql.Null[T]`,
+    /// `struct { V T; Valid bool }`, where `None` is sent with `Valid`
+    /// set to false. Such structs are decoded back into `Option<T>`.
+    Nullable,
+}
+
+/// Controls whether `i128` and `u128` can be sent, which have no gob
+/// encoding of their own.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WideIntegers {
+    /// Serializing an `i128` or `u128` fails.
+    #[default]
+    Reject,
+    /// `i128` and `u128` are sent as Go's `big.Int`, and can be decoded
+    /// from it as long as the value is in range.
+    BigInt,
+}
+
+/// Controls whether registered types are checked for parts which Go
+/// silently ignores or cannot decode.
+///
+/// The checks are made on the type definitions as they are sent, and
+/// find struct fields which Go never assigns because their names are not
+/// exported, structs of which Go can assign no field at all, and maps
+/// whose key types are not comparable in Go. When checking, messages
+/// larger than Go's decoder accepts also fail to serialize.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GoCompatibility {
+    /// Types are not checked.
+    #[default]
+    Unchecked,
+    /// Issues are recorded and registration succeeds, see
+    /// `Schema::go_compatibility_issues`.
+    Report,
+    /// Registering a type with an issue fails, and the type is not added
+    /// to the schema.
+    Reject,
+}
+
+/// A part of a registered type which Go cannot decode as intended, see
+/// `GoCompatibility`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GoIssue {
+    /// A struct field whose name does not start with an uppercase letter.
+    /// Go only decodes exported fields, so it never assigns this one.
+    UnexportedField {
+        type_name: String,
+        field_name: String,
+    },
+    /// A struct which has fields, none of which are exported, which Go
+    /// refuses as having no exported fields.
+    NoExportedFields { type_name: String },
+    /// A map whose key type is not comparable in Go, such as a slice or a
+    /// struct holding one.
+    IncomparableMapKey { key_type: String },
+}
+
+impl fmt::Display for GoIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoIssue::UnexportedField {
+                type_name,
+                field_name,
+            } => write!(
+                f,
+                "field {:?} of {:?} is not exported in Go, which never assigns it",
+                field_name, type_name
+            ),
+            GoIssue::NoExportedFields { type_name } => {
+                write!(f, "struct {:?} has no exported fields", type_name)
+            }
+            GoIssue::IncomparableMapKey { key_type } => {
+                write!(f, "map key type {} is not comparable in Go", key_type)
+            }
+        }
+    }
+}
+
+// the largest message Go's decoder accepts on 32-bit platforms, 64-bit
+// platforms accept up to 8 GiB
+pub(crate) const GO_MAX_MESSAGE_LEN: usize = 1 << 30;
+
+impl FieldNaming {
+    pub(crate) fn apply(self, name: &str) -> Cow<'_, str> {
+        match self {
+            FieldNaming::Verbatim => Cow::Borrowed(name),
+            FieldNaming::PascalCase => Cow::Owned(to_pascal_case(name)),
+        }
+    }
+}
+
+/// The wire type messages sent for a registered type, and the ids of
+/// the types they refer to.
+#[derive(Clone)]
+struct WireTypes {
+    messages: Vec<Vec<u8>>,
+    refs: Vec<TypeId>,
+    // already present on the stream being appended to
+    existing: bool,
+}
+
+/// A registry of the types which can be serialized, along with the
+/// gob type ids assigned to them.
+///
+/// A schema can be shared between many `StreamSerializer`s by putting
+/// it in an `Arc` once all types have been registered, see
+/// `StreamSerializer::set_shared_schema`.
+#[derive(Clone)]
 pub struct Schema {
-    pending_wire_types: Vec<Vec<u8>>,
+    existing_types: Types,
+    wire_types: BTreeMap<TypeId, WireTypes>,
+    field_naming: FieldNaming,
+    byte_sequences: ByteSequences,
+    none_elements: NoneElements,
+    wide_integers: WideIntegers,
+    go_compatibility: GoCompatibility,
+    // definitions of the types registered while checking
+    go_types: Types,
+    go_issues: Vec<GoIssue>,
+    keyed_structs: BTreeSet<TypeId>,
+    nullable_structs: BTreeSet<TypeId>,
     next_type_id: TypeId,
     schema_types: Vec<(TypeId, Arc<Type<TypeId>>)>,
     schema_types_reverse: BTreeMap<Arc<Type<TypeId>>, TypeId>,
 }
 
+impl Default for Schema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Schema {
     pub fn new() -> Schema {
         Schema {
-            pending_wire_types: Vec::new(),
+            existing_types: Types::default(),
+            wire_types: BTreeMap::new(),
+            field_naming: FieldNaming::default(),
+            byte_sequences: ByteSequences::default(),
+            none_elements: NoneElements::default(),
+            wide_integers: WideIntegers::default(),
+            go_compatibility: GoCompatibility::default(),
+            go_types: Types::default(),
+            go_issues: Vec::new(),
+            keyed_structs: BTreeSet::new(),
+            nullable_structs: BTreeSet::new(),
             next_type_id: TypeId(CUSTOM_TYPE_ID_OFFSET),
             schema_types: Vec::new(),
             schema_types_reverse: BTreeMap::new(),
         }
     }
 
+    /// Create a schema for appending to a stream which already contains
+    /// the given type definitions.
+    ///
+    /// Types registered with the schema are assigned the id of an
+    /// identical definition on the stream, if there is one, and are not
+    /// sent again. Other types are assigned ids that do not clash with
+    /// those on the stream. Registering a type fails if the stream
+    /// defines a different type under the same name.
+    ///
+    /// Since definitions are assumed to have been sent already, the
+    /// schema should only be used for a single stream.
+    pub fn from_type_definitions(defs: &TypeDefinitions) -> Schema {
+        let mut schema = Schema::new();
+        schema.existing_types = defs.types().clone();
+        if let Some(max_id) = schema.existing_types.iter().map(|t| t.common().id).max() {
+            if max_id >= schema.next_type_id {
+                schema.next_type_id = max_id.next();
+            }
+        }
+        schema
+    }
+
+    /// Set how field names are written to the wire.
+    ///
+    /// This only affects types which are registered afterwards.
+    pub fn set_field_naming(&mut self, field_naming: FieldNaming) {
+        self.field_naming = field_naming;
+    }
+
+    /// Set how sequences of `u8` are sent.
+    ///
+    /// This only affects types which are registered afterwards.
+    pub fn set_byte_sequences(&mut self, byte_sequences: ByteSequences) {
+        self.byte_sequences = byte_sequences;
+    }
+
+    /// Set how `None` elements of sequences and values of maps are sent.
+    ///
+    /// `NoneElements::Nullable` only affects types which are registered
+    /// afterwards.
+    pub fn set_none_elements(&mut self, none_elements: NoneElements) {
+        self.none_elements = none_elements;
+    }
+
+    pub(crate) fn none_elements(&self) -> NoneElements {
+        self.none_elements
+    }
+
+    /// Set whether `i128` and `u128` values can be sent.
+    pub fn set_wide_integers(&mut self, wide_integers: WideIntegers) {
+        self.wide_integers = wide_integers;
+    }
+
+    pub(crate) fn wide_integers(&self) -> WideIntegers {
+        self.wide_integers
+    }
+
+    /// Set whether types are checked for parts which Go cannot decode.
+    ///
+    /// This only affects types which are registered afterwards. Map key
+    /// types registered before are assumed to be comparable.
+    pub fn set_go_compatibility(&mut self, go_compatibility: GoCompatibility) {
+        self.go_compatibility = go_compatibility;
+    }
+
+    pub(crate) fn go_compatibility(&self) -> GoCompatibility {
+        self.go_compatibility
+    }
+
+    /// Returns the issues found in registered types with
+    /// `GoCompatibility::Report`.
+    pub fn go_compatibility_issues(&self) -> &[GoIssue] {
+        &self.go_issues
+    }
+
+    /// Register the type of a value which does not implement
+    /// `SchemaSerialize`, by inspecting how it serializes.
+    ///
+    /// Only the parts of the type which occur in `value` can be
+    /// discovered. Struct fields which are `None` or skipped, and enum
+    /// variants other than the ones used, are not part of the registered
+    /// type. Registration fails if the type of some part of the value
+    /// cannot be determined at all, such as the element type of an empty
+    /// sequence.
+    pub fn register_inferred<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<TypeId, Error> {
+        infer_type(value, self)
+    }
+
+    /// Returns `true` if values of the type are sent as opaque byte
+    /// slices, see `OPAQUE_NAME`.
+    pub(crate) fn is_opaque(&self, id: TypeId) -> bool {
+        match self.lookup(id) {
+            Some(ty) => match *ty {
+                Type::Struct(ref struct_type) => opaque_wire_type(struct_type.name()).is_some(),
+                _ => false,
+            },
+            None => false,
+        }
+    }
+
+    /// Returns `true` if values of the type are sent as Go structs.
+    pub(crate) fn is_struct(&self, id: TypeId) -> bool {
+        match self.lookup(id) {
+            Some(ty) => matches!(*ty, Type::Struct(_)),
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the struct type is the `sql.Null[T]` layout that
+    /// an `Option<T>` is sent as, see `NoneElements::Nullable` and
+    /// `NULLABLE_NAME`.
+    pub(crate) fn is_nullable(&self, id: TypeId) -> bool {
+        self.nullable_structs.contains(&id)
+    }
+
+    fn has_nullable_layout(&self, id: TypeId) -> bool {
+        match self.lookup(id) {
+            Some(ty) => match *ty {
+                Type::Struct(ref struct_type) => match *struct_type.fields() {
+                    [_, ref valid] => {
+                        valid.name() == "Valid" && *valid.field_type() == TypeId::BOOL
+                    }
+                    _ => false,
+                },
+                _ => false,
+            },
+            None => false,
+        }
+    }
+
+    /// Returns `true` if values of the struct type may serialize their
+    /// fields by name, see `KEYED_NAME`.
+    pub(crate) fn is_keyed(&self, id: TypeId) -> bool {
+        self.keyed_structs.contains(&id)
+    }
+
     #[inline]
     pub(crate) fn lookup(&self, id: TypeId) -> Option<SchemaType> {
         if id.0 < CUSTOM_TYPE_ID_OFFSET {
@@ -66,12 +420,434 @@ impl Schema {
         }
     }
 
-    pub(crate) fn write_pending<O: Output>(&mut self, mut o: O) -> Result<(), Error> {
-        for wire_type_buffer in self.pending_wire_types.drain(..) {
-            o.serialize_part(OutputPart::new(wire_type_buffer))?;
+    /// Returns the id of a type which does not need to be registered,
+    /// or which has been registered before.
+    fn known_type_id(&self, ty: &Type<TypeId>) -> Option<TypeId> {
+        match ty {
+            Type::Option(option_type) if self.none_elements == NoneElements::Nullable => {
+                self.known_type_id(&nullable_type(*option_type.inner_type()))
+            }
+            Type::Option(option_type) => Some(*option_type.inner_type()),
+            Type::NewtypeStruct(newtype_struct_type) => Some(*newtype_struct_type.inner_type()),
+            Type::TupleStruct(tuple_struct_type) if tuple_struct_type.name() == COMPLEX_NAME => {
+                Some(TypeId::COMPLEX)
+            }
+            Type::Seq(seq_type)
+                if *seq_type.element_type() == TypeId::BYTE
+                    && self.byte_sequences == ByteSequences::Bytes =>
+            {
+                Some(TypeId::BYTES)
+            }
+            Type::UnitStruct(unit_struct_type) if unit_struct_type.name() == SKIP_NAME => {
+                Some(TypeId::SKIP)
+            }
+            Type::UnitStruct(unit_struct_type) if unit_struct_type.name() == INTERFACE_NAME => {
+                Some(TypeId::INTERFACE)
+            }
+            _ => self.schema_types_reverse.get(ty).cloned(),
+        }
+    }
+
+    /// Returns the definitions of all types registered with the schema,
+    /// as they are sent on the wire.
+    pub fn type_definitions(&self) -> Result<TypeDefinitions, Error> {
+        let mut defs = TypeDefinitions::new();
+        for wire_types in self.wire_types.values() {
+            for message in &wire_types.messages {
+                let mut section = Message::new(Vec::new());
+                section.write_uint(message.len() as u64);
+                section.get_mut().extend_from_slice(message);
+                defs.insert(decode_wire_type(message)?, section.get_ref());
+            }
+        }
+        Ok(defs)
+    }
+
+    /// Finds a definition on the stream being appended to which is
+    /// identical to the one that would be sent for `ty`.
+    ///
+    /// The definitions of enum variants are sent along with the enum, but
+    /// may have been defined anywhere on the stream, so they are matched
+    /// through the references to them rather than by their ids.
+    fn existing_type_id(
+        &self,
+        ty: &Type<TypeId>,
+    ) -> Result<Option<(TypeId, WireTypes)>, Error> {
+        // the definitions are given ids no type on the stream has, so that
+        // references to those sent along can be told apart
+        let mut messages = Vec::new();
+        SerializeWireTypes::new(&mut messages, self.field_naming)
+            .serialize_wire_types(self.next_type_id, ty)?;
+        let mut nested = Types::default();
+        for message in &messages[1..] {
+            nested.insert(decode_wire_type(message)?);
+        }
+        let wire_type = decode_wire_type(&messages[0])?;
+
+        let mut conflict = false;
+        for existing in self.existing_types.iter() {
+            if existing.common().name != wire_type.common().name {
+                continue;
+            }
+            let mut ids = BTreeMap::new();
+            if self.is_existing_definition(&wire_type, existing, &nested, &mut ids) {
+                let mut messages = vec![encode_wire_type(existing)?];
+                for nested in ids
+                    .values()
+                    .filter_map(|&id| self.existing_types.lookup(id))
+                {
+                    messages.push(encode_wire_type(nested)?);
+                }
+                let wire_types = WireTypes {
+                    messages,
+                    refs: type_refs(ty),
+                    existing: true,
+                };
+                return Ok(Some((existing.common().id, wire_types)));
+            }
+            conflict = true;
+        }
+        if conflict && !wire_type.common().name.is_empty() {
+            return Err(::serde::ser::Error::custom(format!(
+                "type {:?} conflicts with its definition on the stream",
+                wire_type.common().name
+            )));
+        }
+        Ok(None)
+    }
+
+    /// Compares the definition of a type being registered to one on the
+    /// stream. References to the definitions in `nested`, which are sent
+    /// along with it, are matched to the types on the stream they refer
+    /// to, which are recorded in `ids`.
+    fn is_existing_definition(
+        &self,
+        wire_type: &WireType,
+        existing: &WireType,
+        nested: &Types,
+        ids: &mut BTreeMap<TypeId, TypeId>,
+    ) -> bool {
+        if wire_type.common().name != existing.common().name {
+            return false;
+        }
+        let refs = match (wire_type, existing) {
+            (WireType::Array(array), WireType::Array(existing)) if array.len == existing.len => {
+                vec![(array.elem, existing.elem)]
+            }
+            (WireType::Slice(slice), WireType::Slice(existing)) => {
+                vec![(slice.elem, existing.elem)]
+            }
+            (WireType::Map(map), WireType::Map(existing)) => {
+                vec![(map.key, existing.key), (map.elem, existing.elem)]
+            }
+            (WireType::Struct(struct_type), WireType::Struct(existing)) => {
+                let fields = struct_type.fields.iter().zip(existing.fields.iter());
+                if struct_type.fields.len() != existing.fields.len()
+                    || fields
+                        .clone()
+                        .any(|(field, existing)| field.name != existing.name)
+                {
+                    return false;
+                }
+                fields
+                    .map(|(field, existing)| (field.id, existing.id))
+                    .collect()
+            }
+            (WireType::GobEncoder(_), WireType::GobEncoder(_))
+            | (WireType::BinaryMarshaler(_), WireType::BinaryMarshaler(_))
+            | (WireType::TextMarshaler(_), WireType::TextMarshaler(_)) => vec![],
+            _ => return false,
+        };
+        refs.into_iter().all(|(id, existing_id)| {
+            let wire_type = match nested.lookup(id) {
+                Some(wire_type) => wire_type,
+                None => return id == existing_id,
+            };
+            if let Some(&mapped_id) = ids.get(&id) {
+                return mapped_id == existing_id;
+            }
+            match self.existing_types.lookup(existing_id) {
+                Some(existing) => {
+                    ids.insert(id, existing_id);
+                    self.is_existing_definition(wire_type, existing, nested, ids)
+                }
+                None => false,
+            }
+        })
+    }
+
+    /// Checks the wire type messages of a type being registered, see
+    /// `GoCompatibility`.
+    fn check_go_compatibility(&mut self, messages: &[Vec<u8>]) -> Result<(), Error> {
+        if self.go_compatibility == GoCompatibility::Unchecked {
+            return Ok(());
+        }
+        // the definitions of a rejected type are left behind, but are
+        // replaced when their ids are assigned again
+        let mut ids = Vec::new();
+        for message in messages {
+            let wire_type = decode_wire_type(message)?;
+            ids.push(wire_type.common().id);
+            self.go_types.insert(wire_type);
+        }
+        let mut issues = Vec::new();
+        for id in ids {
+            go_issues(&self.go_types, id, &mut issues);
+        }
+        if self.go_compatibility == GoCompatibility::Reject && !issues.is_empty() {
+            let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+            return Err(::serde::ser::Error::custom(format!(
+                "type is not compatible with Go: {}",
+                issues.join("; ")
+            )));
         }
+        self.go_issues.extend(issues);
         Ok(())
     }
+
+    /// Returns the definition of a registered type as it is sent.
+    pub(crate) fn wire_type(&self, id: TypeId) -> Result<WireType, Error> {
+        if let Some((_, wire_types)) = self.wire_types.range(..=id).next_back() {
+            for message in &wire_types.messages {
+                let wire_type = decode_wire_type(message)?;
+                if wire_type.common().id == id {
+                    return Ok(wire_type);
+                }
+            }
+        }
+        Err(::serde::ser::Error::custom(format!(
+            "type {} is not registered",
+            id.0
+        )))
+    }
+
+    /// Write the definitions of the type `id` and all types it refers to,
+    /// skipping those in `written`, which is updated accordingly.
+    pub(crate) fn write_wire_types<O: Output>(
+        &self,
+        id: TypeId,
+        written: &mut BTreeSet<TypeId>,
+        mut o: O,
+    ) -> Result<(), Error> {
+        let mut required = BTreeSet::new();
+        self.collect_wire_types(id, written, &mut required);
+        for id in required {
+            for message in &self.wire_types[&id].messages {
+                o.serialize_part(OutputPart::new(message.clone()))?;
+            }
+            written.insert(id);
+        }
+        Ok(())
+    }
+
+    fn collect_wire_types(
+        &self,
+        id: TypeId,
+        written: &BTreeSet<TypeId>,
+        required: &mut BTreeSet<TypeId>,
+    ) {
+        if written.contains(&id) || required.contains(&id) {
+            return;
+        }
+        if let Some(wire_types) = self.wire_types.get(&id) {
+            if wire_types.existing {
+                return;
+            }
+            required.insert(id);
+            for &ref_id in &wire_types.refs {
+                self.collect_wire_types(ref_id, written, required);
+            }
+        }
+    }
+}
+
+fn decode_wire_type(message: &[u8]) -> Result<WireType, Error> {
+    let mut msg = Message::new(Cursor::new(message));
+    msg.read_int()?;
+    let types = Types::default();
+    let de = FieldValueDeserializer::new(TypeId::WIRE_TYPE, &types, Options::default(), &mut msg);
+    WireType::deserialize(de)
+}
+
+/// Adds the issues Go has with the definition of type `id` to `issues`.
+fn go_issues(types: &Types, id: TypeId, issues: &mut Vec<GoIssue>) {
+    match types.lookup(id) {
+        Some(WireType::Struct(struct_type)) => {
+            let type_name = &struct_type.common.name;
+            let mut exported = false;
+            for field in struct_type.fields.iter() {
+                if field.name.chars().next().is_some_and(char::is_uppercase) {
+                    exported = true;
+                } else {
+                    issues.push(GoIssue::UnexportedField {
+                        type_name: type_name.to_string(),
+                        field_name: field.name.to_string(),
+                    });
+                }
+            }
+            if !exported && !struct_type.fields.is_empty() {
+                issues.push(GoIssue::NoExportedFields {
+                    type_name: type_name.to_string(),
+                });
+            }
+        }
+        Some(WireType::Map(map_type))
+            if !is_go_comparable(types, map_type.key, &mut BTreeSet::new()) =>
+        {
+            issues.push(GoIssue::IncomparableMapKey {
+                key_type: go_type_name(types, map_type.key),
+            });
+        }
+        _ => {}
+    }
+}
+
+/// Returns `true` if Go can use values of type `id` as map keys. Types
+/// which are not known are assumed to be comparable.
+fn is_go_comparable(types: &Types, id: TypeId, visited: &mut BTreeSet<TypeId>) -> bool {
+    if id == TypeId::BYTES {
+        return false;
+    }
+    // a type refers to itself through pointers, which are comparable
+    if !visited.insert(id) {
+        return true;
+    }
+    match types.lookup(id) {
+        Some(WireType::Slice(_)) | Some(WireType::Map(_)) => false,
+        Some(WireType::Array(array_type)) => is_go_comparable(types, array_type.elem, visited),
+        Some(WireType::Struct(struct_type)) => struct_type
+            .fields
+            .iter()
+            .all(|field| is_go_comparable(types, field.id, visited)),
+        _ => true,
+    }
+}
+
+/// Describes type `id` the way Go writes it.
+fn go_type_name(types: &Types, id: TypeId) -> String {
+    match id {
+        TypeId::FLOAT => return "float64".to_owned(),
+        TypeId::BYTES => return "[]byte".to_owned(),
+        TypeId::COMPLEX => return "complex128".to_owned(),
+        TypeId::INTERFACE => return "interface{}".to_owned(),
+        _ => {}
+    }
+    match types.lookup(id) {
+        Some(wire_type) if !wire_type.common().name.is_empty() => {
+            wire_type.common().name.to_string()
+        }
+        Some(WireType::Slice(slice_type)) => format!("[]{}", go_type_name(types, slice_type.elem)),
+        Some(WireType::Array(array_type)) => format!(
+            "[{}]{}",
+            array_type.len,
+            go_type_name(types, array_type.elem)
+        ),
+        Some(WireType::Map(map_type)) => format!(
+            "map[{}]{}",
+            go_type_name(types, map_type.key),
+            go_type_name(types, map_type.elem)
+        ),
+        Some(_) => "struct".to_owned(),
+        None => types.name(id).unwrap_or("unknown").to_owned(),
+    }
+}
+
+/// Looks up types in a schema which must not be modified.
+pub(crate) struct RegisteredTypes<'a>(pub &'a Schema);
+
+impl<'a> ::serde_schema::Schema for RegisteredTypes<'a> {
+    type TypeId = TypeId;
+    type Error = Error;
+
+    fn register_type(&mut self, ty: Type<TypeId>) -> Result<TypeId, Error> {
+        self.0.known_type_id(&ty).ok_or_else(|| {
+            ::serde::ser::Error::custom(format!(
+                "type {:?} is not registered in the shared schema",
+                type_name(&ty)
+            ))
+        })
+    }
+}
+
+fn type_name(ty: &Type<TypeId>) -> &str {
+    match ty {
+        Type::UnitStruct(unit_struct_type) => unit_struct_type.name(),
+        Type::NewtypeStruct(newtype_struct_type) => newtype_struct_type.name(),
+        Type::TupleStruct(tuple_struct_type) => tuple_struct_type.name(),
+        Type::Struct(struct_type) => struct_type.name(),
+        Type::Enum(enum_type) => enum_type.name(),
+        _ => "",
+    }
+}
+
+/// Tuples whose elements all have the same type are sent as Go arrays,
+/// other tuples as Go structs with the fields `F0`, `F1`, ... .
+fn tuple_as_go_type(element_types: &[TypeId]) -> Result<Type<TypeId>, Error> {
+    match element_types.split_first() {
+        Some((first, rest)) if rest.iter().all(|id| id.on_wire() == first.on_wire()) => {
+            // a tuple of `u8` and other unsigned integers is not a byte sequence
+            let elem = if rest.iter().all(|id| id == first) {
+                *first
+            } else {
+                first.on_wire()
+            };
+            Ok(Type::build().seq_type(Some(element_types.len()), elem))
+        }
+        Some(_) if element_types.len() <= TUPLE_FIELD_NAMES.len() => {
+            let mut builder = Type::build().struct_type("", element_types.len());
+            for (name, id) in TUPLE_FIELD_NAMES.iter().zip(element_types) {
+                builder = builder.field(name, *id);
+            }
+            Ok(builder.end())
+        }
+        Some(_) => Err(::serde::ser::Error::custom(format!(
+            "tuples of more than {} elements are not supported",
+            TUPLE_FIELD_NAMES.len()
+        ))),
+        None => Err(::serde::ser::Error::custom(
+            "the unit type has no gob equivalent",
+        )),
+    }
+}
+
+/// The struct an `Option` is sent as, see `NoneElements::Nullable`.
+fn nullable_type(inner: TypeId) -> Type<TypeId> {
+    Type::build()
+        .struct_type("Null", 2)
+        .field("V", inner)
+        .field("Valid", TypeId::BOOL)
+        .end()
+}
+
+fn type_refs(ty: &Type<TypeId>) -> Vec<TypeId> {
+    match ty {
+        Type::Option(option_type) => vec![*option_type.inner_type()],
+        Type::UnitStruct(_) => vec![],
+        Type::NewtypeStruct(newtype_struct_type) => vec![*newtype_struct_type.inner_type()],
+        Type::Seq(seq_type) => vec![*seq_type.element_type()],
+        Type::Tuple(tuple_type) => tuple_type.element_types().to_vec(),
+        Type::TupleStruct(tuple_struct_type) => tuple_struct_type.element_types().to_vec(),
+        Type::Map(map_type) => vec![*map_type.key_type(), *map_type.value_type()],
+        Type::Struct(struct_type) => struct_type
+            .fields()
+            .iter()
+            .map(|field| *field.field_type())
+            .collect(),
+        Type::Enum(enum_type) => enum_type
+            .variants()
+            .iter()
+            .flat_map(|variant| match variant {
+                EnumVariant::Unit(_) => vec![],
+                EnumVariant::Newtype(newtype_variant) => vec![*newtype_variant.inner_type()],
+                EnumVariant::Tuple(tuple_variant) => tuple_variant.element_types().to_vec(),
+                EnumVariant::Struct(struct_variant) => struct_variant
+                    .fields()
+                    .iter()
+                    .map(|field| *field.field_type())
+                    .collect(),
+            })
+            .collect(),
+    }
 }
 
 impl ::serde_schema::Schema for Schema {
@@ -80,22 +856,83 @@ impl ::serde_schema::Schema for Schema {
 
     fn register_type(&mut self, ty: Type<TypeId>) -> Result<TypeId, Error> {
         let next_id = self.next_type_id;
+        let ty = match ty {
+            Type::Tuple(tuple_type) => tuple_as_go_type(tuple_type.element_types())?,
+            Type::NewtypeStruct(ref newtype_struct_type)
+                if newtype_struct_type.name() == KEYED_NAME =>
+            {
+                let id = *newtype_struct_type.inner_type();
+                if !self.is_struct(id) {
+                    return Err(::serde::ser::Error::custom(
+                        "only struct types can be keyed",
+                    ));
+                }
+                self.keyed_structs.insert(id);
+                return Ok(id);
+            }
+            Type::NewtypeStruct(ref newtype_struct_type)
+                if newtype_struct_type.name() == NULLABLE_NAME =>
+            {
+                let id = *newtype_struct_type.inner_type();
+                if !self.has_nullable_layout(id) {
+                    return Err(::serde::ser::Error::custom(
+                        "only structs of a value field and a `Valid bool` field can be nullable",
+                    ));
+                }
+                self.nullable_structs.insert(id);
+                return Ok(id);
+            }
+            Type::Option(ref option_type) if self.none_elements == NoneElements::Nullable => {
+                let id = self.register_type(nullable_type(*option_type.inner_type()))?;
+                self.nullable_structs.insert(id);
+                return Ok(id);
+            }
+            ty => ty,
+        };
 
-        if let Type::Option(ref option_type) = ty {
-            return Ok(*option_type.inner_type());
+        if let Some(id) = self.known_type_id(&ty) {
+            return Ok(id);
+        }
+
+        if type_refs(&ty).contains(&TypeId(0)) {
+            return Err(::serde::ser::Error::custom(format!(
+                "type {:?} refers to the unit type, which has no gob equivalent",
+                type_name(&ty)
+            )));
         }
 
         let arc_ty = Arc::new(ty);
 
-        if let Some(id) = self.schema_types_reverse.get(&arc_ty) {
-            return Ok(*id);
+        if let Some((id, wire_types)) = self.existing_type_id(&arc_ty)? {
+            self.check_go_compatibility(&wire_types.messages)?;
+            if let Err(pos) = self
+                .schema_types
+                .binary_search_by(|(probe_id, _)| probe_id.cmp(&id))
+            {
+                self.schema_types.insert(pos, (id, arc_ty.clone()));
+            }
+            self.schema_types_reverse.insert(arc_ty.clone(), id);
+            self.wire_types.insert(id, wire_types);
+            return Ok(id);
         }
 
+        let mut messages = Vec::new();
+        let delta = SerializeWireTypes::new(&mut messages, self.field_naming)
+            .serialize_wire_types(next_id, &arc_ty)?;
+        self.check_go_compatibility(&messages)?;
+
         self.schema_types.push((next_id, arc_ty.clone()));
         self.schema_types_reverse.insert(arc_ty.clone(), next_id);
 
-        let delta = SerializeWireTypes::new(&mut self.pending_wire_types)
-            .serialize_wire_types(next_id, &arc_ty)?;
+        let refs = type_refs(&arc_ty);
+        self.wire_types.insert(
+            next_id,
+            WireTypes {
+                messages,
+                refs,
+                existing: false,
+            },
+        );
 
         self.next_type_id = TypeId((self.next_type_id.0 as usize + delta) as i64);
 
@@ -114,6 +951,11 @@ impl TypeId {
     pub const BYTES: TypeId = TypeId(5);
     pub const STRING: TypeId = TypeId(6);
     pub const COMPLEX: TypeId = TypeId(7);
+    pub const INTERFACE: TypeId = TypeId(8);
+    // marks struct fields which are not sent, never written to the wire
+    pub(crate) const SKIP: TypeId = TypeId(-1);
+    // `u8`, sent as UINT, but sequences of it are sent as BYTES
+    pub(crate) const BYTE: TypeId = TypeId(-2);
     pub(crate) const WIRE_TYPE: TypeId = TypeId(16);
     pub(crate) const ARRAY_TYPE: TypeId = TypeId(17);
     pub(crate) const COMMON_TYPE: TypeId = TypeId(18);
@@ -122,10 +964,20 @@ impl TypeId {
     pub(crate) const FIELD_TYPE: TypeId = TypeId(21);
     pub(crate) const FIELD_TYPE_SLICE: TypeId = TypeId(22);
     pub(crate) const MAP_TYPE: TypeId = TypeId(23);
+    pub(crate) const GOB_ENCODER_TYPE: TypeId = TypeId(24);
 
     pub(crate) fn next(&self) -> TypeId {
         TypeId(self.0 + 1)
     }
+
+    /// The id which is written to the wire for this type.
+    pub(crate) fn on_wire(self) -> TypeId {
+        if self == TypeId::BYTE {
+            TypeId::UINT
+        } else {
+            self
+        }
+    }
 }
 
 impl ::serde_schema::types::TypeId for TypeId {
@@ -135,7 +987,7 @@ impl ::serde_schema::types::TypeId for TypeId {
     const I32: TypeId = TypeId(2);
     const I64: TypeId = TypeId(2);
     const CHAR: TypeId = TypeId(2);
-    const U8: TypeId = TypeId(3);
+    const U8: TypeId = TypeId(-2);
     const U16: TypeId = TypeId(3);
     const U32: TypeId = TypeId(3);
     const U64: TypeId = TypeId(3);
@@ -144,7 +996,7 @@ impl ::serde_schema::types::TypeId for TypeId {
     const BYTES: TypeId = TypeId(5);
     const STR: TypeId = TypeId(6);
 
-    // not supported yet
+    // Go has no unit type, registering types which refer to it fails
     const UNIT: TypeId = TypeId(0);
 }
 
@@ -158,12 +1010,13 @@ impl<'de> Deserialize<'de> for TypeId {
 #[doc(hidden)]
 impl Serialize for TypeId {
     fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
-        if self.0 <= 0 {
+        let id = self.on_wire();
+        if id.0 <= 0 {
             return Err(::serde::ser::Error::custom(format!(
                 "invalid type id {}",
-                self.0
+                id.0
             )));
         }
-        self.0.serialize(ser)
+        id.0.serialize(ser)
     }
 }
@@ -0,0 +1,236 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet};
+
+use error::Error;
+use internal::ser::encode_wire_type;
+use internal::types::WireType;
+use schema::Schema;
+
+use super::{Output, OutputPart, TypeId};
+
+// the first id Go assigns to user defined types
+const FIRST_GO_TYPE_ID: TypeId = TypeId(65);
+
+/// Assigns type ids and sends type definitions the way Go's encoder does,
+/// see `StreamSerializer::set_go_type_order`.
+pub(crate) struct GoTypeOrder {
+    package: String,
+    // the schema types which have been assigned an id, with that id and
+    // the name they are sent under
+    ids: BTreeMap<TypeId, (TypeId, Cow<'static, str>)>,
+    next_id: TypeId,
+    // the schema types which have been reached while assigning ids
+    visited: BTreeSet<TypeId>,
+    sent: BTreeSet<TypeId>,
+    defs: BTreeMap<TypeId, WireType>,
+}
+
+impl GoTypeOrder {
+    pub fn new(package: &str) -> GoTypeOrder {
+        GoTypeOrder {
+            package: package.to_owned(),
+            ids: BTreeMap::new(),
+            next_id: FIRST_GO_TYPE_ID,
+            visited: BTreeSet::new(),
+            sent: BTreeSet::new(),
+            defs: BTreeMap::new(),
+        }
+    }
+
+    /// Write the definitions of the schema type `id` and all types it
+    /// refers to which have not been sent yet, and return the id the
+    /// type is sent under.
+    pub fn write_wire_types<O: Output>(
+        &mut self,
+        schema: &Schema,
+        id: TypeId,
+        mut o: O,
+    ) -> Result<TypeId, Error> {
+        let id = id.on_wire();
+        self.assign(schema, id, false)?;
+        self.send(id, &mut o)?;
+        Ok(self.go_id(id))
+    }
+
+    /// Returns `true` if any type definitions have been sent.
+    pub fn has_sent(&self) -> bool {
+        !self.sent.is_empty()
+    }
+
+    fn def(&mut self, schema: &Schema, id: TypeId) -> Result<WireType, Error> {
+        if let Some(def) = self.defs.get(&id) {
+            return Ok(def.clone());
+        }
+        let def = schema.wire_type(id)?;
+        self.defs.insert(id, def.clone());
+        Ok(def)
+    }
+
+    fn go_id(&self, id: TypeId) -> TypeId {
+        match self.ids.get(&id) {
+            Some(&(go_id, _)) => go_id,
+            None => id,
+        }
+    }
+
+    /// Assigns ids to type `id` and the types it refers to, in the order
+    /// Go creates its type descriptions: a struct before its fields, and
+    /// the elements of slices, arrays and maps before them.
+    ///
+    /// Go names slices, arrays and maps which are the type of a struct
+    /// field after how the type is written, and leaves others unnamed.
+    fn assign(&mut self, schema: &Schema, id: TypeId, is_field: bool) -> Result<(), Error> {
+        if id < FIRST_GO_TYPE_ID || !self.visited.insert(id) {
+            return Ok(());
+        }
+        let def = self.def(schema, id)?;
+        let name = match def {
+            WireType::Slice(_) | WireType::Array(_) | WireType::Map(_) if is_field => {
+                Cow::Owned(self.go_type_name(schema, id)?)
+            }
+            WireType::Slice(_) | WireType::Array(_) | WireType::Map(_) => Cow::Borrowed(""),
+            _ => def.common().name.clone(),
+        };
+        match def {
+            WireType::Struct(ref struct_type) => {
+                self.assign_id(id, name);
+                for field in struct_type.fields.iter() {
+                    self.assign(schema, field.id, true)?;
+                }
+            }
+            WireType::Slice(ref slice_type) => {
+                self.assign(schema, slice_type.elem, false)?;
+                self.assign_id(id, name);
+            }
+            WireType::Array(ref array_type) => {
+                self.assign(schema, array_type.elem, false)?;
+                self.assign_id(id, name);
+            }
+            WireType::Map(ref map_type) => {
+                self.assign(schema, map_type.key, false)?;
+                self.assign(schema, map_type.elem, false)?;
+                self.assign_id(id, name);
+            }
+            WireType::GobEncoder(_) | WireType::BinaryMarshaler(_) | WireType::TextMarshaler(_) => {
+                self.assign_id(id, name);
+            }
+        }
+        Ok(())
+    }
+
+    fn assign_id(&mut self, id: TypeId, name: Cow<'static, str>) {
+        self.ids.insert(id, (self.next_id, name));
+        self.next_id = self.next_id.next();
+    }
+
+    /// Sends the definition of type `id` followed by those of the types
+    /// it refers to, depth first.
+    fn send<O: Output>(&mut self, id: TypeId, o: &mut O) -> Result<(), Error> {
+        if id < FIRST_GO_TYPE_ID || !self.sent.insert(id) {
+            return Ok(());
+        }
+        let mut def = self.defs[&id].clone();
+        let (go_id, ref name) = self.ids[&id];
+        let refs = match def {
+            WireType::Struct(ref mut struct_type) => {
+                let refs = struct_type.fields.iter().map(|field| field.id).collect();
+                for field in struct_type.fields.to_mut() {
+                    field.id = self.go_id(field.id);
+                }
+                refs
+            }
+            WireType::Slice(ref mut slice_type) => {
+                let refs = vec![slice_type.elem];
+                slice_type.elem = self.go_id(slice_type.elem);
+                refs
+            }
+            WireType::Array(ref mut array_type) => {
+                let refs = vec![array_type.elem];
+                array_type.elem = self.go_id(array_type.elem);
+                refs
+            }
+            WireType::Map(ref mut map_type) => {
+                let refs = vec![map_type.key, map_type.elem];
+                map_type.key = self.go_id(map_type.key);
+                map_type.elem = self.go_id(map_type.elem);
+                refs
+            }
+            WireType::GobEncoder(_) | WireType::BinaryMarshaler(_) | WireType::TextMarshaler(_) => {
+                vec![]
+            }
+        };
+        {
+            let common = match def {
+                WireType::Array(ref mut inner) => &mut inner.common,
+                WireType::Slice(ref mut inner) => &mut inner.common,
+                WireType::Struct(ref mut inner) => &mut inner.common,
+                WireType::Map(ref mut inner) => &mut inner.common,
+                WireType::GobEncoder(ref mut inner)
+                | WireType::BinaryMarshaler(ref mut inner)
+                | WireType::TextMarshaler(ref mut inner) => &mut inner.common,
+            };
+            common.id = go_id;
+            common.name = name.clone();
+        }
+        o.serialize_part(OutputPart::new(encode_wire_type(&def)?))?;
+        for ref_id in refs {
+            self.send(ref_id, o)?;
+        }
+        Ok(())
+    }
+
+    /// Writes type `id` the way Go's reflection does, assuming Go's
+    /// `int`, `uint` and `float64` for all integers and floats, and
+    /// qualifying named types with the package.
+    fn go_type_name(&mut self, schema: &Schema, id: TypeId) -> Result<String, Error> {
+        let name = match id {
+            TypeId::BOOL => "bool",
+            TypeId::INT => "int",
+            TypeId::UINT => "uint",
+            TypeId::FLOAT => "float64",
+            TypeId::BYTES => "[]uint8",
+            TypeId::STRING => "string",
+            TypeId::COMPLEX => "complex128",
+            TypeId::INTERFACE => "interface {}",
+            _ => "",
+        };
+        if !name.is_empty() {
+            return Ok(name.to_owned());
+        }
+        let def = self.def(schema, id)?;
+        if !def.common().name.is_empty() {
+            return Ok(format!("{}.{}", self.package, def.common().name));
+        }
+        Ok(match def {
+            WireType::Slice(slice_type) => {
+                format!("[]{}", self.go_type_name(schema, slice_type.elem)?)
+            }
+            WireType::Array(array_type) => format!(
+                "[{}]{}",
+                array_type.len,
+                self.go_type_name(schema, array_type.elem)?
+            ),
+            WireType::Map(map_type) => format!(
+                "map[{}]{}",
+                self.go_type_name(schema, map_type.key)?,
+                self.go_type_name(schema, map_type.elem)?
+            ),
+            WireType::Struct(struct_type) => {
+                let mut fields = Vec::new();
+                for field in struct_type.fields.iter() {
+                    fields.push(format!(
+                        "{} {}",
+                        field.name,
+                        self.go_type_name(schema, field.id)?
+                    ));
+                }
+                if fields.is_empty() {
+                    "struct {}".to_owned()
+                } else {
+                    format!("struct {{ {} }}", fields.join("; "))
+                }
+            }
+            _ => String::new(),
+        })
+    }
+}
@@ -1,16 +1,28 @@
 //! Serialization
 
+use std::collections::BTreeSet;
 use std::io::Write;
+use std::sync::Arc;
 
 use serde::ser::{self, Impossible};
 use serde::Serialize;
 use serde_schema::SchemaSerialize;
 
-use internal::ser::{FieldValueSerializer, SerializationCtx, SerializeVariantValue};
-use internal::utils::Bow;
+use internal::ser::{infer_type, FieldValueSerializer, SerializationCtx, SerializeVariantValue};
 
 use error::Error;
-pub use schema::{Schema, TypeId};
+use schema::RegisteredTypes;
+pub use schema::{
+    ByteSequences, FieldNaming, GoCompatibility, GoIssue, NoneElements, Schema, TypeId,
+    WideIntegers,
+};
+
+mod options;
+pub(crate) use self::options::Options;
+pub use self::options::ZeroValues;
+
+mod go_order;
+use self::go_order::GoTypeOrder;
 
 mod output;
 pub use self::output::{Output, OutputBuffer, OutputPart, OutputWrite};
@@ -28,17 +40,37 @@ pub use self::serialize_struct_variant::SerializeStructVariant;
 
 /// Serializes a single value.
 pub struct Serializer<'t, O> {
-    ctx: SerializationCtx<Bow<'t, Schema>>,
+    ctx: SerializationCtx<&'t Schema>,
     type_id: TypeId,
+    // the id written to the wire, which differs from `type_id` with
+    // `StreamSerializer::set_go_type_order`
+    wire_id: TypeId,
     out: O,
 }
 
 /// Serializes a stream of values.
 pub struct StreamSerializer<O> {
-    schema: Schema,
+    schema: StreamSchema,
+    written: BTreeSet<TypeId>,
+    go_order: Option<GoTypeOrder>,
+    options: Options,
     out: O,
 }
 
+enum StreamSchema {
+    Owned(Schema),
+    Shared(Arc<Schema>),
+}
+
+impl StreamSchema {
+    fn get(&self) -> &Schema {
+        match self {
+            StreamSchema::Owned(schema) => schema,
+            StreamSchema::Shared(schema) => schema,
+        }
+    }
+}
+
 impl StreamSerializer<OutputBuffer> {
     /// Create a new stream serializer that writes into a buffer.
     pub fn new_with_buffer() -> Self {
@@ -57,17 +89,104 @@ impl<W: Write> StreamSerializer<OutputWrite<W>> {
 impl<O> StreamSerializer<O> {
     fn new(out: O) -> StreamSerializer<O> {
         let schema = Schema::new();
-        StreamSerializer { schema, out }
-    }
-
+        StreamSerializer {
+            schema: StreamSchema::Owned(schema),
+            written: BTreeSet::new(),
+            go_order: None,
+            options: Options::default(),
+            out,
+        }
+    }
+
+    /// Returns the schema, for registering types.
+    ///
+    /// If the schema is shared, it is copied first, and this stream no
+    /// longer shares it.
     pub fn schema_mut(&mut self) -> &mut Schema {
-        &mut self.schema
-    }
-
-    pub fn serializer<'a>(&'a mut self, id: TypeId) -> Result<Serializer<'a, &'a mut O>, Error> {
-        let ctx = SerializationCtx::with_schema(Bow::Borrowed(&mut self.schema));
+        if let StreamSchema::Shared(schema) = &self.schema {
+            self.schema = StreamSchema::Owned(Schema::clone(schema));
+        }
+        match &mut self.schema {
+            StreamSchema::Owned(schema) => schema,
+            StreamSchema::Shared(_) => unreachable!(),
+        }
+    }
+
+    /// Use a schema shared with other streams.
+    ///
+    /// Types are not registered with a shared schema, so serializing a
+    /// value of a type which is not yet registered fails. This allows
+    /// types to be registered once at startup, with every stream
+    /// assigning the same ids to them.
+    ///
+    /// Fails if type definitions have already been sent on the stream, as
+    /// the ids they were sent under may mean other types in `schema`.
+    pub fn set_shared_schema(&mut self, schema: Arc<Schema>) -> Result<(), Error> {
+        if self.types_sent() {
+            return Err(ser::Error::custom(
+                "the shared schema must be set before types are sent",
+            ));
+        }
+        self.schema = StreamSchema::Shared(schema);
+        Ok(())
+    }
+
+    /// Assign type ids and send type definitions the way Go's
+    /// `gob.Encoder` does, so that the output is the same as Go's when
+    /// it encodes the same values in a fresh process.
+    ///
+    /// Ids are assigned when a type is first sent rather than when it is
+    /// registered, to a struct before the types of its fields, and to the
+    /// elements of slices, arrays and maps before them. The definition of
+    /// a type is sent before those of the types it refers to. Slices,
+    /// arrays and maps which are the types of struct fields are named
+    /// like Go names them, e.g. `[]string` or `map[string]int`, where
+    /// named types are qualified with `package`, and all integers and
+    /// floats are assumed to be Go's `int`, `uint` and `float64`.
+    ///
+    /// Go encodes maps in random order, so only maps of at most one entry
+    /// can be compared.
+    ///
+    /// Fails if type definitions have already been sent on the stream, as
+    /// Go rejects types being defined again under new ids.
+    pub fn set_go_type_order(&mut self, package: &str) -> Result<(), Error> {
+        if self.types_sent() {
+            return Err(ser::Error::custom(
+                "the Go type order must be set before types are sent",
+            ));
+        }
+        self.go_order = Some(GoTypeOrder::new(package));
+        Ok(())
+    }
+
+    fn types_sent(&self) -> bool {
+        match self.go_order {
+            Some(ref go_order) => go_order.has_sent(),
+            None => !self.written.is_empty(),
+        }
+    }
+
+    /// Set which struct fields holding a zero value are written.
+    pub fn set_zero_values(&mut self, zero_values: ZeroValues) {
+        self.options.zero_values = zero_values;
+    }
+
+    pub fn serializer<'a>(&'a mut self, id: TypeId) -> Result<Serializer<'a, &'a mut O>, Error>
+    where
+        O: Output,
+    {
+        let schema = self.schema.get();
+        let wire_id = match self.go_order {
+            Some(ref mut go_order) => go_order.write_wire_types(schema, id, &mut self.out)?,
+            None => {
+                schema.write_wire_types(id, &mut self.written, &mut self.out)?;
+                id.on_wire()
+            }
+        };
+        let ctx = SerializationCtx::with_options(schema, self.options);
         Ok(Serializer {
-            type_id: id,
+            type_id: id.on_wire(),
+            wire_id,
             ctx,
             out: &mut self.out,
         })
@@ -79,13 +198,34 @@ impl<O> StreamSerializer<O> {
         T: SchemaSerialize,
         O: Output,
     {
-        let type_id = T::schema_register(&mut self.schema)?;
+        let type_id = match &mut self.schema {
+            StreamSchema::Owned(schema) => T::schema_register(schema)?,
+            StreamSchema::Shared(schema) => T::schema_register(&mut RegisteredTypes(schema))?,
+        };
+        self.serialize_with_type_id(type_id, value)
+    }
+
+    /// Serialize a value whose type does not implement `SchemaSerialize`
+    /// onto the stream, registering a type inferred from the value
+    /// itself, see `Schema::register_inferred`.
+    ///
+    /// Values whose inferred types differ, for example because a field is
+    /// `None` in one but not the other, are sent with different type ids.
+    pub fn serialize_inferred<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+        O: Output,
+    {
+        let type_id = match &mut self.schema {
+            StreamSchema::Owned(schema) => schema.register_inferred(value)?,
+            StreamSchema::Shared(schema) => infer_type(value, &mut RegisteredTypes(schema))?,
+        };
         self.serialize_with_type_id(type_id, value)
     }
 
     pub fn serialize_with_type_id<T>(&mut self, type_id: TypeId, value: &T) -> Result<(), Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
         O: Output,
     {
         value.serialize(self.serializer(type_id)?)
@@ -110,14 +250,14 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
 
     type SerializeSeq = SerializeSeq<'t, O>;
     type SerializeTuple = SerializeTuple<'t, O>;
-    type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = SerializeTuple<'t, O>;
     type SerializeTupleVariant = Impossible<Self::Ok, Self::Error>;
     type SerializeMap = SerializeMap<'t, O>;
     type SerializeStruct = SerializeStruct<'t, O>;
     type SerializeStructVariant = SerializeStructVariant<'t, O>;
 
     fn serialize_bool(mut self, v: bool) -> Result<Self::Ok, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_int(self.wire_id.0);
         self.ctx.value.write_uint(0);
         let mut ok = {
             let ser = FieldValueSerializer {
@@ -142,7 +282,7 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     }
 
     fn serialize_i64(mut self, v: i64) -> Result<Self::Ok, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_int(self.wire_id.0);
         self.ctx.value.write_uint(0);
         let mut ok = {
             let ser = FieldValueSerializer {
@@ -154,6 +294,19 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
         ok.ctx.flush(self.out)
     }
 
+    fn serialize_i128(mut self, v: i128) -> Result<Self::Ok, Self::Error> {
+        self.ctx.value.write_int(self.wire_id.0);
+        self.ctx.value.write_uint(0);
+        let mut ok = {
+            let ser = FieldValueSerializer {
+                ctx: self.ctx,
+                type_id: self.type_id,
+            };
+            ser.serialize_i128(v)?
+        };
+        ok.ctx.flush(self.out)
+    }
+
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
         self.serialize_u64(v as u64)
     }
@@ -167,7 +320,7 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     }
 
     fn serialize_u64(mut self, v: u64) -> Result<Self::Ok, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_int(self.wire_id.0);
         self.ctx.value.write_uint(0);
         let mut ok = {
             let ser = FieldValueSerializer {
@@ -179,12 +332,25 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
         ok.ctx.flush(self.out)
     }
 
+    fn serialize_u128(mut self, v: u128) -> Result<Self::Ok, Self::Error> {
+        self.ctx.value.write_int(self.wire_id.0);
+        self.ctx.value.write_uint(0);
+        let mut ok = {
+            let ser = FieldValueSerializer {
+                ctx: self.ctx,
+                type_id: self.type_id,
+            };
+            ser.serialize_u128(v)?
+        };
+        ok.ctx.flush(self.out)
+    }
+
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         self.serialize_f64(v as f64)
     }
 
     fn serialize_f64(mut self, v: f64) -> Result<Self::Ok, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_int(self.wire_id.0);
         self.ctx.value.write_uint(0);
         let mut ok = {
             let ser = FieldValueSerializer {
@@ -201,7 +367,7 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     }
 
     fn serialize_str(mut self, v: &str) -> Result<Self::Ok, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_int(self.wire_id.0);
         self.ctx.value.write_uint(0);
         let mut ok = {
             let ser = FieldValueSerializer {
@@ -214,7 +380,7 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     }
 
     fn serialize_bytes(mut self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_int(self.wire_id.0);
         self.ctx.value.write_uint(0);
         let mut ok = {
             let ser = FieldValueSerializer {
@@ -227,8 +393,10 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     }
 
     fn serialize_none(mut self) -> Result<Self::Ok, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
-        self.ctx.value.write_uint(0);
+        self.ctx.value.write_int(self.wire_id.0);
+        if !self.ctx.schema.is_struct(self.type_id) {
+            self.ctx.value.write_uint(0);
+        }
         let mut ok = {
             let ser = FieldValueSerializer {
                 ctx: self.ctx,
@@ -239,11 +407,22 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
         ok.ctx.flush(self.out)
     }
 
-    fn serialize_some<T: ?Sized>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    fn serialize_some<T>(mut self, value: &T) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        value.serialize(self)
+        if !self.ctx.schema.is_nullable(self.type_id) {
+            return value.serialize(self);
+        }
+        self.ctx.value.write_int(self.wire_id.0);
+        let mut ok = {
+            let ser = FieldValueSerializer {
+                ctx: self.ctx,
+                type_id: self.type_id,
+            };
+            ser.serialize_some(value)?
+        };
+        ok.ctx.flush(self.out)
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
@@ -263,18 +442,18 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
         Err(ser::Error::custom("not implemented yet"))
     }
 
-    fn serialize_newtype_struct<T: ?Sized>(
+    fn serialize_newtype_struct<T>(
         self,
         _name: &'static str,
-        _value: &T,
+        value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        Err(ser::Error::custom("not implemented yet"))
+        value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized>(
+    fn serialize_newtype_variant<T>(
         mut self,
         name: &'static str,
         variant_index: u32,
@@ -282,9 +461,9 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
         value: &T,
     ) -> Result<Self::Ok, Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
-        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_int(self.wire_id.0);
         let mut ok = {
             let ser = FieldValueSerializer {
                 ctx: self.ctx,
@@ -296,23 +475,27 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     }
 
     fn serialize_seq(mut self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_int(self.wire_id.0);
         self.ctx.value.write_uint(0);
         SerializeSeq::new(len, self.type_id, self.ctx, self.out)
     }
 
     fn serialize_tuple(mut self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
-        self.ctx.value.write_uint(0);
-        SerializeTuple::homogeneous(self.type_id, self.ctx, self.out)
+        self.ctx.value.write_int(self.wire_id.0);
+        if !self.ctx.schema.is_struct(self.type_id) {
+            self.ctx.value.write_uint(0);
+        }
+        SerializeTuple::new(self.type_id, self.ctx, self.out)
     }
 
     fn serialize_tuple_struct(
-        self,
-        _name: &'static str,
-        _len: usize,
+        mut self,
+        name: &'static str,
+        len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
-        Err(ser::Error::custom("not implemented yet"))
+        self.ctx.value.write_int(self.wire_id.0);
+        self.ctx.value.write_uint(0);
+        SerializeTuple::tuple_struct(name, len, self.type_id, self.ctx, self.out)
     }
 
     fn serialize_tuple_variant(
@@ -326,8 +509,10 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
     }
 
     fn serialize_map(mut self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
-        self.ctx.value.write_uint(0);
+        self.ctx.value.write_int(self.wire_id.0);
+        if !self.ctx.schema.is_struct(self.type_id) {
+            self.ctx.value.write_uint(0);
+        }
         SerializeMap::new(len, self.type_id, self.ctx, self.out)
     }
 
@@ -336,8 +521,8 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
         _name: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
-        Ok(SerializeStruct::new(self.type_id, self.ctx, self.out)?)
+        self.ctx.value.write_int(self.wire_id.0);
+        SerializeStruct::new(self.type_id, self.ctx, self.out)
     }
 
     fn serialize_struct_variant(
@@ -347,7 +532,7 @@ impl<'t, O: Output> ser::Serializer for Serializer<'t, O> {
         _variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        self.ctx.value.write_int(self.type_id.0);
+        self.ctx.value.write_int(self.wire_id.0);
         let inner =
             SerializeVariantValue::new(self.ctx, self.type_id, variant_index)?.serialize_struct()?;
         SerializeStructVariant::new(inner, self.out)
@@ -0,0 +1,30 @@
+/// Controls which struct fields holding a zero value are written.
+///
+/// Go's decoder assigns every field that is present in a message and
+/// leaves all other fields of the destination untouched, so it cannot
+/// tell an omitted field from one holding the zero value unless it
+/// decodes into a pointer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZeroValues {
+    /// Fields holding a zero value (`0`, `false`, `""`, empty slices and
+    /// maps, `None`) are omitted, as Go's encoder does. This produces the
+    /// most compact output.
+    #[default]
+    Omit,
+    /// Every field is written, including zero values. `None` is written as
+    /// the zero value of the inner type. Go observes the same values as
+    /// with `Omit`, but a destination that is reused between messages
+    /// has all of its fields overwritten.
+    Emit,
+    /// Like `Omit`, but `Some` values of `Option` fields are always
+    /// written, even if they hold a zero value. When decoding into a
+    /// pointer field, Go observes `Some(0)` as a pointer to `0` and `None`
+    /// as `nil`.
+    EmitSome,
+}
+
+/// Encoding options which are shared by all values of a stream.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Options {
+    pub zero_values: ZeroValues,
+}
@@ -70,7 +70,7 @@ pub trait Output {
     fn serialize_part(&mut self, part: OutputPart) -> Result<(), Error>;
 }
 
-impl<'a, O: Output> Output for &'a mut O {
+impl<O: Output> Output for &mut O {
     fn serialize_part(&mut self, part: OutputPart) -> Result<(), Error> {
         Output::serialize_part(*self, part)
     }
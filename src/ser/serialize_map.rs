@@ -3,13 +3,12 @@ use serde::ser::{self, Serialize};
 use error::Error;
 use internal::ser::{SerializationCtx, SerializeMapValue};
 use internal::types::TypeId;
-use internal::utils::Bow;
 use schema::Schema;
 
 use super::output::Output;
 
 pub struct SerializeMap<'t, O> {
-    inner: SerializeMapValue<Bow<'t, Schema>>,
+    inner: SerializeMapValue<&'t Schema>,
     out: O,
 }
 
@@ -17,7 +16,7 @@ impl<'t, O: Output> SerializeMap<'t, O> {
     pub(crate) fn new(
         len: Option<usize>,
         type_id: TypeId,
-        ctx: SerializationCtx<Bow<'t, Schema>>,
+        ctx: SerializationCtx<&'t Schema>,
         out: O,
     ) -> Result<Self, Error> {
         Ok(SerializeMap {
@@ -31,16 +30,16 @@ impl<'t, O: Output> ser::SerializeMap for SerializeMap<'t, O> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_key<T: ?Sized>(&mut self, key: &T) -> Result<(), Self::Error>
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         self.inner.serialize_key(key)
     }
 
-    fn serialize_value<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         self.inner.serialize_value(value)
     }
@@ -3,13 +3,12 @@ use serde::ser::{self, Serialize};
 use error::Error;
 use internal::ser::{SerializationCtx, SerializeSeqValue};
 use internal::types::TypeId;
-use internal::utils::Bow;
 use schema::Schema;
 
 use super::output::Output;
 
 pub struct SerializeSeq<'t, O> {
-    inner: SerializeSeqValue<Bow<'t, Schema>>,
+    inner: SerializeSeqValue<&'t Schema>,
     out: O,
 }
 
@@ -17,7 +16,7 @@ impl<'t, O: Output> SerializeSeq<'t, O> {
     pub(crate) fn new(
         len: Option<usize>,
         type_id: TypeId,
-        ctx: SerializationCtx<Bow<'t, Schema>>,
+        ctx: SerializationCtx<&'t Schema>,
         out: O,
     ) -> Result<Self, Error> {
         Ok(SerializeSeq {
@@ -31,9 +30,9 @@ impl<'t, O: Output> ser::SerializeSeq for SerializeSeq<'t, O> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         self.inner.serialize_element(value)
     }
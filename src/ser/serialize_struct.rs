@@ -3,20 +3,19 @@ use serde::ser::{self, Serialize};
 use error::Error;
 use internal::ser::{SerializationCtx, SerializeStructValue};
 use internal::types::TypeId;
-use internal::utils::Bow;
 use schema::Schema;
 
 use super::output::Output;
 
 pub struct SerializeStruct<'t, O> {
-    inner: SerializeStructValue<Bow<'t, Schema>>,
+    inner: SerializeStructValue<&'t Schema>,
     out: O,
 }
 
 impl<'t, O: Output> SerializeStruct<'t, O> {
     pub(crate) fn new(
         type_id: TypeId,
-        ctx: SerializationCtx<Bow<'t, Schema>>,
+        ctx: SerializationCtx<&'t Schema>,
         out: O,
     ) -> Result<Self, Error> {
         Ok(SerializeStruct {
@@ -30,13 +29,9 @@ impl<'t, O: Output> ser::SerializeStruct for SerializeStruct<'t, O> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         self.inner.serialize_field(key, value)
     }
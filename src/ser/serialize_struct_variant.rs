@@ -2,19 +2,18 @@ use serde::ser::{self, Serialize};
 
 use error::Error;
 use internal::ser::SerializeStructVariantValue;
-use internal::utils::Bow;
 use schema::Schema;
 
 use super::output::Output;
 
 pub struct SerializeStructVariant<'t, O> {
-    inner: SerializeStructVariantValue<Bow<'t, Schema>>,
+    inner: SerializeStructVariantValue<&'t Schema>,
     out: O,
 }
 
 impl<'t, O: Output> SerializeStructVariant<'t, O> {
     pub(crate) fn new(
-        inner: SerializeStructVariantValue<Bow<'t, Schema>>,
+        inner: SerializeStructVariantValue<&'t Schema>,
         out: O,
     ) -> Result<Self, Error> {
         Ok(SerializeStructVariant { inner, out })
@@ -25,13 +24,9 @@ impl<'t, O: Output> ser::SerializeStructVariant for SerializeStructVariant<'t, O
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T: ?Sized>(
-        &mut self,
-        key: &'static str,
-        value: &T,
-    ) -> Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         self.inner.serialize_field(key, value)
     }
@@ -1,26 +1,39 @@
-use serde::ser::{self, Serialize};
+use serde::ser::{self, Serialize, Serializer};
 
 use error::Error;
-use internal::ser::{SerializationCtx, SerializeTupleValue};
+use internal::ser::{FieldValueSerializer, SerializationCtx, SerializeTupleValue};
 use internal::types::TypeId;
-use internal::utils::Bow;
 use schema::Schema;
 
 use super::output::Output;
 
 pub struct SerializeTuple<'t, O> {
-    inner: SerializeTupleValue<Bow<'t, Schema>>,
+    inner: SerializeTupleValue<&'t Schema>,
     out: O,
 }
 
 impl<'t, O: Output> SerializeTuple<'t, O> {
-    pub(crate) fn homogeneous(
+    pub(crate) fn new(
         type_id: TypeId,
-        ctx: SerializationCtx<Bow<'t, Schema>>,
+        ctx: SerializationCtx<&'t Schema>,
         out: O,
     ) -> Result<Self, Error> {
         Ok(SerializeTuple {
-            inner: SerializeTupleValue::homogeneous(ctx, type_id)?,
+            inner: SerializeTupleValue::new(ctx, type_id)?,
+            out,
+        })
+    }
+
+    pub(crate) fn tuple_struct(
+        name: &'static str,
+        len: usize,
+        type_id: TypeId,
+        ctx: SerializationCtx<&'t Schema>,
+        out: O,
+    ) -> Result<Self, Error> {
+        let ser = FieldValueSerializer { ctx, type_id };
+        Ok(SerializeTuple {
+            inner: ser.serialize_tuple_struct(name, len)?,
             out,
         })
     }
@@ -30,9 +43,9 @@ impl<'t, O: Output> ser::SerializeTuple for SerializeTuple<'t, O> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_element<T: ?Sized>(&mut self, value: &T) -> Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         self.inner.serialize_element(value)
     }
@@ -42,3 +55,19 @@ impl<'t, O: Output> ser::SerializeTuple for SerializeTuple<'t, O> {
         ok.ctx.flush(self.out)
     }
 }
+
+impl<'t, O: Output> ser::SerializeTupleStruct for SerializeTuple<'t, O> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeTuple::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        ser::SerializeTuple::end(self)
+    }
+}
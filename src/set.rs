@@ -0,0 +1,175 @@
+//! Sets, sent as Go maps
+
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeStruct, Serializer};
+use serde_schema::types::{Type, TypeId};
+use serde_schema::{Schema, SchemaSerialize};
+
+use gob_schema::GobSchema;
+
+/// Name of Go's empty struct type, which `StructMapSet` uses as the
+/// element type of its map.
+const EMPTY_STRUCT_NAME: &str = "struct {}";
+
+/// A set which is sent as a Go `map[T]struct{}`.
+///
+/// serde serializes sets as sequences, which Go can only decode into
+/// slices. Wrapping a `HashSet` or `BTreeSet` sends it as a map instead.
+/// When decoding, both `map[T]struct{}` and `map[T]bool` are accepted,
+/// as well as slices.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StructMapSet<S>(pub S);
+
+/// A set which is sent as a Go `map[T]bool`, with all elements mapped to
+/// `true`.
+///
+/// When decoding, elements which are mapped to `false` are left out,
+/// and `map[T]struct{}` as well as slices are accepted too.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BoolMapSet<S>(pub S);
+
+struct EmptyStruct;
+
+impl Serialize for EmptyStruct {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        ser.serialize_struct(EMPTY_STRUCT_NAME, 0)?.end()
+    }
+}
+
+fn register_empty_struct<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+    schema.register_type(Type::build().struct_type(EMPTY_STRUCT_NAME, 0).end())
+}
+
+fn register_bool<S: Schema>(_: &mut S) -> Result<S::TypeId, S::Error> {
+    Ok(S::TypeId::BOOL)
+}
+
+macro_rules! set_impl {
+    ($set:ident, $value:expr, $register_value:ident) => {
+        set_impl!($set, $value, $register_value, BTreeSet<T>, [T: Ord], []);
+        set_impl!(
+            $set,
+            $value,
+            $register_value,
+            HashSet<T, H>,
+            [T: Eq + Hash],
+            [, H: BuildHasher + Default]
+        );
+    };
+    (
+        $set:ident,
+        $value:expr,
+        $register_value:ident,
+        $inner:ty,
+        [T: $($bound:tt)*],
+        [$($params:tt)*]
+    ) => {
+        impl<T: Serialize + $($bound)* $($params)*> Serialize for $set<$inner> {
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                let mut map = ser.serialize_map(Some(self.0.len()))?;
+                for elem in &self.0 {
+                    map.serialize_entry(elem, &$value)?;
+                }
+                map.end()
+            }
+        }
+
+        impl<'de, T: Deserialize<'de> + $($bound)* $($params)*> Deserialize<'de>
+            for $set<$inner>
+        {
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                de.deserialize_any(SetVisitor(PhantomData)).map($set)
+            }
+        }
+
+        impl<T: GobSchema + $($bound)* $($params)*> GobSchema for $set<$inner> {
+            fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                let key = T::register(schema)?;
+                let value = $register_value(schema)?;
+                schema.register_type(Type::build().map_type(key, value))
+            }
+        }
+
+        impl<T: SchemaSerialize + $($bound)* $($params)*> SchemaSerialize
+            for $set<$inner>
+        {
+            fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                let key = T::schema_register(schema)?;
+                let value = $register_value(schema)?;
+                schema.register_type(Type::build().map_type(key, value))
+            }
+        }
+    };
+}
+
+set_impl!(StructMapSet, EmptyStruct, register_empty_struct);
+set_impl!(BoolMapSet, true, register_bool);
+
+struct SetVisitor<C, T>(PhantomData<(C, T)>);
+
+impl<'de, C, T> Visitor<'de> for SetVisitor<C, T>
+where
+    C: Default + Extend<T>,
+    T: Deserialize<'de>,
+{
+    type Value = C;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a map of set elements or a sequence")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<C, A::Error> {
+        let mut set = C::default();
+        while let Some((elem, Member(is_member))) = map.next_entry()? {
+            if is_member {
+                set.extend(Some(elem));
+            }
+        }
+        Ok(set)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<C, A::Error> {
+        let mut set = C::default();
+        while let Some(elem) = seq.next_element()? {
+            set.extend(Some(elem));
+        }
+        Ok(set)
+    }
+}
+
+/// The value of a set element in a `map[T]struct{}` or `map[T]bool`.
+struct Member(bool);
+
+impl<'de> Deserialize<'de> for Member {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_any(MemberVisitor)
+    }
+}
+
+struct MemberVisitor;
+
+impl<'de> Visitor<'de> for MemberVisitor {
+    type Value = Member;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a bool or an empty struct")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Member, E> {
+        Ok(Member(v))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Member, E> {
+        Ok(Member(true))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Member, A::Error> {
+        while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+        Ok(Member(true))
+    }
+}
@@ -0,0 +1,127 @@
+//! Optional values, sent as Go's `database/sql` null types
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use serde_schema::types::{Type, TypeId};
+use serde_schema::{Schema, SchemaSerialize};
+
+use go::Time;
+use gob_schema::GobSchema;
+use schema::NULLABLE_NAME;
+
+fn register_nullable<S: Schema>(
+    schema: &mut S,
+    name: &'static str,
+    field: &'static str,
+    value: S::TypeId,
+) -> Result<S::TypeId, S::Error> {
+    let id = schema.register_type(
+        Type::build()
+            .struct_type(name, 2)
+            .field(field, value)
+            .field("Valid", S::TypeId::BOOL)
+            .end(),
+    )?;
+    schema.register_type(Type::build().newtype_struct_type(NULLABLE_NAME, id))
+}
+
+macro_rules! null_impl {
+    ($name:ident, $value:ty, $field:expr, $id:ident) => {
+        #[doc = concat!(
+            "An optional `", stringify!($value), "`, sent as Go's `sql.", stringify!($name),
+            "`, a struct of a `", $field, "` field and a `Valid` field which is false for `None`."
+        )]
+        ///
+        /// When decoding, such structs are accepted for plain `Option`s too.
+        #[derive(Clone, Debug, Default, PartialEq)]
+        pub struct $name(pub Option<$value>);
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+                self.0.serialize(ser)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+                Option::deserialize(de).map($name)
+            }
+        }
+
+        impl SchemaSerialize for $name {
+            fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                register_nullable(schema, stringify!($name), $field, S::TypeId::$id)
+            }
+        }
+
+        impl GobSchema for $name {
+            fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+                <$name as SchemaSerialize>::schema_register(schema)
+            }
+        }
+
+        impl From<Option<$value>> for $name {
+            fn from(value: Option<$value>) -> $name {
+                $name(value)
+            }
+        }
+
+        impl From<$name> for Option<$value> {
+            fn from(value: $name) -> Option<$value> {
+                value.0
+            }
+        }
+    };
+}
+
+null_impl!(NullString, String, "String", STR);
+null_impl!(NullInt64, i64, "Int64", I64);
+null_impl!(NullInt32, i32, "Int32", I32);
+null_impl!(NullInt16, i16, "Int16", I16);
+null_impl!(NullByte, u8, "Byte", U8);
+null_impl!(NullFloat64, f64, "Float64", F64);
+null_impl!(NullBool, bool, "Bool", BOOL);
+
+/// An optional time, sent as Go's `sql.NullTime`, a struct of a `Time`
+/// field and a `Valid` field which is false for `None`.
+///
+/// The time is sent like `go::Time`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NullTime(pub Option<Time>);
+
+impl Serialize for NullTime {
+    fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(ser)
+    }
+}
+
+impl<'de> Deserialize<'de> for NullTime {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        Option::deserialize(de).map(NullTime)
+    }
+}
+
+impl SchemaSerialize for NullTime {
+    fn schema_register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        let time = Time::register(schema)?;
+        register_nullable(schema, "NullTime", "Time", time)
+    }
+}
+
+impl GobSchema for NullTime {
+    fn register<S: Schema>(schema: &mut S) -> Result<S::TypeId, S::Error> {
+        <NullTime as SchemaSerialize>::schema_register(schema)
+    }
+}
+
+impl From<Option<Time>> for NullTime {
+    fn from(time: Option<Time>) -> NullTime {
+        NullTime(time)
+    }
+}
+
+impl From<NullTime> for Option<Time> {
+    fn from(time: NullTime) -> Option<Time> {
+        time.0
+    }
+}
@@ -0,0 +1,155 @@
+extern crate gob;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_schema;
+#[macro_use]
+extern crate serde_schema_derive;
+
+use gob::compat::{self, Compatibility};
+use gob::de::TypeDefinitions;
+use gob::ser::Schema;
+use gob::StreamDeserializer;
+use serde_schema::SchemaSerialize;
+
+fn stream_type_definitions(input: &[u8]) -> TypeDefinitions {
+    let mut stream = StreamDeserializer::new(input);
+    while stream.skip().unwrap() {}
+    stream.type_definitions().clone()
+}
+
+fn schema_type_definitions<T: SchemaSerialize>() -> TypeDefinitions {
+    let mut schema = Schema::new();
+    T::schema_register(&mut schema).unwrap();
+    schema.type_definitions().unwrap()
+}
+
+#[derive(Serialize, SchemaSerialize)]
+struct Point {
+    #[serde(rename = "X")]
+    x: i64,
+    #[serde(rename = "Y")]
+    y: i64,
+}
+
+#[test]
+fn schema_matches_stream() {
+    let old = stream_type_definitions(include_bytes!("reference/output/point_struct.gob"));
+    let new = schema_type_definitions::<Point>();
+    assert_eq!(old.as_bytes(), new.as_bytes());
+
+    let report = compat::check(&old, &new);
+    assert!(report.changes().is_empty());
+    assert_eq!(report.compatibility(), Compatibility::Both);
+}
+
+#[test]
+fn field_added() {
+    #[derive(Serialize, SchemaSerialize)]
+    #[serde(rename = "Point")]
+    struct Point3 {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+        #[serde(rename = "Z")]
+        z: u32,
+    }
+
+    let report = compat::check(
+        &schema_type_definitions::<Point>(),
+        &schema_type_definitions::<Point3>(),
+    );
+    assert_eq!(report.changes().len(), 1);
+    let change = &report.changes()[0];
+    assert_eq!(change.type_name(), "Point");
+    assert_eq!(change.field_name(), Some("Z"));
+    assert_eq!(change.compatibility(), Compatibility::Both);
+    assert_eq!(report.compatibility(), Compatibility::Both);
+}
+
+#[test]
+fn field_class_changed() {
+    #[derive(Serialize, SchemaSerialize)]
+    #[serde(rename = "Point")]
+    struct StringPoint {
+        #[serde(rename = "X")]
+        x: String,
+        #[serde(rename = "Y")]
+        y: i32,
+    }
+
+    let report = compat::check(
+        &schema_type_definitions::<Point>(),
+        &schema_type_definitions::<StringPoint>(),
+    );
+    assert_eq!(report.changes().len(), 1);
+    let change = &report.changes()[0];
+    assert_eq!(change.field_name(), Some("X"));
+    assert_eq!(change.compatibility(), Compatibility::Neither);
+    assert_eq!(report.type_compatibility("Point"), Compatibility::Neither);
+    assert_eq!(report.compatibility(), Compatibility::Neither);
+}
+
+#[test]
+fn no_fields_in_common() {
+    #[derive(Serialize, SchemaSerialize)]
+    #[serde(rename = "Point")]
+    struct PolarPoint {
+        #[serde(rename = "R")]
+        r: f64,
+        #[serde(rename = "Phi")]
+        phi: f64,
+    }
+
+    let report = compat::check(
+        &schema_type_definitions::<Point>(),
+        &schema_type_definitions::<PolarPoint>(),
+    );
+    assert_eq!(report.compatibility(), Compatibility::Neither);
+}
+
+#[test]
+fn nested_array_length_changed() {
+    #[derive(Serialize, SchemaSerialize)]
+    #[serde(rename = "Line")]
+    struct Line2 {
+        #[serde(rename = "Points")]
+        points: [i64; 2],
+    }
+
+    #[derive(Serialize, SchemaSerialize)]
+    #[serde(rename = "Line")]
+    struct Line3 {
+        #[serde(rename = "Points")]
+        points: [i64; 3],
+    }
+
+    let report = compat::check(
+        &schema_type_definitions::<Line2>(),
+        &schema_type_definitions::<Line3>(),
+    );
+    assert_eq!(report.changes().len(), 1);
+    assert_eq!(report.changes()[0].field_name(), Some("Points"));
+    assert_eq!(report.compatibility(), Compatibility::Neither);
+}
+
+#[test]
+fn types_added_and_removed() {
+    #[derive(Serialize, SchemaSerialize)]
+    struct BoolStruct {
+        #[serde(rename = "V")]
+        v: bool,
+    }
+
+    let report = compat::check(
+        &schema_type_definitions::<Point>(),
+        &schema_type_definitions::<BoolStruct>(),
+    );
+    assert_eq!(report.type_compatibility("Point"), Compatibility::WriteOld);
+    assert_eq!(
+        report.type_compatibility("BoolStruct"),
+        Compatibility::ReadOld
+    );
+    assert_eq!(report.compatibility(), Compatibility::Neither);
+}
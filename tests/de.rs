@@ -6,11 +6,17 @@ extern crate serde_bytes;
 extern crate serde_derive;
 #[macro_use]
 extern crate quickcheck;
+extern crate serde_schema;
+#[macro_use]
+extern crate serde_schema_derive;
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::io::Cursor;
 
-use gob::{error::ErrorKind, Deserializer, StreamDeserializer};
+use gob::de::{Dispatcher, FieldMatching, InvalidUtf8, TypeDefinitions};
+use gob::{
+    error::ErrorKind, BoolMapSet, Complex128, Deserializer, StreamDeserializer, StreamSerializer,
+};
 use partial_io::{GenWouldBlock, PartialRead, PartialWithErrors};
 use serde::Deserialize;
 use serde_bytes::{ByteBuf, Bytes};
@@ -19,14 +25,14 @@ use serde_bytes::{ByteBuf, Bytes};
 fn bool_true() {
     let deserializer = Deserializer::from_slice(&[3, 2, 0, 1]);
     let decoded = bool::deserialize(deserializer).unwrap();
-    assert_eq!(decoded, true);
+    assert!(decoded);
 }
 
 #[test]
 fn bool_false() {
     let deserializer = Deserializer::from_slice(&[3, 2, 0, 0]);
     let decoded = bool::deserialize(deserializer).unwrap();
-    assert_eq!(decoded, false);
+    assert!(!decoded);
 }
 
 #[test]
@@ -83,7 +89,7 @@ fn u64_max() {
     let deserializer =
         Deserializer::from_slice(&[11, 6, 0, 248, 255, 255, 255, 255, 255, 255, 255, 255]);
     let decoded = u64::deserialize(deserializer).unwrap();
-    assert_eq!(decoded, ::std::u64::MAX);
+    assert_eq!(decoded, u64::MAX);
 }
 
 #[test]
@@ -154,7 +160,7 @@ fn i64_min() {
     let deserializer =
         Deserializer::from_slice(&[11, 4, 0, 248, 255, 255, 255, 255, 255, 255, 255, 255]);
     let decoded = i64::deserialize(deserializer).unwrap();
-    assert_eq!(decoded, ::std::i64::MIN);
+    assert_eq!(decoded, i64::MIN);
 }
 
 #[test]
@@ -162,7 +168,7 @@ fn i64_max() {
     let deserializer =
         Deserializer::from_slice(&[11, 4, 0, 248, 255, 255, 255, 255, 255, 255, 255, 254]);
     let decoded = i64::deserialize(deserializer).unwrap();
-    assert_eq!(decoded, ::std::i64::MAX);
+    assert_eq!(decoded, i64::MAX);
 }
 
 #[test]
@@ -235,6 +241,158 @@ fn bytebuf_non_empty() {
     assert_eq!(&*decoded, &[1, 2, 3, 4]);
 }
 
+// a `[]uint` with the elements 1, 2 and 255
+const SLICE_OF_UINT: &[u8] = &[
+    12, 255, 129, 2, 1, 2, 255, 130, 0, 1, 6, 0, 0, 8, 255, 130, 0, 3, 1, 2, 255, 255,
+];
+
+#[test]
+fn vec_of_u8_from_bytes() {
+    let deserializer = Deserializer::from_slice(&[7, 10, 0, 4, 1, 2, 3, 4]);
+    let decoded = <Vec<u8>>::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, &[1, 2, 3, 4]);
+}
+
+#[test]
+fn array_of_u8_from_bytes() {
+    let deserializer = Deserializer::from_slice(&[7, 10, 0, 4, 1, 2, 3, 4]);
+    let decoded = <[u8; 4]>::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, [1, 2, 3, 4]);
+
+    let deserializer = Deserializer::from_slice(&[7, 10, 0, 4, 1, 2, 3, 4]);
+    assert!(<[u8; 3]>::deserialize(deserializer).is_err());
+}
+
+#[test]
+fn bytebuf_from_slice_of_uint() {
+    let deserializer = Deserializer::from_slice(SLICE_OF_UINT);
+    let decoded = ByteBuf::deserialize(deserializer).unwrap();
+    assert_eq!(&*decoded, &[1, 2, 255]);
+}
+
+#[test]
+fn validate_bytes_as_sequence_of_u8() {
+    let mut stream = StreamDeserializer::new(SLICE_OF_UINT);
+    stream.validate::<Vec<u8>>().unwrap();
+    assert!(stream.validate::<String>().is_err());
+
+    let mut stream = StreamDeserializer::new(&[7, 10, 0, 4, 1, 2, 3, 4][..]);
+    stream.validate::<Vec<u64>>().unwrap();
+}
+
+// "a\xffb", which is not valid UTF-8
+const INVALID_UTF8_STRING: &[u8] = &[6, 12, 0, 3, b'a', 0xff, b'b'];
+
+#[test]
+fn invalid_utf8_is_rejected() {
+    let deserializer = Deserializer::from_slice(INVALID_UTF8_STRING);
+    assert!(String::deserialize(deserializer).is_err());
+}
+
+#[test]
+fn invalid_utf8_replaced() {
+    let mut deserializer = Deserializer::from_slice(INVALID_UTF8_STRING);
+    deserializer.set_invalid_utf8(InvalidUtf8::Replace);
+    let decoded = String::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, "a\u{fffd}b");
+}
+
+#[test]
+fn invalid_utf8_as_bytes() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(untagged)]
+    enum Text {
+        Str(String),
+        Bytes(ByteBuf),
+    }
+
+    let mut deserializer = Deserializer::from_slice(INVALID_UTF8_STRING);
+    deserializer.set_invalid_utf8(InvalidUtf8::Bytes);
+    let decoded = Text::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, Text::Bytes(ByteBuf::from(vec![b'a', 0xff, b'b'])));
+
+    let mut deserializer = Deserializer::from_slice(&[6, 12, 0, 3, b'a', b'b', b'c']);
+    deserializer.set_invalid_utf8(InvalidUtf8::Bytes);
+    let decoded = Text::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, Text::Str("abc".to_owned()));
+
+    let mut deserializer = Deserializer::from_slice(INVALID_UTF8_STRING);
+    deserializer.set_invalid_utf8(InvalidUtf8::Bytes);
+    assert!(String::deserialize(deserializer).is_err());
+}
+
+#[test]
+fn strings_as_bytes() {
+    let bytes: &[u8] = &[6, 10, 0, 3, b'a', b'b', b'c'];
+    let string: &[u8] = &[6, 12, 0, 3, b'a', b'b', b'c'];
+
+    assert!(String::deserialize(Deserializer::from_slice(bytes)).is_err());
+    assert!(<Vec<u8>>::deserialize(Deserializer::from_slice(string)).is_err());
+
+    let mut deserializer = Deserializer::from_slice(bytes);
+    deserializer.set_strings_as_bytes(true);
+    assert_eq!(String::deserialize(deserializer).unwrap(), "abc");
+
+    let mut deserializer = Deserializer::from_slice(string);
+    deserializer.set_strings_as_bytes(true);
+    assert_eq!(<Vec<u8>>::deserialize(deserializer).unwrap(), b"abc");
+
+    let mut deserializer = Deserializer::from_slice(string);
+    deserializer.set_strings_as_bytes(true);
+    assert_eq!(&*ByteBuf::deserialize(deserializer).unwrap(), b"abc");
+
+    let mut stream = StreamDeserializer::new(bytes);
+    assert!(stream.validate::<String>().is_err());
+    stream.set_strings_as_bytes(true);
+    stream.validate::<String>().unwrap();
+    assert_eq!(stream.deserialize::<String>().unwrap().unwrap(), "abc");
+}
+
+#[test]
+fn strings_as_bytes_in_struct_fields() {
+    #[derive(Serialize, SchemaSerialize)]
+    #[serde(rename = "Message")]
+    struct GoMessage {
+        #[serde(rename = "Name")]
+        name: ByteBuf,
+        #[serde(rename = "Payload")]
+        payload: String,
+    }
+
+    #[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+    struct Message {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Payload")]
+        payload: Vec<u8>,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .serialize(&GoMessage {
+                name: ByteBuf::from(vec![b'a', 0xff, b'b']),
+                payload: "xyz".to_owned(),
+            })
+            .unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    assert!(stream.validate::<Message>().is_err());
+    stream.set_strings_as_bytes(true);
+    stream.set_invalid_utf8(InvalidUtf8::Replace);
+    stream.validate::<Message>().unwrap();
+    let decoded: Message = stream.deserialize().unwrap().unwrap();
+    assert_eq!(
+        decoded,
+        Message {
+            name: "a\u{fffd}b".to_owned(),
+            payload: b"xyz".to_vec(),
+        }
+    );
+}
+
 #[test]
 fn str_empty() {
     let deserializer = Deserializer::from_slice(&[3, 12, 0, 0]);
@@ -338,8 +496,32 @@ fn map_non_empty() {
         Deserializer::from_slice(include_bytes!("reference/output/map_non_empty.gob"));
     let decoded = <HashMap<String, bool>>::deserialize(deserializer).unwrap();
     assert_eq!(decoded.len(), 2);
-    assert_eq!(decoded["foo"], true);
-    assert_eq!(decoded["bar"], false);
+    assert!(decoded["foo"]);
+    assert!(!decoded["bar"]);
+}
+
+#[test]
+fn map_of_bool_into_set() {
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/map_non_empty.gob"));
+    let decoded = <HashSet<String>>::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, vec!["foo".to_owned()].into_iter().collect());
+}
+
+#[test]
+fn validate_map_of_bool_as_set() {
+    let mut stream =
+        StreamDeserializer::new(&include_bytes!("reference/output/map_non_empty.gob")[..]);
+    stream.validate::<HashSet<String>>().unwrap();
+    assert!(stream.validate::<HashSet<i64>>().is_err());
+}
+
+#[test]
+fn map_of_bool_into_bool_map_set() {
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/map_non_empty.gob"));
+    let decoded = <BoolMapSet<BTreeSet<String>>>::deserialize(deserializer).unwrap();
+    assert_eq!(decoded.0, vec!["foo".to_owned()].into_iter().collect());
 }
 
 #[test]
@@ -358,6 +540,13 @@ fn complex_128() {
     assert_eq!(decoded.1, 2f64);
 }
 
+#[test]
+fn complex_128_type() {
+    let deserializer = Deserializer::from_slice(&[6, 14, 0, 254, 240, 63, 64]);
+    let decoded = Complex128::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, Complex128::new(1.0, 2.0));
+}
+
 #[test]
 fn point_struct() {
     #[derive(Deserialize)]
@@ -376,10 +565,157 @@ fn point_struct() {
     assert_eq!(decoded.y, 33);
 }
 
+#[derive(Deserialize, Debug, PartialEq)]
+struct PlainPoint {
+    x: i64,
+    y: i64,
+}
+
+#[test]
+fn point_struct_with_type_definitions_out_of_band() {
+    let input = include_bytes!("reference/output/point_struct.gob");
+
+    let defs = {
+        let mut stream = StreamDeserializer::new(input.as_ref());
+        stream.skip().unwrap();
+        stream.type_definitions().clone()
+    };
+    let (def_bytes, value_bytes) = input.split_at(defs.as_bytes().len());
+    assert_eq!(defs.as_bytes(), def_bytes);
+
+    let mut defs = TypeDefinitions::from_bytes(def_bytes).unwrap();
+
+    let mut stream = StreamDeserializer::with_type_definitions(value_bytes, defs.clone());
+    let mut deserializer = stream.deserializer().unwrap().unwrap();
+    deserializer.set_field_matching(FieldMatching::CaseInsensitive);
+    let decoded = PlainPoint::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, PlainPoint { x: 22, y: 33 });
+
+    let mut deserializer = Deserializer::from_slice_with_type_definitions(value_bytes, &mut defs);
+    deserializer.set_field_matching(FieldMatching::CaseInsensitive);
+    let decoded = PlainPoint::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, PlainPoint { x: 22, y: 33 });
+}
+
+#[test]
+fn type_definitions_redefined_type() {
+    fn definitions(input: &[u8], defs: TypeDefinitions) -> TypeDefinitions {
+        let mut stream = StreamDeserializer::with_type_definitions(input, defs);
+        stream.skip().unwrap();
+        stream.type_definitions().clone()
+    }
+
+    // both streams define type id 65
+    let point = definitions(
+        include_bytes!("reference/output/point_struct.gob"),
+        TypeDefinitions::new(),
+    );
+    let bool_struct = definitions(
+        include_bytes!("reference/output/bool_struct.gob"),
+        TypeDefinitions::new(),
+    );
+    let redefined = definitions(include_bytes!("reference/output/bool_struct.gob"), point);
+    assert_eq!(redefined.as_bytes(), bool_struct.as_bytes());
+    assert!(TypeDefinitions::from_bytes(redefined.as_bytes()).is_ok());
+}
+
+#[test]
+fn type_definitions_from_bytes_rejects_values() {
+    let input = include_bytes!("reference/output/point_struct.gob");
+    assert!(TypeDefinitions::from_bytes(input).is_err());
+}
+
+#[test]
+fn point_struct_exact_field_matching() {
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/point_struct.gob"));
+    assert!(PlainPoint::deserialize(deserializer).is_err());
+}
+
+#[test]
+fn point_struct_case_insensitive_field_matching() {
+    let mut deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/point_struct.gob"));
+    deserializer.set_field_matching(FieldMatching::CaseInsensitive);
+    let decoded = PlainPoint::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, PlainPoint { x: 22, y: 33 });
+}
+
+#[test]
+fn point_struct_snake_case_field_matching() {
+    let buffer = include_bytes!("reference/output/point_struct.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    stream.set_field_matching(FieldMatching::SnakeCase);
+    let decoded = stream.deserialize::<PlainPoint>().unwrap().unwrap();
+    assert_eq!(decoded, PlainPoint { x: 22, y: 33 });
+}
+
+#[test]
+fn point_struct_snake_case_field_matching_into_map() {
+    let mut deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/point_struct.gob"));
+    deserializer.set_field_matching(FieldMatching::SnakeCase);
+    let decoded = HashMap::<String, i64>::deserialize(deserializer).unwrap();
+    assert_eq!(decoded["x"], 22);
+    assert_eq!(decoded["y"], 33);
+}
+
+#[test]
+fn point_struct_skip_x_without_zero_values() {
+    #[derive(Deserialize)]
+    #[allow(unused)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/point_struct_skip_x.gob"));
+    assert!(Point::deserialize(deserializer).is_err());
+}
+
+#[test]
+fn point_struct_skip_x_with_zero_values() {
+    let mut deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/point_struct_skip_x.gob"));
+    deserializer.set_fill_zero_values(true);
+    deserializer.set_field_matching(FieldMatching::CaseInsensitive);
+    let decoded = PlainPoint::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, PlainPoint { x: 0, y: 42 });
+}
+
+#[test]
+fn struct_with_zero_values() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Struct {
+        #[serde(rename = "Var1")]
+        var1: bool,
+        #[serde(rename = "Var2")]
+        var2: Option<i64>,
+        #[serde(rename = "Var3")]
+        var3: Option<String>,
+    }
+
+    let buffer = include_bytes!("reference/output/enum_with_newtype_variants.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+    stream.set_fill_zero_values(true);
+    let decoded = stream.deserialize::<Struct>().unwrap().unwrap();
+    assert_eq!(
+        decoded,
+        Struct {
+            var1: false,
+            var2: Some(42),
+            var3: None,
+        }
+    );
+}
+
 #[test]
 fn unit_struct() {
     #[derive(Deserialize)]
-    struct EmptyStruct {};
+    struct EmptyStruct {}
 
     let deserializer =
         Deserializer::from_slice(include_bytes!("reference/output/empty_struct.gob"));
@@ -435,6 +771,97 @@ fn enum_with_struct_variants_and_external_tags() {
     assert_eq!(decoded, Enum::V2 { bar: 42, baz: 1234 });
 }
 
+#[test]
+fn type_name_of_struct() {
+    let mut deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/point_struct.gob"));
+    assert_eq!(deserializer.type_name().unwrap(), Some("Point"));
+}
+
+#[test]
+fn type_name_of_builtin() {
+    let mut deserializer = Deserializer::from_slice(&[3, 6, 0, 42]);
+    assert_eq!(deserializer.type_name().unwrap(), Some("uint"));
+    let decoded = u64::deserialize(deserializer).unwrap();
+    assert_eq!(decoded, 42);
+}
+
+#[test]
+fn dispatch_heterogeneous_stream() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct BoolStruct {
+        #[serde(rename = "V")]
+        v: bool,
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Message {
+        Point(Point),
+        Bool(BoolStruct),
+    }
+
+    let mut dispatcher = Dispatcher::new();
+    dispatcher
+        .register("Point", Message::Point)
+        .register("BoolStruct", Message::Bool);
+
+    let buffer = include_bytes!("reference/output/point_and_bool_struct.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+
+    assert_eq!(
+        stream.dispatch(&dispatcher).unwrap(),
+        Some(Message::Point(Point { x: 22, y: 33 }))
+    );
+    assert_eq!(
+        stream.dispatch(&dispatcher).unwrap(),
+        Some(Message::Bool(BoolStruct { v: true }))
+    );
+    assert_eq!(stream.dispatch(&dispatcher).unwrap(), None);
+}
+
+#[test]
+fn dispatch_unregistered_type() {
+    let dispatcher = Dispatcher::<()>::new();
+    let deserializer =
+        Deserializer::from_slice(include_bytes!("reference/output/point_struct.gob"));
+    assert!(dispatcher.dispatch(deserializer).is_err());
+}
+
+#[test]
+fn peek_and_skip() {
+    let buffer = include_bytes!("reference/output/point_and_bool_struct.gob");
+    let mut stream = StreamDeserializer::new(Cursor::new(buffer.as_ref()));
+
+    {
+        let header = stream.peek().unwrap().unwrap();
+        assert_eq!(header.type_name(), Some("Point"));
+        assert_eq!(header.payload_len(), 5);
+    }
+    assert_eq!(stream.peek().unwrap().unwrap().type_name(), Some("Point"));
+    assert!(stream.skip().unwrap());
+
+    assert_eq!(
+        stream.peek().unwrap().unwrap().type_name(),
+        Some("BoolStruct")
+    );
+    let decoded = stream
+        .deserialize::<HashMap<String, bool>>()
+        .unwrap()
+        .unwrap();
+    assert!(decoded["V"]);
+
+    assert!(stream.peek().unwrap().is_none());
+    assert!(!stream.skip().unwrap());
+}
+
 #[test]
 fn unit_from_any() {
     let buffer = include_bytes!("reference/output/non_empty_values.gob");
@@ -493,3 +920,79 @@ quickcheck! {
         block!(stream.deserialize::<()>()).unwrap().is_none()
     }
 }
+
+#[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+struct ValidPoint {
+    #[serde(rename = "X")]
+    x: i64,
+    #[serde(rename = "Y")]
+    y: i32,
+}
+
+#[derive(Serialize, Deserialize, SchemaSerialize)]
+struct InvalidPoint {
+    #[serde(rename = "X")]
+    x: String,
+    #[serde(rename = "Z")]
+    z: i64,
+}
+
+#[test]
+fn validate_matching_type() {
+    let input = include_bytes!("reference/output/point_struct.gob");
+    let mut stream = StreamDeserializer::new(input.as_ref());
+    stream.validate::<ValidPoint>().unwrap();
+    let decoded = stream.deserialize::<ValidPoint>().unwrap().unwrap();
+    assert_eq!(decoded, ValidPoint { x: 22, y: 33 });
+}
+
+#[test]
+fn validate_matching_type_with_field_matching() {
+    #[derive(Serialize, SchemaSerialize)]
+    struct SnakePoint {
+        x: i64,
+        y: i64,
+    }
+
+    let input = include_bytes!("reference/output/point_struct.gob");
+    let mut stream = StreamDeserializer::new(input.as_ref());
+    assert!(stream.validate::<SnakePoint>().is_err());
+    stream.set_field_matching(FieldMatching::CaseInsensitive);
+    stream.validate::<SnakePoint>().unwrap();
+
+    #[derive(Serialize, SchemaSerialize)]
+    struct SuffixedPoint {
+        #[serde(rename = "x_")]
+        x: i64,
+        #[serde(rename = "y_")]
+        y: i64,
+    }
+
+    assert!(stream.validate::<SuffixedPoint>().is_err());
+    stream.set_field_matching(FieldMatching::SnakeCase);
+    stream.validate::<SuffixedPoint>().unwrap();
+}
+
+#[test]
+fn validate_reports_all_incompatibilities() {
+    let input = include_bytes!("reference/output/point_struct.gob");
+    let mut stream = StreamDeserializer::new(input.as_ref());
+    let err = stream.validate::<InvalidPoint>().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Deserialize);
+    let message = err.to_string();
+    assert!(message.contains("Point.X: sent as int, but declared as string"));
+    assert!(message.contains("Point.Y: field is sent but not declared"));
+    assert!(message.contains("Point.Z: field is not defined by the sender"));
+}
+
+#[test]
+fn validate_cached() {
+    let buffer = include_bytes!("reference/output/point_and_bool_struct.gob");
+    let mut stream = StreamDeserializer::new(buffer.as_ref());
+    stream.set_cache_validation(true);
+    stream.validate::<ValidPoint>().unwrap();
+    stream.validate::<ValidPoint>().unwrap();
+    assert!(stream.validate::<InvalidPoint>().is_err());
+    assert!(stream.skip().unwrap());
+    assert!(stream.validate::<ValidPoint>().is_err());
+}
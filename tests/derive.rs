@@ -0,0 +1,639 @@
+extern crate gob;
+#[macro_use]
+extern crate gob_derive;
+extern crate serde;
+extern crate serde_bytes;
+#[macro_use]
+extern crate serde_derive;
+#[cfg(feature = "uuid")]
+extern crate uuid;
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use gob::{GobSchema, StreamDeserializer, StreamSerializer};
+use serde_bytes::ByteBuf;
+
+#[test]
+fn container_and_field_names() {
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    #[gob(name = "Point")]
+    struct Coordinates {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Coordinates { x: 22, y: 33 }).unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/point_struct.gob").as_ref()
+    );
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Coordinates = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, Coordinates { x: 22, y: 33 });
+}
+
+#[test]
+fn serde_rename_all() {
+    #[derive(Serialize, GobSchema)]
+    #[serde(rename_all = "PascalCase")]
+    struct Point {
+        x: i64,
+        y: i64,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Point { x: 22, y: 33 }).unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/point_struct.gob").as_ref()
+    );
+}
+
+#[test]
+fn serde_skip_serializing() {
+    #[derive(Serialize, GobSchema)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(skip_serializing)]
+        #[allow(dead_code)]
+        label: String,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .serialize(&Point {
+                x: 22,
+                label: "origin".to_owned(),
+                y: 33,
+            })
+            .unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/point_struct.gob").as_ref()
+    );
+}
+
+#[test]
+fn gob_skip() {
+    #[derive(Serialize, GobSchema)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[gob(skip)]
+        label: String,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .serialize(&Point {
+                x: 22,
+                label: "origin".to_owned(),
+                y: 33,
+            })
+            .unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/point_struct.gob").as_ref()
+    );
+}
+
+#[test]
+fn opaque_field() {
+    #[derive(Serialize, GobSchema)]
+    struct Event {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "At", with = "serde_bytes")]
+        #[gob(opaque, name = "Time")]
+        at: Vec<u8>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct DecodedEvent {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "At", with = "serde_bytes")]
+        at: Vec<u8>,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .serialize(&Event {
+                name: "launch".to_owned(),
+                at: vec![1, 0, 0, 0, 14, 187, 75, 55, 229, 0, 0, 0, 0, 255, 255],
+            })
+            .unwrap();
+    }
+
+    // id 65 is defined as a BinaryMarshaler named "Time"
+    assert_eq!(
+        &buffer[..17],
+        &[16, 255, 129, 6, 1, 1, 4, 84, 105, 109, 101, 1, 255, 130, 0, 0, 0]
+    );
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let event: DecodedEvent = stream.deserialize().unwrap().unwrap();
+    assert_eq!(
+        event,
+        DecodedEvent {
+            name: "launch".to_owned(),
+            at: vec![1, 0, 0, 0, 14, 187, 75, 55, 229, 0, 0, 0, 0, 255, 255],
+        }
+    );
+}
+
+#[test]
+fn opaque_marshaler_kinds() {
+    #[derive(Serialize, GobSchema)]
+    struct Event {
+        #[serde(rename = "At", with = "serde_bytes")]
+        #[gob(opaque = "gob", name = "Time")]
+        at: Vec<u8>,
+    }
+
+    #[derive(Serialize, GobSchema)]
+    struct Host {
+        #[serde(rename = "Addr", with = "serde_bytes")]
+        #[gob(opaque = "text", name = "IP")]
+        addr: Vec<u8>,
+    }
+
+    // id 65 is defined as a GobEncoder, then as a TextMarshaler
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&Event { at: vec![1] })
+        .unwrap();
+    assert_eq!(&buffer[..4], &[16, 255, 129, 5]);
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&Host {
+            addr: b"10.0.0.1".to_vec(),
+        })
+        .unwrap();
+    assert_eq!(&buffer[..4], &[14, 255, 129, 7]);
+}
+
+#[test]
+fn nil_interface_field() {
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Message {
+        #[serde(rename = "Body")]
+        body: String,
+        #[serde(rename = "Payload")]
+        #[gob(interface)]
+        payload: Option<String>,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .serialize(&Message {
+                body: "hello".to_owned(),
+                payload: None,
+            })
+            .unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let message: Message = stream.deserialize().unwrap().unwrap();
+    assert_eq!(
+        message,
+        Message {
+            body: "hello".to_owned(),
+            payload: None,
+        }
+    );
+}
+
+#[test]
+fn non_nil_interface_field_is_rejected() {
+    #[derive(Serialize, GobSchema)]
+    struct Message {
+        #[gob(interface)]
+        payload: Option<String>,
+    }
+
+    let mut buffer = Vec::new();
+    let mut stream = StreamSerializer::new_with_write(&mut buffer);
+    assert!(stream
+        .serialize(&Message {
+            payload: Some("hello".to_owned()),
+        })
+        .is_err());
+}
+
+#[test]
+fn enum_variant_names() {
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    #[gob(name = "Shape")]
+    enum Figure {
+        UnitCircle(i64),
+        #[serde(rename = "Rect")]
+        Rectangle {
+            width: i64,
+            height: i64,
+        },
+    }
+
+    let rectangle = Figure::Rectangle {
+        width: 4,
+        height: 2,
+    };
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Figure::UnitCircle(3)).unwrap();
+        stream.serialize(&rectangle).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let figure: Figure = stream.deserialize().unwrap().unwrap();
+    assert_eq!(figure, Figure::UnitCircle(3));
+    let figure: Figure = stream.deserialize().unwrap().unwrap();
+    assert_eq!(figure, rectangle);
+
+    assert!(buffer.windows(5).any(|w| w == b"Shape"));
+    assert!(buffer.windows(11).any(|w| w == b"unit_circle"));
+    assert!(buffer.windows(4).any(|w| w == b"Rect"));
+}
+
+#[test]
+fn std_types() {
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Inventory<'a> {
+        owner: Arc<String>,
+        shelf: Rc<u64>,
+        label: Box<str>,
+        note: Cow<'a, str>,
+        counts: BTreeMap<String, i64>,
+        tags: HashSet<String>,
+        queue: VecDeque<i64>,
+        corners: [i64; 4],
+        position: (i64, i64),
+        entry: (u64, String),
+        checksum: ByteBuf,
+    }
+
+    let mut counts = BTreeMap::new();
+    counts.insert("apples".to_owned(), 3);
+    let inventory = Inventory {
+        owner: Arc::new("warehouse".to_owned()),
+        shelf: Rc::new(7),
+        label: "fruit".into(),
+        note: Cow::Borrowed("fresh"),
+        counts,
+        tags: vec!["food".to_owned()].into_iter().collect(),
+        queue: vec![1, 2].into_iter().collect(),
+        corners: [1, 2, 3, 4],
+        position: (5, 6),
+        entry: (1, "first".to_owned()),
+        checksum: ByteBuf::from(vec![0xca, 0xfe]),
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&inventory).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Inventory = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, inventory);
+}
+
+#[test]
+fn large_array() {
+    // serde does not implement `Serialize` for arrays this large, so the
+    // value is sent as a slice under the id of the array type
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        let id = <[i64; 64] as GobSchema>::register(stream.schema_mut()).unwrap();
+        stream
+            .serialize_with_type_id(id, &vec![7i64; 64][..])
+            .unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Vec<i64> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, vec![7; 64]);
+}
+
+#[test]
+fn generic_struct() {
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Tagged<T> {
+        tag: String,
+        value: Arc<T>,
+    }
+
+    let tagged = Tagged {
+        tag: "answer".to_owned(),
+        value: Arc::new(vec![42i64]),
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&tagged).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Tagged<Vec<i64>> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, tagged);
+}
+
+#[test]
+fn int_enum() {
+    #[derive(Serialize, Deserialize, GobSchema, Clone, Copy, Debug, PartialEq)]
+    #[serde(into = "i64", try_from = "i64")]
+    #[gob(repr = "int")]
+    enum Color {
+        Red,
+        Green,
+        Blue = 4,
+    }
+
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Pixel {
+        #[serde(rename = "Color")]
+        color: Color,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Color::Green).unwrap();
+        stream.serialize(&Color::Blue).unwrap();
+        stream.serialize(&7i64).unwrap();
+    }
+    assert_eq!(&buffer[..8], &[3, 4, 0, 2, 3, 4, 0, 8]);
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    assert_eq!(stream.deserialize::<Color>().unwrap(), Some(Color::Green));
+    assert_eq!(stream.deserialize::<Color>().unwrap(), Some(Color::Blue));
+    let err = stream.deserialize::<Color>().unwrap_err();
+    assert!(err.to_string().contains("unknown value 7 for enum Color"));
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Pixel { color: Color::Blue }).unwrap();
+        stream.serialize(&Pixel { color: Color::Red }).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    stream.set_fill_zero_values(true);
+    let pixel: Pixel = stream.deserialize().unwrap().unwrap();
+    assert_eq!(pixel, Pixel { color: Color::Blue });
+    // the zero value is omitted, like Go does
+    let pixel: Pixel = stream.deserialize().unwrap().unwrap();
+    assert_eq!(pixel, Pixel { color: Color::Red });
+}
+
+#[test]
+fn flattened_fields() {
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Position {
+        x: i64,
+        y: i64,
+    }
+
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Label {
+        text: String,
+    }
+
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Marker {
+        id: u64,
+        #[serde(flatten)]
+        position: Position,
+        #[serde(flatten)]
+        label: Option<Label>,
+    }
+
+    // the struct Go sees
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct GoMarker {
+        id: u64,
+        x: i64,
+        y: i64,
+        text: Option<String>,
+    }
+
+    let markers = [
+        Marker {
+            id: 1,
+            position: Position { x: 2, y: 3 },
+            label: Some(Label {
+                text: "here".into(),
+            }),
+        },
+        Marker {
+            id: 4,
+            position: Position { x: 5, y: 6 },
+            label: None,
+        },
+    ];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for marker in &markers {
+            stream.serialize(marker).unwrap();
+        }
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let marker: GoMarker = stream.deserialize().unwrap().unwrap();
+    assert_eq!(
+        marker,
+        GoMarker {
+            id: 1,
+            x: 2,
+            y: 3,
+            text: Some("here".into()),
+        }
+    );
+    let marker: GoMarker = stream.deserialize().unwrap().unwrap();
+    assert_eq!(
+        marker,
+        GoMarker {
+            id: 4,
+            x: 5,
+            y: 6,
+            text: None,
+        }
+    );
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    for marker in &markers {
+        assert_eq!(
+            stream.deserialize::<Marker>().unwrap().as_ref(),
+            Some(marker)
+        );
+    }
+}
+
+#[test]
+fn internally_tagged_enum() {
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Position {
+        x: i64,
+        y: i64,
+    }
+
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    #[serde(tag = "kind")]
+    enum Shape {
+        Circle { radius: f64 },
+        Rect { width: f64, height: f64 },
+        Point(Position),
+        Empty,
+    }
+
+    // the struct Go sees
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct GoShape {
+        kind: String,
+        radius: Option<f64>,
+        width: Option<f64>,
+        height: Option<f64>,
+        x: Option<i64>,
+        y: Option<i64>,
+    }
+
+    let shapes = [
+        Shape::Circle { radius: 1.5 },
+        Shape::Rect {
+            width: 2.0,
+            height: 3.0,
+        },
+        Shape::Point(Position { x: 4, y: 5 }),
+        Shape::Empty,
+    ];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for shape in &shapes {
+            stream.serialize(shape).unwrap();
+        }
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let shape: GoShape = stream.deserialize().unwrap().unwrap();
+    assert_eq!(
+        shape,
+        GoShape {
+            kind: "Circle".into(),
+            radius: Some(1.5),
+            width: None,
+            height: None,
+            x: None,
+            y: None,
+        }
+    );
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    for shape in &shapes {
+        assert_eq!(stream.deserialize::<Shape>().unwrap().as_ref(), Some(shape));
+    }
+}
+
+#[test]
+fn adjacently_tagged_enum() {
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Position {
+        x: i64,
+        y: i64,
+    }
+
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    #[serde(tag = "type", content = "data")]
+    enum Message {
+        Text { body: String },
+        Move(Position),
+        Quit,
+    }
+
+    let messages = [
+        Message::Text {
+            body: "hello".into(),
+        },
+        Message::Move(Position { x: 1, y: 2 }),
+        Message::Quit,
+    ];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        for message in &messages {
+            stream.serialize(message).unwrap();
+        }
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    for message in &messages {
+        assert_eq!(
+            stream.deserialize::<Message>().unwrap().as_ref(),
+            Some(message)
+        );
+    }
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn uuid_as_string() {
+    #[derive(Serialize, Deserialize, GobSchema, Debug, PartialEq)]
+    struct Order {
+        id: uuid::Uuid,
+    }
+
+    let order = Order {
+        id: uuid::Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap(),
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&order).unwrap();
+    }
+    assert!(buffer
+        .windows(36)
+        .any(|w| w == b"67e55044-10b1-426f-9247-bb680e5fe0c8"));
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Order = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, order);
+}
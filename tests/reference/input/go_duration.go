@@ -0,0 +1,12 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+	"time"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(1500 * time.Millisecond)
+}
@@ -0,0 +1,12 @@
+package main
+
+import (
+	"encoding/gob"
+	"net"
+	"os"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(net.ParseIP("10.0.0.1"))
+}
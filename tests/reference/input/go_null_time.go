@@ -0,0 +1,21 @@
+package main
+
+import (
+	"database/sql"
+	"encoding/gob"
+	"os"
+	"time"
+)
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(sql.NullTime{
+		Time:  time.Date(2009, time.November, 10, 23, 0, 0, 123456789, time.UTC),
+		Valid: true,
+	})
+	enc.Encode(sql.NullTime{
+		Time:  time.Date(2009, time.November, 10, 23, 0, 0, 0, time.FixedZone("IST", 5*60*60+30*60)),
+		Valid: true,
+	})
+	enc.Encode(sql.NullTime{})
+}
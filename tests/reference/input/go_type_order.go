@@ -0,0 +1,22 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Point struct {
+	X int
+	Y int
+}
+
+type Shape struct {
+	Name   string
+	Points []Point
+	Tags   []string
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Shape{Name: "tri", Points: []Point{{X: 1, Y: 2}}, Tags: []string{"a"}})
+}
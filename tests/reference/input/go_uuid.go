@@ -0,0 +1,22 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+// UUID is encoded like github.com/google/uuid.UUID, which marshals
+// itself through MarshalBinary as its 16 bytes.
+type UUID [16]byte
+
+func (u UUID) MarshalBinary() ([]byte, error) {
+	return u[:], nil
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(UUID{
+		0x67, 0xe5, 0x50, 0x44, 0x10, 0xb1, 0x42, 0x6f,
+		0x92, 0x47, 0xbb, 0x68, 0x0e, 0x5f, 0xe0, 0xc8,
+	})
+}
@@ -0,0 +1,21 @@
+package main
+
+import (
+	"encoding/gob"
+	"os"
+)
+
+type Point struct {
+	X int64
+	Y int64
+}
+
+type BoolStruct struct {
+	V bool
+}
+
+func main() {
+	var enc = gob.NewEncoder(os.Stdout)
+	enc.Encode(Point{X: 22, Y: 33})
+	enc.Encode(BoolStruct{V: true})
+}
@@ -1,4 +1,8 @@
 extern crate gob;
+#[cfg(feature = "num-bigint")]
+extern crate num_bigint;
+#[cfg(feature = "num-bigint")]
+extern crate num_rational;
 extern crate serde;
 extern crate serde_bytes;
 #[macro_use]
@@ -6,11 +10,24 @@ extern crate serde_derive;
 extern crate serde_schema;
 #[macro_use]
 extern crate serde_schema_derive;
+#[cfg(feature = "uuid")]
+extern crate uuid;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use std::time::Duration;
 
-use gob::StreamSerializer;
-use serde_bytes::Bytes;
+use gob::go;
+use gob::ser::{
+    ByteSequences, FieldNaming, GoCompatibility, GoIssue, NoneElements, Schema, WideIntegers,
+    ZeroValues,
+};
+use gob::{BigFloat, BigInt, BigRat};
+use gob::{BoolMapSet, StructMapSet};
+use gob::{Complex128, Complex64, StreamDeserializer, StreamSerializer};
+use gob::{NullFloat64, NullInt64, NullString, NullTime};
+use serde_bytes::{ByteBuf, Bytes};
 
 #[test]
 fn bool_true() {
@@ -97,7 +114,7 @@ fn u64_max() {
     let mut buffer = Vec::new();
     {
         let mut stream = StreamSerializer::new_with_write(&mut buffer);
-        stream.serialize(&::std::u64::MAX).unwrap();
+        stream.serialize(&u64::MAX).unwrap();
     }
     assert_eq!(
         buffer,
@@ -190,7 +207,7 @@ fn i64_min() {
     let mut buffer = Vec::new();
     {
         let mut stream = StreamSerializer::new_with_write(&mut buffer);
-        stream.serialize(&::std::i64::MIN).unwrap();
+        stream.serialize(&i64::MIN).unwrap();
     }
     assert_eq!(
         buffer,
@@ -203,7 +220,7 @@ fn i64_max() {
     let mut buffer = Vec::new();
     {
         let mut stream = StreamSerializer::new_with_write(&mut buffer);
-        stream.serialize(&::std::i64::MAX).unwrap();
+        stream.serialize(&i64::MAX).unwrap();
     }
     assert_eq!(
         buffer,
@@ -291,6 +308,307 @@ fn bytes_non_empty() {
     assert_eq!(buffer, &[7, 10, 0, 4, 1, 2, 3, 4]);
 }
 
+#[test]
+fn vec_of_u8_as_bytes() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&vec![1u8, 2, 3, 4]).unwrap();
+    }
+    assert_eq!(buffer, &[7, 10, 0, 4, 1, 2, 3, 4]);
+}
+
+#[test]
+fn array_of_u8_as_bytes() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&[1u8, 2, 3, 4]).unwrap();
+        stream.serialize_inferred(&vec![1u8, 2, 3, 4]).unwrap();
+    }
+    assert_eq!(buffer, &[7, 10, 0, 4, 1, 2, 3, 4, 7, 10, 0, 4, 1, 2, 3, 4]);
+}
+
+#[test]
+fn vec_of_u8_as_uints() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.schema_mut().set_byte_sequences(ByteSequences::Uints);
+        stream.serialize(&vec![1u8, 2, 255]).unwrap();
+    }
+    assert_eq!(
+        buffer,
+        &[12, 255, 129, 2, 1, 2, 255, 130, 0, 1, 6, 0, 0, 8, 255, 130, 0, 3, 1, 2, 255, 255]
+    );
+}
+
+#[test]
+fn none_elements_are_rejected() {
+    let mut buffer = Vec::new();
+    let mut stream = StreamSerializer::new_with_write(&mut buffer);
+    let err = stream.serialize(&vec![Some(1i64), None]).unwrap_err();
+    assert!(err.to_string().contains("slice or array"));
+
+    let mut map = BTreeMap::new();
+    map.insert("a".to_owned(), None::<String>);
+    let err = stream.serialize(&map).unwrap_err();
+    assert!(err.to_string().contains("map"));
+
+    stream.serialize(&vec![Some(1i64), Some(0)]).unwrap();
+    drop(stream);
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Vec<i64> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, vec![1, 0]);
+}
+
+#[test]
+fn none_elements_as_zero_values() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .schema_mut()
+            .set_none_elements(NoneElements::ZeroValue);
+        stream.serialize(&vec![Some(1i64), None]).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Vec<Option<i64>> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, vec![Some(1), Some(0)]);
+}
+
+#[test]
+fn none_elements_as_nullable() {
+    #[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+    struct Reading {
+        #[serde(rename = "Values")]
+        values: Vec<Option<i64>>,
+        #[serde(rename = "Labels")]
+        labels: BTreeMap<String, Option<String>>,
+        #[serde(rename = "Unit")]
+        unit: Option<String>,
+    }
+
+    // the Go view, `[]sql.Null[int]`
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Null {
+        #[serde(rename = "V", default)]
+        v: i64,
+        #[serde(rename = "Valid", default)]
+        valid: bool,
+    }
+
+    let mut labels = BTreeMap::new();
+    labels.insert("a".to_owned(), Some("x".to_owned()));
+    labels.insert("b".to_owned(), None);
+    let readings = [
+        Reading {
+            values: vec![Some(1), None, Some(0)],
+            labels,
+            unit: None,
+        },
+        Reading {
+            values: vec![],
+            labels: BTreeMap::new(),
+            unit: Some("".to_owned()),
+        },
+    ];
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .schema_mut()
+            .set_none_elements(NoneElements::Nullable);
+        for reading in &readings {
+            stream.serialize(reading).unwrap();
+        }
+        stream.serialize(&vec![Some(2i64), None]).unwrap();
+        stream.serialize(&vec![Some(2i64), None]).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    stream.set_fill_zero_values(true);
+    for reading in &readings {
+        assert_eq!(
+            stream.deserialize::<Reading>().unwrap().as_ref(),
+            Some(reading)
+        );
+    }
+    let decoded: Vec<Option<i64>> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, vec![Some(2), None]);
+    let decoded: Vec<Null> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(
+        decoded,
+        vec![Null { v: 2, valid: true }, Null { v: 0, valid: false }]
+    );
+}
+
+#[test]
+fn sql_null_types() {
+    #[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+    struct Row {
+        #[serde(rename = "Name")]
+        name: NullString,
+        #[serde(rename = "Age")]
+        age: NullInt64,
+        #[serde(rename = "Scores")]
+        scores: Vec<NullFloat64>,
+        #[serde(rename = "Deleted")]
+        deleted: NullTime,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct RowOptions {
+        #[serde(rename = "Name")]
+        name: Option<String>,
+        #[serde(rename = "Age")]
+        age: Option<i64>,
+        #[serde(rename = "Scores")]
+        scores: Vec<Option<f64>>,
+    }
+
+    // the Go view, `sql.NullString`
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct NullStringView {
+        #[serde(rename = "String", default)]
+        string: String,
+        #[serde(rename = "Valid", default)]
+        valid: bool,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct RowView {
+        #[serde(rename = "Name")]
+        name: NullStringView,
+    }
+
+    let row = Row {
+        name: NullString(Some("".to_owned())),
+        age: NullInt64(None),
+        scores: vec![NullFloat64(Some(1.5)), NullFloat64(None)],
+        deleted: NullTime(Some(go::Time {
+            secs: -1,
+            nanos: 5,
+            offset: Some(-3630),
+        })),
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&row).unwrap();
+        stream.serialize(&row).unwrap();
+        stream.serialize(&row).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    assert_eq!(stream.deserialize::<Row>().unwrap(), Some(row));
+    assert_eq!(
+        stream.deserialize::<RowOptions>().unwrap(),
+        Some(RowOptions {
+            name: Some("".to_owned()),
+            age: None,
+            scores: vec![Some(1.5), None],
+        })
+    );
+    assert_eq!(
+        stream.deserialize::<RowView>().unwrap(),
+        Some(RowView {
+            name: NullStringView {
+                string: "".to_owned(),
+                valid: true,
+            },
+        })
+    );
+}
+
+#[test]
+fn sql_null_time() {
+    let times = [
+        NullTime(Some(go::Time {
+            secs: 1257894000,
+            nanos: 123456789,
+            offset: None,
+        })),
+        NullTime(Some(go::Time {
+            secs: 1257894000 - 19800,
+            nanos: 0,
+            offset: Some(19800),
+        })),
+        NullTime(None),
+    ];
+    let fixture = include_bytes!("reference/output/go_null_time.gob");
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.set_go_type_order("sql").unwrap();
+        for time in &times {
+            stream.serialize(time).unwrap();
+        }
+    }
+    // time.Time is defined as a GobEncoder, as Go checks
+    assert_eq!(buffer, fixture.as_ref());
+
+    let mut stream = StreamDeserializer::new(fixture.as_ref());
+    for time in &times {
+        assert_eq!(
+            stream.deserialize::<NullTime>().unwrap().as_ref(),
+            Some(time)
+        );
+    }
+}
+
+#[test]
+fn byte_fields() {
+    #[derive(Serialize, SchemaSerialize)]
+    struct Packet {
+        #[serde(rename = "Kind")]
+        kind: u8,
+        #[serde(rename = "Header")]
+        header: [u8; 2],
+        #[serde(rename = "Payload")]
+        payload: Vec<u8>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct DecodedPacket {
+        #[serde(rename = "Kind")]
+        kind: u64,
+        #[serde(rename = "Header")]
+        header: ByteBuf,
+        #[serde(rename = "Payload")]
+        payload: Vec<u8>,
+    }
+
+    let packet = Packet {
+        kind: 7,
+        header: [0xca, 0xfe],
+        payload: vec![1, 2, 3],
+    };
+    let expected = DecodedPacket {
+        kind: 7,
+        header: ByteBuf::from(vec![0xca, 0xfe]),
+        payload: vec![1, 2, 3],
+    };
+
+    for &byte_sequences in &[ByteSequences::Bytes, ByteSequences::Uints] {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamSerializer::new_with_write(&mut buffer);
+            stream.schema_mut().set_byte_sequences(byte_sequences);
+            stream.serialize(&packet).unwrap();
+        }
+
+        let mut stream = StreamDeserializer::new(buffer.as_slice());
+        let decoded: DecodedPacket = stream.deserialize().unwrap().unwrap();
+        assert_eq!(decoded, expected);
+    }
+}
+
 #[test]
 fn str_empty() {
     let mut buffer = Vec::new();
@@ -311,6 +629,96 @@ fn str_non_empty() {
     assert_eq!(buffer, &[6, 12, 0, 3, 102, 111, 111]);
 }
 
+#[test]
+fn complex_64() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Complex64::new(1.0, 2.0)).unwrap();
+    }
+    assert_eq!(buffer, &[6, 14, 0, 254, 240, 63, 64]);
+}
+
+#[test]
+fn complex_128() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Complex128::new(1.0, 2.0)).unwrap();
+    }
+    assert_eq!(buffer, &[6, 14, 0, 254, 240, 63, 64]);
+}
+
+#[test]
+fn complex_128_in_struct_slice_and_map() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize, SchemaSerialize)]
+    struct Signal {
+        #[serde(rename = "Gain")]
+        gain: Complex128,
+        #[serde(rename = "Samples")]
+        samples: Vec<Complex128>,
+        #[serde(rename = "Poles")]
+        poles: BTreeMap<String, Complex128>,
+    }
+
+    let mut poles = BTreeMap::new();
+    poles.insert("a".to_owned(), Complex128::new(-0.5, 0.25));
+    let signal = Signal {
+        gain: Complex128::new(1.0, -1.0),
+        samples: vec![Complex128::new(0.0, 1.0), Complex128::new(3.5, 0.0)],
+        poles,
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&signal).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded = stream.deserialize::<Signal>().unwrap().unwrap();
+    assert_eq!(decoded, signal);
+}
+
+#[test]
+fn complex_zero_field_is_omitted() {
+    #[derive(Serialize, SchemaSerialize)]
+    struct Signal {
+        #[serde(rename = "Gain")]
+        gain: Complex128,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .serialize(&Signal {
+                gain: Complex128::new(0.0, -0.0),
+            })
+            .unwrap();
+    }
+    assert_eq!(&buffer[buffer.len() - 4..], &[3, 255, 130, 0]);
+}
+
+#[test]
+fn float_zero_field_is_sent() {
+    #[derive(Serialize, SchemaSerialize)]
+    struct Reading {
+        #[serde(rename = "Value")]
+        value: f64,
+    }
+
+    // the field delta, the value and the end of the struct
+    for &(value, tail) in &[(0.0, &[1, 0, 0][..]), (-0.0, &[1, 255, 128, 0][..])] {
+        let mut buffer = Vec::new();
+        {
+            let mut stream = StreamSerializer::new_with_write(&mut buffer);
+            stream.serialize(&Reading { value }).unwrap();
+        }
+        assert!(buffer.ends_with(tail));
+    }
+}
+
 #[test]
 fn vec_of_bool_to_empty_slice() {
     let mut buffer = Vec::new();
@@ -454,6 +862,74 @@ fn point_struct_skip_x() {
     );
 }
 
+#[test]
+fn point_struct_pascal_case_field_naming() {
+    #[derive(Serialize, SchemaSerialize)]
+    #[serde(rename = "Point")]
+    struct PlainPoint {
+        x: i64,
+        y: i64,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .schema_mut()
+            .set_field_naming(FieldNaming::PascalCase);
+        stream.serialize(&PlainPoint { x: 22, y: 33 }).unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/point_struct.gob").as_ref()
+    );
+}
+
+#[test]
+fn point_struct_skip_x_emit_zero_values() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.set_zero_values(ZeroValues::Emit);
+        stream.serialize(&Point { x: 0, y: 42 }).unwrap();
+    }
+    let mut expected = include_bytes!("reference/output/point_struct_skip_x.gob")[..32].to_vec();
+    expected.extend_from_slice(&[7, 255, 130, 1, 0, 1, 84, 0]);
+    assert_eq!(buffer, expected);
+}
+
+#[test]
+fn option_point_struct_emit_some_zero_values() {
+    #[derive(Serialize, SchemaSerialize)]
+    #[serde(rename = "Point")]
+    struct OptionPoint {
+        #[serde(rename = "X")]
+        x: Option<i64>,
+        #[serde(rename = "Y")]
+        y: Option<i64>,
+    }
+
+    let value = OptionPoint {
+        x: Some(0),
+        y: None,
+    };
+
+    let mut omitted = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut omitted);
+        stream.serialize(&value).unwrap();
+    }
+    assert_eq!(&omitted[32..], &[3, 255, 130, 0]);
+
+    let mut emitted = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut emitted);
+        stream.set_zero_values(ZeroValues::EmitSome);
+        stream.serialize(&value).unwrap();
+    }
+    assert_eq!(&emitted[32..], &[5, 255, 130, 1, 0, 0]);
+}
+
 #[derive(Serialize, SchemaSerialize)]
 struct BoolStruct {
     #[serde(rename = "V")]
@@ -473,6 +949,807 @@ fn bool_struct() {
     );
 }
 
+#[test]
+fn shared_schema() {
+    use serde_schema::SchemaSerialize;
+
+    let mut schema = Schema::new();
+    Point::schema_register(&mut schema).unwrap();
+    BoolStruct::schema_register(&mut schema).unwrap();
+    let schema = Arc::new(schema);
+
+    let mut buffer1 = Vec::new();
+    let mut buffer2 = Vec::new();
+    {
+        let mut stream1 = StreamSerializer::new_with_write(&mut buffer1);
+        let mut stream2 = StreamSerializer::new_with_write(&mut buffer2);
+        stream1.set_shared_schema(schema.clone()).unwrap();
+        stream2.set_shared_schema(schema.clone()).unwrap();
+        stream1.serialize(&Point { x: 22, y: 33 }).unwrap();
+        stream2.serialize(&Point { x: 22, y: 33 }).unwrap();
+        stream1.serialize(&BoolStruct { v: true }).unwrap();
+    }
+    assert_eq!(
+        buffer1,
+        include_bytes!("reference/output/point_and_bool_struct.gob").as_ref()
+    );
+    assert_eq!(
+        buffer2,
+        include_bytes!("reference/output/point_struct.gob").as_ref()
+    );
+}
+
+#[test]
+fn shared_schema_rejects_unregistered_types() {
+    let mut stream = StreamSerializer::new_with_buffer();
+    stream.set_shared_schema(Arc::new(Schema::new())).unwrap();
+    assert!(stream.serialize(&Point { x: 22, y: 33 }).is_err());
+}
+
+#[test]
+fn shared_schema_must_be_set_before_types_are_sent() {
+    use serde_schema::SchemaSerialize;
+
+    let mut schema = Schema::new();
+    BoolStruct::schema_register(&mut schema).unwrap();
+    Point::schema_register(&mut schema).unwrap();
+
+    let mut stream = StreamSerializer::new_with_buffer();
+    stream.serialize(&Point { x: 22, y: 33 }).unwrap();
+    // `Point` was sent under the id `BoolStruct` has in the shared schema
+    assert!(stream.set_shared_schema(Arc::new(schema)).is_err());
+    stream.serialize(&Point { x: 22, y: 33 }).unwrap();
+}
+
+#[test]
+fn append_to_existing_stream() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct DecodedPoint {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let mut buffer = include_bytes!("reference/output/point_struct.gob").to_vec();
+    let schema = {
+        let mut stream = StreamDeserializer::new(buffer.as_slice());
+        while stream.skip().unwrap() {}
+        Schema::from_type_definitions(stream.type_definitions())
+    };
+
+    let len_before = buffer.len();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        *stream.schema_mut() = schema;
+        stream.serialize(&Point { x: 1, y: 2 }).unwrap();
+    }
+    // only the value is appended, referring to the existing type id
+    assert_eq!(&buffer[len_before..], &[7, 255, 130, 1, 2, 1, 4, 0]);
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let first = stream.deserialize::<DecodedPoint>().unwrap().unwrap();
+    assert_eq!(first, DecodedPoint { x: 22, y: 33 });
+    let second = stream.deserialize::<DecodedPoint>().unwrap().unwrap();
+    assert_eq!(second, DecodedPoint { x: 1, y: 2 });
+}
+
+#[test]
+fn append_to_existing_stream_with_new_type() {
+    let input = include_bytes!("reference/output/point_struct.gob");
+    let mut stream = StreamDeserializer::new(input.as_ref());
+    while stream.skip().unwrap() {}
+    let schema = Schema::from_type_definitions(stream.type_definitions());
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        *stream.schema_mut() = schema;
+        stream.serialize(&BoolStruct { v: true }).unwrap();
+    }
+    let mut expected = include_bytes!("reference/output/point_and_bool_struct.gob").to_vec();
+    expected.drain(..input.len());
+    assert_eq!(buffer, expected);
+}
+
+#[test]
+fn append_to_existing_stream_with_conflicting_type() {
+    #[derive(Serialize, SchemaSerialize)]
+    #[serde(rename = "Point")]
+    struct Point3 {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+        #[serde(rename = "Z")]
+        z: i64,
+    }
+
+    let input = include_bytes!("reference/output/point_struct.gob");
+    let mut stream = StreamDeserializer::new(input.as_ref());
+    while stream.skip().unwrap() {}
+    let schema = Schema::from_type_definitions(stream.type_definitions());
+
+    let mut stream = StreamSerializer::new_with_buffer();
+    *stream.schema_mut() = schema;
+    assert!(stream.serialize(&Point3 { x: 1, y: 2, z: 3 }).is_err());
+}
+
+#[test]
+fn append_to_existing_stream_with_variants_defined_apart() {
+    #[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+    enum Enum {
+        V1 {
+            #[serde(rename = "Foo")]
+            foo: bool,
+        },
+        V2 {
+            #[serde(rename = "Bar")]
+            bar: i64,
+            #[serde(rename = "Baz")]
+            baz: u64,
+        },
+        V3 {
+            #[serde(rename = "Quux")]
+            quux: String,
+        },
+    }
+
+    // `V2` is defined before `Enum`, and `V1` and `V3` after it
+    let mut buffer = include_bytes!("reference/output/enum_after_variant.gob").to_vec();
+    let schema = {
+        let mut stream = StreamDeserializer::new(buffer.as_slice());
+        while stream.skip().unwrap() {}
+        Schema::from_type_definitions(stream.type_definitions())
+    };
+
+    let len_before = buffer.len();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        *stream.schema_mut() = schema;
+        stream.serialize(&Enum::V2 { bar: 1, baz: 2 }).unwrap();
+    }
+    // only the value is appended, referring to the existing type id
+    assert_eq!(&buffer[len_before..], &[9, 255, 132, 2, 1, 2, 1, 2, 0, 0]);
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    assert!(stream.skip().unwrap());
+    assert_eq!(
+        stream.deserialize::<Enum>().unwrap(),
+        Some(Enum::V2 { bar: 42, baz: 1234 })
+    );
+    assert_eq!(
+        stream.deserialize::<Enum>().unwrap(),
+        Some(Enum::V2 { bar: 1, baz: 2 })
+    );
+}
+
+#[test]
+fn inferred_point_struct() {
+    #[derive(Serialize)]
+    #[serde(rename = "Point")]
+    struct PlainPoint {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .serialize_inferred(&PlainPoint { x: 22, y: 33 })
+            .unwrap();
+    }
+    assert_eq!(
+        buffer,
+        include_bytes!("reference/output/point_struct.gob").as_ref()
+    );
+}
+
+#[test]
+fn inferred_nested_types() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Item {
+        name: String,
+        count: u64,
+        note: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Inventory {
+        items: Vec<Item>,
+        tags: BTreeMap<String, i64>,
+        note: Option<String>,
+    }
+
+    let mut tags = BTreeMap::new();
+    tags.insert("aisle".to_owned(), 4);
+    let inventory = Inventory {
+        items: vec![
+            Item {
+                name: "bolt".to_owned(),
+                count: 12,
+                note: None,
+            },
+            Item {
+                name: "nut".to_owned(),
+                count: 30,
+                note: Some("metric".to_owned()),
+            },
+        ],
+        tags,
+        note: None,
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize_inferred(&inventory).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Inventory = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, inventory);
+}
+
+#[test]
+fn inferred_enum_variants() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Shape {
+        Circle(f64),
+        Square(f64),
+        Rect { w: i64, h: i64 },
+    }
+
+    let shapes = vec![Shape::Rect { w: 2, h: 3 }, Shape::Circle(1.5)];
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize_inferred(&shapes).unwrap();
+        stream
+            .serialize_inferred(&Shape::Rect { w: 4, h: 5 })
+            .unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Vec<Shape> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, shapes);
+    let decoded: Shape = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, Shape::Rect { w: 4, h: 5 });
+}
+
+#[test]
+fn inferred_type_of_empty_sequence() {
+    let mut stream = StreamSerializer::new_with_buffer();
+    let err = stream.serialize_inferred(&Vec::<i64>::new()).unwrap_err();
+    assert!(err.to_string().contains("empty sequence"));
+}
+
+#[test]
+fn inferred_types_must_be_registered_in_shared_schema() {
+    #[derive(Serialize, SchemaSerialize)]
+    struct Point {
+        x: i64,
+        y: i64,
+    }
+
+    let mut schema = Schema::new();
+    schema.register_inferred(&Point { x: 1, y: 2 }).unwrap();
+
+    let mut stream = StreamSerializer::new_with_buffer();
+    stream.set_shared_schema(Arc::new(schema)).unwrap();
+    stream.serialize_inferred(&Point { x: 3, y: 4 }).unwrap();
+    stream.serialize(&Point { x: 5, y: 6 }).unwrap();
+    assert!(stream.serialize_inferred(&vec![1u64]).is_err());
+}
+
+#[test]
+fn newtype_struct() {
+    #[derive(Serialize, SchemaSerialize)]
+    struct Meters(i64);
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&Meters(42)).unwrap();
+        stream.serialize_inferred(&Meters(42)).unwrap();
+    }
+    assert_eq!(buffer, &[3, 4, 0, 84, 3, 4, 0, 84]);
+}
+
+#[test]
+fn homogeneous_tuple_as_array() {
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&(1i64, 2i64, 3i64)).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let array: [i64; 3] = stream.deserialize().unwrap().unwrap();
+    assert_eq!(array, [1, 2, 3]);
+}
+
+#[test]
+fn heterogeneous_tuple_as_struct() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Pair {
+        #[serde(rename = "F0")]
+        f0: i64,
+        #[serde(rename = "F1")]
+        f1: String,
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&(0i64, "zero".to_owned())).unwrap();
+        stream.serialize(&(1i64, "one".to_owned())).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let tuple: (i64, String) = stream.deserialize().unwrap().unwrap();
+    assert_eq!(tuple, (0, "zero".to_owned()));
+    let pair: Pair = stream.deserialize().unwrap().unwrap();
+    assert_eq!(
+        pair,
+        Pair {
+            f0: 1,
+            f1: "one".to_owned(),
+        }
+    );
+}
+
+#[test]
+fn tuple_fields() {
+    #[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+    struct Segment {
+        from: (i64, i64),
+        label: (String, u64),
+    }
+
+    let segment = Segment {
+        from: (0, 7),
+        label: (String::new(), 0),
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&segment).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Segment = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, segment);
+}
+
+#[test]
+fn unit_type_is_rejected() {
+    #[derive(Serialize, SchemaSerialize)]
+    struct Marker {
+        unit: (),
+    }
+
+    let mut buffer = Vec::new();
+    let mut stream = StreamSerializer::new_with_write(&mut buffer);
+    let err = stream.serialize(&Marker { unit: () }).unwrap_err();
+    assert!(err.to_string().contains("unit type"));
+}
+
+/// The bytes which a value marshals itself into.
+fn gob_encoded<T: serde::Serialize + serde_schema::SchemaSerialize>(value: &T) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(value)
+        .unwrap();
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    stream.deserialize::<ByteBuf>().unwrap().unwrap().into()
+}
+
+#[test]
+fn big_numbers() {
+    // as encoded by Go's `GobEncode` methods
+    assert_eq!(gob_encoded(&BigInt::from(0)), [2]);
+    assert_eq!(gob_encoded(&BigInt::from(-300)), [3, 1, 44]);
+    assert_eq!(
+        gob_encoded(&BigRat::new(BigInt::from(1), BigInt::from(-3))),
+        [3, 0, 0, 0, 1, 1, 3]
+    );
+    assert_eq!(
+        gob_encoded(&BigFloat::from_f64(1.5).unwrap()),
+        [1, 10, 0, 0, 0, 53, 0, 0, 0, 1, 0xc0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        gob_encoded(&BigFloat::from_f64(0.0).unwrap()),
+        [1, 8, 0, 0, 0, 53]
+    );
+
+    for &v in &[1.5, -0.0, 0.1, 5e-324, -f64::MAX, f64::INFINITY] {
+        let float = BigFloat::from_f64(v).unwrap();
+        assert_eq!(float.to_f64().to_bits(), v.to_bits());
+    }
+    assert_eq!(BigFloat::from_f64(f64::NAN), None);
+
+    let int = BigInt::from(i128::MIN);
+    assert_eq!(int.to_i128(), Some(i128::MIN));
+    assert_eq!(int.to_u128(), None);
+
+    #[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+    struct Account {
+        #[serde(rename = "Balance")]
+        balance: BigInt,
+        #[serde(rename = "Share")]
+        share: BigRat,
+        #[serde(rename = "Rate")]
+        rate: BigFloat,
+    }
+
+    let account = Account {
+        balance: BigInt::from_bytes_be(true, &[1; 20]),
+        share: BigRat::from(BigInt::from(7)),
+        rate: BigFloat::from_f64(-2.25).unwrap(),
+    };
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&account)
+        .unwrap();
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    assert_eq!(stream.deserialize::<Account>().unwrap(), Some(account));
+}
+
+#[test]
+fn big_numbers_wire_types() {
+    fn serialized<T: serde::Serialize + serde_schema::SchemaSerialize>(value: &T) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        StreamSerializer::new_with_write(&mut buffer)
+            .serialize(value)
+            .unwrap();
+        buffer
+    }
+
+    // defined as GobEncoders, as Go checks
+    for &(ref buffer, name) in &[
+        (serialized(&BigInt::from(1)), "Int"),
+        (serialized(&BigRat::from(BigInt::from(1))), "Rat"),
+        (serialized(&BigFloat::from_f64(1.0).unwrap()), "Float"),
+    ] {
+        let mut message = vec![255, 129, 5, 1, 1, name.len() as u8];
+        message.extend_from_slice(name.as_bytes());
+        message.extend_from_slice(&[1, 255, 130, 0, 0, 0]);
+        assert_eq!(buffer[0] as usize, message.len());
+        assert_eq!(&buffer[1..=message.len()], &message[..]);
+    }
+}
+
+#[test]
+fn go_duration() {
+    let duration = go::Duration(Duration::from_millis(1500));
+    let fixture = include_bytes!("reference/output/go_duration.gob");
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&duration)
+        .unwrap();
+    assert_eq!(buffer, fixture.as_ref());
+    let mut stream = StreamDeserializer::new(fixture.as_ref());
+    assert_eq!(
+        stream.deserialize::<go::Duration>().unwrap(),
+        Some(duration)
+    );
+
+    let too_long = go::Duration(Duration::from_secs(u64::MAX));
+    assert!(StreamSerializer::new_with_buffer()
+        .serialize(&too_long)
+        .is_err());
+}
+
+#[test]
+fn go_time_out_of_range() {
+    let time = go::Time {
+        secs: 0,
+        nanos: 0,
+        offset: None,
+    };
+    for invalid in &[
+        go::Time {
+            nanos: 1_000_000_000,
+            ..time
+        },
+        go::Time {
+            secs: i64::MAX,
+            ..time
+        },
+        // Go would decode it as UTC
+        go::Time {
+            offset: Some(-60),
+            ..time
+        },
+    ] {
+        assert!(StreamSerializer::new_with_buffer()
+            .serialize(invalid)
+            .is_err());
+    }
+}
+
+#[test]
+fn go_ip() {
+    let fixture = include_bytes!("reference/output/go_ip.gob");
+    let v4 = Ipv4Addr::new(10, 0, 0, 1);
+    for &addr in &[IpAddr::V4(v4), IpAddr::V6(v4.to_ipv6_mapped())] {
+        let mut buffer = Vec::new();
+        StreamSerializer::new_with_write(&mut buffer)
+            .serialize(&go::Ip(addr))
+            .unwrap();
+        assert_eq!(buffer, fixture.as_ref());
+    }
+    let mut stream = StreamDeserializer::new(fixture.as_ref());
+    assert_eq!(
+        stream.deserialize::<go::Ip>().unwrap(),
+        Some(go::Ip(IpAddr::V4(v4)))
+    );
+
+    let v6 = go::Ip("2001:db8::1".parse().unwrap());
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&v6)
+        .unwrap();
+    assert!(buffer.ends_with(b"2001:db8::1"));
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    assert_eq!(stream.deserialize::<go::Ip>().unwrap(), Some(v6));
+}
+
+#[cfg(feature = "uuid")]
+#[test]
+fn go_uuid() {
+    let uuid = uuid::Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+    let fixture = include_bytes!("reference/output/go_uuid.gob");
+
+    let mut buffer = Vec::new();
+    StreamSerializer::new_with_write(&mut buffer)
+        .serialize(&go::Uuid(uuid))
+        .unwrap();
+    assert_eq!(buffer, fixture.as_ref());
+    let mut stream = StreamDeserializer::new(fixture.as_ref());
+    assert_eq!(
+        stream.deserialize::<go::Uuid>().unwrap(),
+        Some(go::Uuid(uuid))
+    );
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn num_bigint_conversions() {
+    let int = num_bigint::BigInt::from(-1i64) << 100usize;
+    let ratio = num_rational::BigRational::new(int.clone(), num_bigint::BigInt::from(-6i64));
+    assert_eq!(num_bigint::BigInt::from(BigInt::from(int.clone())), int);
+    assert_eq!(
+        num_rational::BigRational::from(BigRat::from(ratio.clone())),
+        ratio
+    );
+    assert_eq!(
+        gob_encoded(&BigInt::from(int)),
+        gob_encoded(&BigInt::from_bytes_be(true, &(1u128 << 100).to_be_bytes()))
+    );
+}
+
+#[test]
+fn wide_integers() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Ledger {
+        #[serde(rename = "Debit")]
+        debit: i128,
+        #[serde(rename = "Credit")]
+        credit: u128,
+    }
+
+    let ledger = Ledger {
+        debit: i128::MIN,
+        credit: u128::MAX,
+    };
+    assert!(StreamSerializer::new_with_buffer()
+        .serialize_inferred(&ledger)
+        .is_err());
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.schema_mut().set_wide_integers(WideIntegers::BigInt);
+        stream.serialize_inferred(&ledger).unwrap();
+        stream.serialize_inferred(&-5i128).unwrap();
+        stream.serialize_inferred(&-5i128).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    assert_eq!(stream.deserialize::<Ledger>().unwrap(), Some(ledger));
+    assert_eq!(
+        stream.deserialize::<BigInt>().unwrap(),
+        Some(BigInt::from(-5))
+    );
+    assert!(stream.deserialize::<u128>().is_err());
+}
+
+#[test]
+fn go_compatibility() {
+    #[derive(Serialize, SchemaSerialize)]
+    struct User {
+        id: u64,
+        name: String,
+    }
+
+    #[derive(Serialize, SchemaSerialize)]
+    struct Index {
+        #[serde(rename = "Paths")]
+        paths: BTreeMap<Vec<String>, u64>,
+    }
+
+    let user = User {
+        id: 1,
+        name: "gopher".to_owned(),
+    };
+    let index = Index {
+        paths: vec![(vec!["usr".to_owned()], 1)].into_iter().collect(),
+    };
+    let mut stream = StreamSerializer::new_with_buffer();
+    stream
+        .schema_mut()
+        .set_go_compatibility(GoCompatibility::Report);
+    stream.serialize(&user).unwrap();
+    stream.serialize(&index).unwrap();
+    assert_eq!(
+        stream.schema_mut().go_compatibility_issues(),
+        &[
+            GoIssue::UnexportedField {
+                type_name: "User".to_owned(),
+                field_name: "id".to_owned(),
+            },
+            GoIssue::UnexportedField {
+                type_name: "User".to_owned(),
+                field_name: "name".to_owned(),
+            },
+            GoIssue::NoExportedFields {
+                type_name: "User".to_owned(),
+            },
+            GoIssue::IncomparableMapKey {
+                key_type: "[]string".to_owned(),
+            },
+        ][..]
+    );
+
+    // nothing is written for rejected types
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream
+            .schema_mut()
+            .set_go_compatibility(GoCompatibility::Reject);
+        assert!(stream.serialize(&user).is_err());
+        assert!(stream.serialize(&index).is_err());
+    }
+    assert!(buffer.is_empty());
+
+    let mut stream = StreamSerializer::new_with_buffer();
+    stream
+        .schema_mut()
+        .set_go_compatibility(GoCompatibility::Reject);
+    stream
+        .schema_mut()
+        .set_field_naming(FieldNaming::PascalCase);
+    stream.serialize(&user).unwrap();
+    assert!(stream.schema_mut().go_compatibility_issues().is_empty());
+}
+
+#[test]
+fn go_type_order() {
+    #[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+    struct Point {
+        #[serde(rename = "X")]
+        x: i64,
+        #[serde(rename = "Y")]
+        y: i64,
+    }
+
+    #[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+    struct Shape {
+        #[serde(rename = "Name")]
+        name: String,
+        #[serde(rename = "Points")]
+        points: Vec<Point>,
+        #[serde(rename = "Tags")]
+        tags: Vec<String>,
+    }
+
+    let shape = Shape {
+        name: "tri".to_owned(),
+        points: vec![Point { x: 1, y: 2 }],
+        tags: vec!["a".to_owned()],
+    };
+
+    let fixture = include_bytes!("reference/output/go_type_order.gob");
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.set_go_type_order("main").unwrap();
+        stream.serialize(&shape).unwrap();
+        // the types would be sent again under new ids
+        assert!(stream.set_go_type_order("main").is_err());
+    }
+    assert_eq!(buffer, fixture.as_ref());
+
+    let mut stream = StreamSerializer::new_with_buffer();
+    stream.serialize(&shape).unwrap();
+    assert!(stream.set_go_type_order("main").is_err());
+
+    let mut stream = StreamDeserializer::new(fixture.as_ref());
+    assert_eq!(stream.deserialize::<Shape>().unwrap(), Some(shape));
+}
+
+#[test]
+fn set_as_map_of_empty_struct() {
+    let set: BTreeSet<String> = vec!["bar".to_owned(), "foo".to_owned()]
+        .into_iter()
+        .collect();
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&StructMapSet(set.clone())).unwrap();
+        stream.serialize(&StructMapSet(set.clone())).unwrap();
+    }
+
+    // the element type is Go's empty struct
+    assert!(buffer.windows(9).any(|w| w == b"struct {}"));
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: StructMapSet<BTreeSet<String>> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded.0, set);
+    let decoded: HashSet<String> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, set.iter().cloned().collect());
+}
+
+#[test]
+fn set_as_map_of_bool() {
+    let set: HashSet<i64> = vec![3, 5].into_iter().collect();
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&BoolMapSet(set.clone())).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: BTreeMap<i64, bool> = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, vec![(3, true), (5, true)].into_iter().collect());
+}
+
+#[test]
+fn set_fields() {
+    #[derive(Serialize, Deserialize, SchemaSerialize, Debug, PartialEq)]
+    struct Tags {
+        #[serde(rename = "Names")]
+        names: StructMapSet<BTreeSet<String>>,
+        #[serde(rename = "Ids")]
+        ids: BoolMapSet<BTreeSet<u64>>,
+    }
+
+    let tags = Tags {
+        names: StructMapSet(vec!["a".to_owned()].into_iter().collect()),
+        ids: BoolMapSet(vec![7].into_iter().collect()),
+    };
+
+    let mut buffer = Vec::new();
+    {
+        let mut stream = StreamSerializer::new_with_write(&mut buffer);
+        stream.serialize(&tags).unwrap();
+    }
+
+    let mut stream = StreamDeserializer::new(buffer.as_slice());
+    let decoded: Tags = stream.deserialize().unwrap().unwrap();
+    assert_eq!(decoded, tags);
+}
+
 #[test]
 fn enum_with_newtype_variants_and_external_tags() {
     #[derive(Serialize, SchemaSerialize)]
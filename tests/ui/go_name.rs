@@ -0,0 +1,13 @@
+extern crate gob;
+#[macro_use]
+extern crate gob_derive;
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Serialize, GobSchema)]
+struct Point {
+    #[gob(go_name = "X")]
+    x: i64,
+}
+
+fn main() {}
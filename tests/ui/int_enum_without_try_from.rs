@@ -0,0 +1,15 @@
+extern crate gob;
+#[macro_use]
+extern crate gob_derive;
+#[macro_use]
+extern crate serde_derive;
+
+#[derive(Serialize, Deserialize, GobSchema, Clone, Copy)]
+#[serde(into = "i64")]
+#[gob(repr = "int")]
+enum Color {
+    Red,
+    Green,
+}
+
+fn main() {}